@@ -1,6 +1,13 @@
+use crate::parser::{
+    render_with, LintIssue, LintResult, LintSeverity, OutputParser, ParseResult, TestFailure,
+    TestResult, TokenFormatter,
+};
+use crate::stream::{self, StreamFilter, StreamOutput};
+use crate::test_diff;
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::process::Command;
@@ -27,15 +34,95 @@ pub fn run(cmd: CargoCommand, args: &[String], verbose: u8) -> Result<()> {
     }
 }
 
+/// Final text and parse tier for a finished cargo run, given the verbosity
+type Render<'a> = &'a dyn Fn(&StreamOutput, u8) -> (String, Option<u8>);
+
+/// Text from the stream filter as-is, for subcommands without a parser
+fn render_filtered(output: &StreamOutput, _verbose: u8) -> (String, Option<u8>) {
+    (output.flushed.clone(), None)
+}
+
+/// Parse the raw output with `P`; the stream filter's text stays the Compact rendering
+fn render_parsed<P>(tool: &str, output: &StreamOutput, verbose: u8) -> (String, Option<u8>)
+where
+    P: OutputParser,
+    P::Output: TokenFormatter + Serialize,
+{
+    let result = P::parse(&output.raw);
+    let tier = result.tier();
+    json_cut_notice(output);
+    let text = render_with(tool, result, verbose, || output.flushed.clone());
+    (text, Some(tier))
+}
+
+/// `cargo test`: like [`render_parsed`], with Compact text reduced to the
+/// failures that changed since the previous run of `command`
+fn render_test(command: &str, output: &StreamOutput, verbose: u8) -> (String, Option<u8>) {
+    let result = CargoTestParser::parse(&output.raw);
+    let tier = result.tier();
+    json_cut_notice(output);
+    let current = result.data().map(|report| report.workspace.clone());
+    let diff = test_diff::record(command, current.as_ref());
+    let text = render_with("cargo test", result, verbose, || {
+        let text = test_diff::compact("cargo test", current.as_ref(), diff, output.flushed.clone());
+        // Keep TestStream's separator from the live failure lines
+        if output.flushed.starts_with('\n') && !text.starts_with('\n') {
+            format!("\n{}", text)
+        } else {
+            text
+        }
+    });
+    (text, Some(tier))
+}
+
+/// Under `--format json` the cut-off notice goes to stderr, outside the document
+fn json_cut_notice(output: &StreamOutput) {
+    if let (true, Some(cut)) = (crate::parser::json_output(), output.cut_off) {
+        eprintln!("{}", cut.notice());
+    }
+}
+
 /// Generic cargo command runner with filtering
-fn run_cargo_filtered<F>(subcommand: &str, args: &[String], verbose: u8, filter_fn: F) -> Result<()>
+fn run_cargo_filtered<F>(
+    subcommand: &str,
+    args: &[String],
+    verbose: u8,
+    filter_fn: F,
+    render: Render,
+) -> Result<()>
 where
     F: Fn(&str) -> String,
 {
+    run_cargo_streamed(
+        subcommand,
+        args,
+        verbose,
+        &mut stream::Batch(filter_fn),
+        render,
+    )
+}
+
+/// Subcommands whose diagnostics are requested as JSON (`--message-format=json`)
+const JSON_DIAGNOSTIC_SUBCOMMANDS: [&str; 3] = ["build", "check", "clippy"];
+
+/// Cargo command runner that streams output through `filter` while cargo runs
+fn run_cargo_streamed(
+    subcommand: &str,
+    args: &[String],
+    verbose: u8,
+    filter: &mut dyn StreamFilter,
+    render: Render,
+) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     let mut cmd = Command::new("cargo");
     cmd.arg(subcommand);
+    // Machine-readable diagnostics, unless the caller picked a format
+    if JSON_DIAGNOSTIC_SUBCOMMANDS.contains(&subcommand)
+        && !args.iter().any(|a| a.starts_with("--message-format"))
+    {
+        cmd.arg("--message-format=json");
+    }
     for arg in args {
         cmd.arg(arg);
     }
@@ -44,34 +131,39 @@ where
         eprintln!("Running: cargo {} {}", subcommand, args.join(" "));
     }
 
-    let output = cmd
-        .output()
-        .with_context(|| format!("Failed to run cargo {}", subcommand))?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    // Live lines would corrupt a JSON document on stdout
+    let mut quiet = stream::Batch(|_: &str| String::new());
+    let filter: &mut dyn StreamFilter = if crate::parser::json_output() {
+        &mut quiet
+    } else {
+        filter
+    };
 
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    let filtered = filter_fn(&raw);
+    let output = stream::run(&mut cmd, filter)
+        .with_context(|| format!("Failed to run cargo {}", subcommand))?;
+    let exit_code = output.exit_code;
+    let (text, tier) = render(&output, verbose);
+    // What cargo would have printed without --message-format=json
+    let human = rendered_output(&output.raw);
+
+    println!(
+        "{}",
+        crate::tee::tee_and_link(&human, &format!("cargo_{}", subcommand), exit_code, &text)
+    );
 
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, &format!("cargo_{}", subcommand), exit_code)
-    {
-        println!("{}\n{}", filtered, hint);
-    } else {
-        println!("{}", filtered);
+    let mut context = tracking::RunContext::exited(exit_code);
+    if let Some(tier) = tier {
+        context = context.with_tier(tier);
     }
-
-    timer.track(
+    timer.track_with(
         &format!("cargo {} {}", subcommand, args.join(" ")),
         &format!("rtk cargo {} {}", subcommand, args.join(" ")),
-        &raw,
-        &filtered,
+        &human,
+        &format!("{}{}", output.live, text),
+        context,
     );
 
-    if !output.status.success() {
+    if exit_code != 0 {
         std::process::exit(exit_code);
     }
 
@@ -79,27 +171,52 @@ where
 }
 
 fn run_build(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("build", args, verbose, filter_cargo_build)
+    run_cargo_filtered("build", args, verbose, filter_cargo_build, &|out, v| {
+        render_parsed::<CargoDiagnosticsParser>("cargo build", out, v)
+    })
 }
 
 fn run_test(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("test", args, verbose, filter_cargo_test)
+    let command = format!("cargo test {}", args.join(" "));
+    run_cargo_streamed(
+        "test",
+        args,
+        verbose,
+        &mut TestStream::default(),
+        &|out, v| render_test(&command, out, v),
+    )
 }
 
 fn run_clippy(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("clippy", args, verbose, filter_cargo_clippy)
+    run_cargo_filtered("clippy", args, verbose, filter_cargo_clippy, &|out, v| {
+        render_parsed::<CargoDiagnosticsParser>("cargo clippy", out, v)
+    })
 }
 
 fn run_check(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("check", args, verbose, filter_cargo_build)
+    run_cargo_filtered("check", args, verbose, filter_cargo_build, &|out, v| {
+        render_parsed::<CargoDiagnosticsParser>("cargo check", out, v)
+    })
 }
 
 fn run_install(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("install", args, verbose, filter_cargo_install)
+    run_cargo_filtered(
+        "install",
+        args,
+        verbose,
+        filter_cargo_install,
+        &render_filtered,
+    )
 }
 
 fn run_nextest(args: &[String], verbose: u8) -> Result<()> {
-    run_cargo_filtered("nextest", args, verbose, filter_cargo_nextest)
+    run_cargo_filtered(
+        "nextest",
+        args,
+        verbose,
+        filter_cargo_nextest,
+        &render_filtered,
+    )
 }
 
 /// Format crate name + version into a display string
@@ -114,7 +231,7 @@ fn format_crate_info(name: &str, version: &str, fallback: &str) -> String {
 }
 
 /// Filter cargo install output - strip dep compilation, keep installed/replaced/errors
-fn filter_cargo_install(output: &str) -> String {
+pub(crate) fn filter_cargo_install(output: &str) -> String {
     let mut errors: Vec<String> = Vec::new();
     let mut error_count = 0;
     let mut compiled = 0;
@@ -301,7 +418,7 @@ fn flush_failure_block(header: &mut String, body: &mut Vec<String>, failures: &m
 }
 
 /// Filter cargo nextest output - show failures + compact summary
-fn filter_cargo_nextest(output: &str) -> String {
+pub(crate) fn filter_cargo_nextest(output: &str) -> String {
     static SUMMARY_RE: OnceLock<regex::Regex> = OnceLock::new();
     let summary_re = SUMMARY_RE.get_or_init(|| {
         regex::Regex::new(
@@ -519,8 +636,307 @@ fn filter_cargo_nextest(output: &str) -> String {
     String::new()
 }
 
+/// One line of `cargo --message-format=json` output
+#[derive(Debug, Deserialize)]
+struct CargoMessage {
+    reason: String,
+    message: Option<RustcDiagnostic>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RustcDiagnostic {
+    message: String,
+    level: String,
+    code: Option<DiagnosticCode>,
+    #[serde(default)]
+    spans: Vec<DiagnosticSpan>,
+    #[serde(default)]
+    children: Vec<RustcDiagnostic>,
+    rendered: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiagnosticCode {
+    code: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct DiagnosticSpan {
+    file_name: String,
+    line_start: usize,
+    column_start: usize,
+    is_primary: bool,
+    label: Option<String>,
+    suggested_replacement: Option<String>,
+}
+
+/// A rustc/clippy diagnostic reduced to its primary span and suggested fix
+#[derive(Debug, Clone, PartialEq)]
+struct CargoDiagnostic {
+    is_error: bool,
+    /// Lint or error code (`clippy::needless_return`, `E0308`), empty if none
+    code: String,
+    message: String,
+    file: String,
+    line: usize,
+    column: usize,
+    label: Option<String>,
+    suggestion: Option<String>,
+}
+
+impl CargoDiagnostic {
+    fn from_rustc(diag: &RustcDiagnostic) -> Option<Self> {
+        let is_error = match diag.level.as_str() {
+            "error" => true,
+            "warning" => false,
+            _ => return None,
+        };
+        let primary = diag.spans.iter().find(|s| s.is_primary);
+        // Summary messages ("aborting due to ...", "2 warnings emitted")
+        if primary.is_none()
+            && (diag.message.starts_with("aborting due to") || diag.message.ends_with("emitted"))
+        {
+            return None;
+        }
+
+        // First help whose spans carry a replacement: "prefix it with an underscore: `_x`"
+        let suggestion = diag.children.iter().find_map(|child| {
+            let fixes: Vec<String> = child
+                .spans
+                .iter()
+                .filter_map(|s| s.suggested_replacement.as_deref())
+                .filter(|r| !r.is_empty())
+                .map(|r| format!("`{}`", truncate(r, 60)))
+                .collect();
+            let has_fix = child
+                .spans
+                .iter()
+                .any(|s| s.suggested_replacement.is_some());
+            match (has_fix, fixes.is_empty()) {
+                (false, _) => None,
+                (true, true) => Some(child.message.clone()),
+                (true, false) => Some(format!("{}: {}", child.message, fixes.join(", "))),
+            }
+        });
+
+        Some(CargoDiagnostic {
+            is_error,
+            code: diag
+                .code
+                .as_ref()
+                .map(|c| c.code.clone())
+                .unwrap_or_default(),
+            message: diag.message.clone(),
+            file: primary.map(|s| s.file_name.clone()).unwrap_or_default(),
+            line: primary.map(|s| s.line_start).unwrap_or(0),
+            column: primary.map(|s| s.column_start).unwrap_or(0),
+            label: primary.and_then(|s| s.label.clone()),
+            suggestion,
+        })
+    }
+
+    /// Grouping key: the code, or the message for uncoded diagnostics
+    fn key(&self) -> &str {
+        if self.code.is_empty() {
+            &self.message
+        } else {
+            &self.code
+        }
+    }
+
+    fn location(&self) -> String {
+        if self.file.is_empty() {
+            "(no location)".to_string()
+        } else {
+            format!("{}:{}:{}", self.file, self.line, self.column)
+        }
+    }
+}
+
+/// Diagnostics from `--message-format=json` output, deduplicated (the same
+/// warning is reported once per target). `None` if the output has no JSON
+/// messages, i.e. cargo ran with human diagnostics.
+fn json_diagnostics(output: &str) -> Option<Vec<CargoDiagnostic>> {
+    let mut seen_json = false;
+    let mut diags: Vec<CargoDiagnostic> = Vec::new();
+    for line in output.lines().filter(|l| l.starts_with('{')) {
+        let msg: CargoMessage = match serde_json::from_str(line) {
+            Ok(m) => m,
+            Err(_) => continue,
+        };
+        seen_json = true;
+        if msg.reason != "compiler-message" {
+            continue;
+        }
+        if let Some(diag) = msg.message.as_ref().and_then(CargoDiagnostic::from_rustc) {
+            if !diags.contains(&diag) {
+                diags.push(diag);
+            }
+        }
+    }
+    seen_json.then_some(diags)
+}
+
+/// Raw output as cargo and libtest print it by default: JSON diagnostics
+/// replaced by their human rendering, libtest JSON events by libtest's text
+/// format (so `rtk tee show --section cargo-test:<name>` still works), other
+/// JSON records dropped
+fn rendered_output(output: &str) -> String {
+    let mut human = String::with_capacity(output.len());
+    // (name, captured output) of the current suite's failing tests
+    let mut failures: Vec<(String, String)> = Vec::new();
+    for line in output.lines() {
+        if line.starts_with('{') {
+            if let Ok(msg) = serde_json::from_str::<CargoMessage>(line) {
+                if let Some(rendered) = msg.message.and_then(|m| m.rendered) {
+                    human.push_str(&rendered);
+                }
+                continue;
+            }
+            if let Ok(event) = serde_json::from_str::<LibtestEvent>(line) {
+                render_libtest_event(&event, &mut failures, &mut human);
+                continue;
+            }
+        }
+        human.push_str(line);
+        human.push('\n');
+    }
+    human
+}
+
+/// Append libtest's text rendering of one JSON event
+fn render_libtest_event(
+    event: &LibtestEvent,
+    failures: &mut Vec<(String, String)>,
+    out: &mut String,
+) {
+    let name = event.name.as_deref().unwrap_or_default();
+    match (event.kind.as_str(), event.event.as_str()) {
+        ("suite", "started") => {
+            let count = event.test_count.unwrap_or(0);
+            let plural = if count == 1 { "" } else { "s" };
+            out.push_str(&format!("\nrunning {} test{}\n", count, plural));
+        }
+        ("suite", status) => {
+            if !failures.is_empty() {
+                out.push_str("\nfailures:\n\n");
+                for (name, stdout) in failures.iter() {
+                    out.push_str(&format!(
+                        "---- {} stdout ----\n{}\n\n",
+                        name,
+                        stdout.trim_matches('\n')
+                    ));
+                }
+                out.push_str("\nfailures:\n");
+                for (name, _) in failures.drain(..) {
+                    out.push_str(&format!("    {}\n", name));
+                }
+            }
+            out.push_str(&format!(
+                "\ntest result: {}. {} passed; {} failed; {} ignored; {} measured; {} filtered out; finished in {:.2}s\n\n",
+                if status == "ok" { "ok" } else { "FAILED" },
+                event.passed,
+                event.failed,
+                event.ignored,
+                event.measured,
+                event.filtered_out,
+                event.exec_time.unwrap_or(0.0)
+            ));
+        }
+        ("test", "ok") => out.push_str(&format!("test {} ... ok\n", name)),
+        ("test", "ignored") => out.push_str(&format!("test {} ... ignored\n", name)),
+        ("test", "failed") => {
+            out.push_str(&format!("test {} ... FAILED\n", name));
+            failures.push((name.to_string(), event.stdout.clone().unwrap_or_default()));
+        }
+        ("test", "timeout") => out.push_str(&format!(
+            "test {} has been running for over 60 seconds\n",
+            name
+        )),
+        _ => {}
+    }
+}
+
+/// Compact listing of JSON diagnostics: grouped by code (errors first, then by
+/// frequency), one line per primary span sorted by file, with the suggested fix
+fn format_diagnostics(diags: &[CargoDiagnostic]) -> String {
+    let mut groups: Vec<(&str, Vec<&CargoDiagnostic>)> = Vec::new();
+    for diag in diags {
+        match groups.iter_mut().find(|(key, _)| *key == diag.key()) {
+            Some((_, members)) => members.push(diag),
+            None => groups.push((diag.key(), vec![diag])),
+        }
+    }
+    groups.sort_by_key(|(_, members)| {
+        (
+            !members.iter().any(|d| d.is_error),
+            std::cmp::Reverse(members.len()),
+        )
+    });
+
+    let mut result = String::new();
+    for (key, members) in groups.iter_mut().take(15) {
+        members.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+        let first = members[0];
+        if first.code.is_empty() {
+            result.push_str(&format!("{} ({}x)\n", key, members.len()));
+        } else {
+            result.push_str(&format!(
+                "{} ({}x): {}\n",
+                key,
+                members.len(),
+                first.message
+            ));
+        }
+        for diag in members.iter().take(5) {
+            let mut line = format!("  {}", diag.location());
+            if let Some(label) = &diag.label {
+                line.push_str(&format!(" {}", label));
+            } else if diag.message != first.message {
+                line.push_str(&format!(" {}", diag.message));
+            }
+            if let Some(fix) = &diag.suggestion {
+                line.push_str(&format!(" → {}", fix));
+            }
+            result.push_str(&line);
+            result.push('\n');
+        }
+        if members.len() > 5 {
+            result.push_str(&format!("  ... +{} more\n", members.len() - 5));
+        }
+    }
+    if groups.len() > 15 {
+        result.push_str(&format!("\n... +{} more codes\n", groups.len() - 15));
+    }
+    result
+}
+
 /// Filter cargo build/check output - strip "Compiling"/"Checking" lines, keep errors + summary
-fn filter_cargo_build(output: &str) -> String {
+pub(crate) fn filter_cargo_build(output: &str) -> String {
+    if let Some(diags) = json_diagnostics(output) {
+        let compiled = output
+            .lines()
+            .filter(|l| {
+                let t = l.trim_start();
+                t.starts_with("Compiling") || t.starts_with("Checking")
+            })
+            .count();
+        if diags.is_empty() {
+            return format!("✓ cargo build ({} crates compiled)", compiled);
+        }
+        let errors = diags.iter().filter(|d| d.is_error).count();
+        return format!(
+            "cargo build: {} errors, {} warnings ({} crates)\n\
+             ═══════════════════════════════════════\n{}",
+            errors,
+            diags.len() - errors,
+            compiled,
+            format_diagnostics(&diags)
+        )
+        .trim()
+        .to_string();
+    }
+
     let mut errors: Vec<String> = Vec::new();
     let mut warnings = 0;
     let mut error_count = 0;
@@ -708,12 +1124,79 @@ impl AggregatedTestResult {
     }
 }
 
+/// Streaming filter for `cargo test`: reports each failing test as soon as
+/// libtest prints it, then flushes the [`filter_cargo_test`] summary (or the
+/// per-crate report, for libtest JSON).
+#[derive(Default)]
+struct TestStream {
+    failed: usize,
+}
+
+impl StreamFilter for TestStream {
+    fn feed_line(&mut self, line: &str) -> Vec<String> {
+        let failed = match serde_json::from_str::<LibtestEvent>(line) {
+            Ok(event) if event.kind == "test" && event.event == "failed" => event.name,
+            Ok(_) => None,
+            Err(_) => line
+                .strip_prefix("test ")
+                .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+                .map(str::to_string),
+        };
+        match failed {
+            Some(name) => {
+                self.failed += 1;
+                vec![format!("FAILED {}", name)]
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn finish(&mut self, raw: &str) -> String {
+        let summary = match libtest_events(raw) {
+            Some(events) => {
+                CargoTestReport::from_suites(tally_events(&events), &test_binary_crates(raw))
+                    .format_compact()
+            }
+            None => filter_cargo_test(raw),
+        };
+        if self.failed > 0 {
+            // Separate the live failure list from the flushed details
+            format!("\n{}", summary)
+        } else {
+            summary
+        }
+    }
+}
+
+/// What `rtk cargo test` prints for captured test output
+pub(crate) fn replay_test(raw: &str) -> String {
+    stream::replay(&mut TestStream::default(), raw)
+}
+
 /// Filter cargo test output - show failures + summary only
 fn filter_cargo_test(output: &str) -> String {
     let mut failures: Vec<String> = Vec::new();
     let mut summary_lines: Vec<String> = Vec::new();
     let mut in_failure_section = false;
+    let mut in_failure_list = false;
     let mut current_failure = Vec::new();
+    // Crate of each failure / summary line, shown when the run spans several crates
+    let crates = test_binary_crates(output);
+    let multi_crate = crates
+        .iter()
+        .collect::<std::collections::HashSet<_>>()
+        .len()
+        > 1;
+    let mut suite = 0;
+    let mut failure_crates: Vec<Option<&str>> = Vec::new();
+    let mut summary_crates: Vec<Option<&str>> = Vec::new();
+    let crate_label = |suite: usize| -> Option<&str> {
+        if multi_crate && suite > 0 {
+            crates.get(suite - 1).map(String::as_str)
+        } else {
+            None
+        }
+    };
 
     for line in output.lines() {
         // Skip compilation lines
@@ -726,12 +1209,24 @@ fn filter_cargo_test(output: &str) -> String {
         }
 
         // Skip "running N tests" and individual "test ... ok" lines
-        if line.starts_with("running ") || (line.starts_with("test ") && line.ends_with("... ok")) {
+        if line.starts_with("running ") {
+            suite += 1;
+            continue;
+        }
+        if line.starts_with("test ") && line.ends_with("... ok") {
             continue;
         }
 
-        // Detect failures section
+        // Detect failures section; a second heading introduces the name list
         if line == "failures:" {
+            if in_failure_section {
+                in_failure_list = true;
+                if !current_failure.is_empty() {
+                    failures.push(current_failure.join("\n"));
+                    failure_crates.push(crate_label(suite));
+                    current_failure.clear();
+                }
+            }
             in_failure_section = true;
             continue;
         }
@@ -739,11 +1234,19 @@ fn filter_cargo_test(output: &str) -> String {
         if in_failure_section {
             if line.starts_with("test result:") {
                 in_failure_section = false;
+                in_failure_list = false;
                 summary_lines.push(line.to_string());
+                summary_crates.push(crate_label(suite));
+                continue;
+            } else if in_failure_list {
+                // Names repeated from the blocks above
             } else if line.starts_with("    ") || line.starts_with("---- ") {
                 current_failure.push(line.to_string());
+            } else if line.trim().is_empty() && current_failure.len() == 1 {
+                // Newer libtest: blank line between the header and the output
             } else if line.trim().is_empty() && !current_failure.is_empty() {
                 failures.push(current_failure.join("\n"));
+                failure_crates.push(crate_label(suite));
                 current_failure.clear();
             } else if !line.trim().is_empty() {
                 current_failure.push(line.to_string());
@@ -753,11 +1256,13 @@ fn filter_cargo_test(output: &str) -> String {
         // Capture test result summary
         if !in_failure_section && line.starts_with("test result:") {
             summary_lines.push(line.to_string());
+            summary_crates.push(crate_label(suite));
         }
     }
 
     if !current_failure.is_empty() {
         failures.push(current_failure.join("\n"));
+        failure_crates.push(crate_label(suite));
     }
 
     let mut result = String::new();
@@ -800,16 +1305,48 @@ fn filter_cargo_test(output: &str) -> String {
         result.push_str(&format!("FAILURES ({}):\n", failures.len()));
         result.push_str("═══════════════════════════════════════\n");
         for (i, failure) in failures.iter().enumerate().take(10) {
-            result.push_str(&format!("{}. {}\n", i + 1, truncate(failure, 200)));
+            let shown = truncate(failure, 200);
+            let label = match failure_crates[i] {
+                Some(krate) => format!("[{}] ", krate),
+                None => String::new(),
+            };
+            match failure_test_name(failure) {
+                Some(name) if shown != *failure => result.push_str(&format!(
+                    "{}. {}{}{}\n",
+                    i + 1,
+                    label,
+                    shown,
+                    crate::tee::section_ref(&format!("cargo-test:{}", name))
+                )),
+                _ => result.push_str(&format!("{}. {}{}\n", i + 1, label, shown)),
+            }
         }
         if failures.len() > 10 {
             result.push_str(&format!("\n... +{} more failures\n", failures.len() - 10));
+            for name in failures
+                .iter()
+                .skip(10)
+                .filter_map(|f| failure_test_name(f))
+            {
+                result.push_str(&format!(
+                    "  {}{}\n",
+                    name,
+                    crate::tee::section_ref(&format!("cargo-test:{}", name))
+                ));
+            }
         }
         result.push('\n');
     }
 
-    for line in &summary_lines {
-        result.push_str(&format!("{}\n", line));
+    for (line, krate) in summary_lines.iter().zip(&summary_crates) {
+        // Suites without tests (typically doc-tests) are noise next to failures
+        if line.contains(" 0 passed; 0 failed; 0 ignored;") {
+            continue;
+        }
+        match krate {
+            Some(krate) => result.push_str(&format!("{}: {}\n", krate, line)),
+            None => result.push_str(&format!("{}\n", line)),
+        }
     }
 
     if result.trim().is_empty() {
@@ -826,8 +1363,673 @@ fn filter_cargo_test(output: &str) -> String {
     result.trim().to_string()
 }
 
+/// Test name from a libtest failure block header (`---- name stdout ----`)
+fn failure_test_name(failure: &str) -> Option<&str> {
+    failure
+        .lines()
+        .next()?
+        .strip_prefix("---- ")?
+        .strip_suffix(" stdout ----")
+}
+
+/// Raw failure output of one test, for `rtk tee show --section cargo-test:<name>`.
+pub fn extract_test_section(output: &str, name: &str) -> Option<String> {
+    let header = format!("---- {} stdout ----", name);
+    let mut lines = output.lines().skip_while(|l| *l != header);
+    let mut section = vec![lines.next()?];
+    section.extend(lines.take_while(|l| !l.starts_with("---- ") && *l != "failures:"));
+    Some(section.join("\n").trim_end().to_string())
+}
+
+/// Tests at least this slow are flagged in `cargo test` reports
+const SLOW_TEST_SECS: f64 = 1.0;
+
+/// One event of libtest's JSON output (`-- -Z unstable-options --format json`)
+#[derive(Debug, Deserialize)]
+struct LibtestEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    event: String,
+    name: Option<String>,
+    stdout: Option<String>,
+    exec_time: Option<f64>,
+    test_count: Option<usize>,
+    #[serde(default)]
+    passed: usize,
+    #[serde(default)]
+    failed: usize,
+    #[serde(default)]
+    ignored: usize,
+    #[serde(default)]
+    measured: usize,
+    #[serde(default)]
+    filtered_out: usize,
+}
+
+/// libtest JSON events in the output; `None` if libtest printed its human format
+fn libtest_events(output: &str) -> Option<Vec<LibtestEvent>> {
+    let events: Vec<LibtestEvent> = output
+        .lines()
+        .filter(|l| l.starts_with('{'))
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    (!events.is_empty()).then_some(events)
+}
+
+/// Crate of each test binary, in the order cargo runs them, from cargo's
+/// `Running unittests src/lib.rs (target/debug/deps/foo-1a2b3c)` and
+/// `Doc-tests foo` lines. Cargo runs a package's unit tests before its
+/// integration tests, so those go to the preceding unit-test crate.
+fn test_binary_crates(output: &str) -> Vec<String> {
+    let mut crates: Vec<String> = Vec::new();
+    let mut unit_crate: Option<String> = None;
+    for line in output.lines().map(str::trim_start) {
+        if let Some(name) = line.strip_prefix("Doc-tests ") {
+            crates.push(name.trim().to_string());
+        } else if let Some(rest) = line.strip_prefix("Running ") {
+            let binary = match rest.rsplit_once(" (") {
+                Some((_, path)) => {
+                    let stem = path
+                        .trim_end_matches(')')
+                        .rsplit(['/', '\\'])
+                        .next()
+                        .unwrap_or_default();
+                    stem.rsplit_once('-').map(|(s, _)| s).unwrap_or(stem)
+                }
+                None => rest,
+            };
+            if rest.starts_with("unittests ") {
+                unit_crate = Some(binary.to_string());
+            }
+            crates.push(unit_crate.clone().unwrap_or_else(|| binary.to_string()));
+        }
+    }
+    crates
+}
+
+/// Results of one test binary run, before grouping by crate
+#[derive(Debug, Default)]
+struct SuiteTally {
+    passed: usize,
+    failed: usize,
+    ignored: usize,
+    duration_secs: f64,
+    summarized: bool,
+    failures: Vec<TestFailure>,
+    ignored_names: Vec<String>,
+    slow: Vec<SlowTest>,
+}
+
+/// `cargo test` results for one crate, across its unit, integration and doc tests
+#[derive(Debug, Clone, Serialize)]
+pub struct CrateTestResult {
+    pub name: String,
+    #[serde(flatten)]
+    pub result: TestResult,
+    pub ignored: Vec<String>,
+    pub slow: Vec<SlowTest>,
+}
+
+/// A test that ran for at least [`SLOW_TEST_SECS`]
+#[derive(Debug, Clone, Serialize)]
+pub struct SlowTest {
+    pub name: String,
+    pub duration_ms: u64,
+}
+
+/// `cargo test` results per crate, with the workspace rollup
+#[derive(Debug, Clone, Serialize)]
+pub struct CargoTestReport {
+    pub workspace: TestResult,
+    pub crates: Vec<CrateTestResult>,
+}
+
+impl CargoTestReport {
+    /// Group suites by the crate of their test binary (unnamed if cargo's
+    /// `Running` lines are missing) and total them
+    fn from_suites(suites: Vec<SuiteTally>, crates: &[String]) -> Self {
+        let mut by_crate: Vec<CrateTestResult> = Vec::new();
+        for (idx, suite) in suites.into_iter().enumerate() {
+            let name = crates.get(idx).cloned().unwrap_or_default();
+            let pos = match by_crate.iter().position(|c| c.name == name) {
+                Some(pos) => pos,
+                None => {
+                    by_crate.push(CrateTestResult {
+                        name,
+                        result: TestResult {
+                            total: 0,
+                            passed: 0,
+                            failed: 0,
+                            skipped: 0,
+                            duration_ms: Some(0),
+                            failures: Vec::new(),
+                        },
+                        ignored: Vec::new(),
+                        slow: Vec::new(),
+                    });
+                    by_crate.len() - 1
+                }
+            };
+            let entry = &mut by_crate[pos];
+            let result = &mut entry.result;
+            result.passed += suite.passed;
+            result.failed += suite.failed;
+            result.skipped += suite.ignored;
+            result.total = result.passed + result.failed + result.skipped;
+            result.duration_ms = Some(
+                result.duration_ms.unwrap_or(0) + (suite.duration_secs * 1000.0).round() as u64,
+            );
+            result.failures.extend(suite.failures);
+            entry.ignored.extend(suite.ignored_names);
+            entry.slow.extend(suite.slow);
+        }
+
+        let mut workspace = TestResult {
+            total: 0,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration_ms: Some(0),
+            failures: Vec::new(),
+        };
+        for entry in &by_crate {
+            let result = &entry.result;
+            workspace.total += result.total;
+            workspace.passed += result.passed;
+            workspace.failed += result.failed;
+            workspace.skipped += result.skipped;
+            workspace.duration_ms =
+                Some(workspace.duration_ms.unwrap_or(0) + result.duration_ms.unwrap_or(0));
+            workspace.failures.extend(result.failures.iter().cloned());
+        }
+
+        CargoTestReport {
+            workspace,
+            crates: by_crate,
+        }
+    }
+
+    /// Crate a failing test belongs to, when the run spans several crates
+    fn crate_of(&self, failure: &TestFailure) -> Option<&str> {
+        if self.crates.len() < 2 {
+            return None;
+        }
+        self.crates
+            .iter()
+            .find(|c| {
+                c.result
+                    .failures
+                    .iter()
+                    .any(|f| f.test_name == failure.test_name)
+            })
+            .map(|c| c.name.as_str())
+            .filter(|name| !name.is_empty())
+    }
+
+    fn slow_tests(&self) -> Vec<&SlowTest> {
+        let mut slow: Vec<&SlowTest> = self.crates.iter().flat_map(|c| &c.slow).collect();
+        slow.sort_by_key(|s| std::cmp::Reverse(s.duration_ms));
+        slow
+    }
+}
+
+impl TokenFormatter for CargoTestReport {
+    fn format_compact(&self) -> String {
+        let ws = &self.workspace;
+        let mut counts = vec![format!("{} passed", ws.passed)];
+        if ws.failed > 0 {
+            counts.push(format!("{} failed", ws.failed));
+        }
+        if ws.skipped > 0 {
+            counts.push(format!("{} ignored", ws.skipped));
+        }
+        let crates = match self.crates.len() {
+            1 => "1 crate".to_string(),
+            n => format!("{} crates", n),
+        };
+        let secs = ws.duration_ms.unwrap_or(0) as f64 / 1000.0;
+
+        let mut lines = Vec::new();
+        if ws.failed == 0 {
+            lines.push(format!(
+                "✓ cargo test: {} ({}, {:.2}s)",
+                counts.join(", "),
+                crates,
+                secs
+            ));
+        } else {
+            lines.push(format!(
+                "cargo test: {} ({}, {:.2}s)",
+                counts.join(", "),
+                crates,
+                secs
+            ));
+            lines.push("═══════════════════════════════════════".to_string());
+            if self.crates.len() > 1 {
+                for entry in &self.crates {
+                    lines.push(format!(
+                        "  {}: {} passed, {} failed",
+                        entry.name, entry.result.passed, entry.result.failed
+                    ));
+                }
+            }
+            for (idx, failure) in ws.failures.iter().enumerate().take(10) {
+                let name = match self.crate_of(failure) {
+                    Some(krate) => format!("[{}] {}", krate, failure.test_name),
+                    None => failure.test_name.clone(),
+                };
+                // The panic line carries file:line; keep it plus the assertion
+                let message: Vec<&str> = failure.error_message.lines().collect();
+                if message.len() > 4 {
+                    let key = format!("cargo-test:{}", failure.test_name);
+                    lines.push(format!(
+                        "{}. {}{}",
+                        idx + 1,
+                        name,
+                        crate::tee::section_ref(&key)
+                    ));
+                } else {
+                    lines.push(format!("{}. {}", idx + 1, name));
+                }
+                for line in message.iter().take(4) {
+                    lines.push(format!("   {}", truncate(line, 200)));
+                }
+            }
+            if ws.failures.len() > 10 {
+                lines.push(format!("... +{} more failures", ws.failures.len() - 10));
+            }
+        }
+
+        let slow = self.slow_tests();
+        if !slow.is_empty() {
+            let listed: Vec<String> = slow
+                .iter()
+                .take(5)
+                .map(|s| format!("{} ({:.1}s)", s.name, s.duration_ms as f64 / 1000.0))
+                .collect();
+            lines.push(format!("slow: {}", listed.join(", ")));
+        }
+        let ignored: Vec<&str> = self
+            .crates
+            .iter()
+            .flat_map(|c| c.ignored.iter().map(String::as_str))
+            .collect();
+        if !ignored.is_empty() {
+            let mut line = format!("ignored: {}", ignored[..ignored.len().min(5)].join(", "));
+            if ignored.len() > 5 {
+                line.push_str(&format!(" (+{} more)", ignored.len() - 5));
+            }
+            lines.push(line);
+        }
+
+        lines.join("\n")
+    }
+
+    fn format_verbose(&self) -> String {
+        let mut sections = vec![self.workspace.format_verbose()];
+        if self.crates.len() > 1 {
+            sections.push("Crates:".to_string());
+            for entry in &self.crates {
+                let r = &entry.result;
+                sections.push(format!(
+                    "  {}: {} passed, {} failed, {} ignored ({}ms)",
+                    entry.name,
+                    r.passed,
+                    r.failed,
+                    r.skipped,
+                    r.duration_ms.unwrap_or(0)
+                ));
+            }
+        }
+        for slow in self.slow_tests() {
+            sections.push(format!("slow: {} ({}ms)", slow.name, slow.duration_ms));
+        }
+        for entry in &self.crates {
+            for name in &entry.ignored {
+                sections.push(format!("ignored: {}", name));
+            }
+        }
+        sections.join("\n")
+    }
+
+    fn format_ultra(&self) -> String {
+        self.workspace.format_ultra()
+    }
+}
+
+/// Parser for `cargo test`: libtest JSON events parse fully, as do human
+/// `test result:` summaries; per-test lines alone are a degraded fallback
+pub struct CargoTestParser;
+
+impl OutputParser for CargoTestParser {
+    type Output = CargoTestReport;
+
+    fn parse(input: &str) -> ParseResult<CargoTestReport> {
+        let crates = test_binary_crates(input);
+        if let Some(events) = libtest_events(input) {
+            let report = CargoTestReport::from_suites(tally_events(&events), &crates);
+            return ParseResult::Full(report);
+        }
+
+        let suites = tally_text(input);
+        if suites.iter().any(|s| s.summarized) {
+            return ParseResult::Full(CargoTestReport::from_suites(suites, &crates));
+        }
+
+        if suites.iter().any(|s| s.passed + s.failed + s.ignored > 0) {
+            return ParseResult::Degraded(
+                CargoTestReport::from_suites(suites, &crates),
+                vec!["no `test result:` summary, counted per-test lines".to_string()],
+            );
+        }
+
+        if input.contains("could not compile") {
+            return ParseResult::Degraded(
+                CargoTestReport::from_suites(Vec::new(), &crates),
+                vec!["compilation failed before tests ran".to_string()],
+            );
+        }
+
+        ParseResult::Passthrough(crate::parser::truncate_output(input, 500))
+    }
+}
+
+/// Per-suite results from libtest JSON events
+fn tally_events(events: &[LibtestEvent]) -> Vec<SuiteTally> {
+    let mut suites: Vec<SuiteTally> = Vec::new();
+    for event in events {
+        if event.kind == "suite" && event.event == "started" {
+            suites.push(SuiteTally::default());
+            continue;
+        }
+        let suite = match suites.last_mut() {
+            Some(suite) => suite,
+            None => continue,
+        };
+        let name = event.name.clone().unwrap_or_default();
+        match (event.kind.as_str(), event.event.as_str()) {
+            ("suite", _) => {
+                suite.summarized = true;
+                suite.passed = event.passed + event.measured;
+                suite.failed = event.failed;
+                suite.ignored = event.ignored;
+                suite.duration_secs = event.exec_time.unwrap_or(0.0);
+            }
+            ("test", "failed") => {
+                let stdout = event.stdout.as_deref().unwrap_or_default();
+                suite
+                    .failures
+                    .push(failure_from_output(&name, stdout.lines()));
+            }
+            ("test", "ignored") => suite.ignored_names.push(name),
+            ("test", "timeout") => suite.slow.push(SlowTest {
+                name,
+                duration_ms: 60_000,
+            }),
+            ("test", "ok") => {
+                if let Some(secs) = event.exec_time.filter(|s| *s >= SLOW_TEST_SECS) {
+                    suite.slow.push(SlowTest {
+                        name,
+                        duration_ms: (secs * 1000.0).round() as u64,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    suites
+}
+
+/// Per-suite results from libtest's human output: `running N tests` opens a
+/// suite, `test result:` closes it
+fn tally_text(input: &str) -> Vec<SuiteTally> {
+    static SUMMARY: OnceLock<regex::Regex> = OnceLock::new();
+    static TEST_LINE: OnceLock<regex::Regex> = OnceLock::new();
+    let summary = SUMMARY.get_or_init(|| {
+        regex::Regex::new(
+            r"test result: \w+\.\s+(\d+) passed;\s+(\d+) failed;\s+(\d+) ignored;(?:.*finished in ([\d.]+)s)?",
+        )
+        .unwrap()
+    });
+    // `test a::b ... ok <1.234s>` (the time only with --report-time)
+    let test_line = TEST_LINE.get_or_init(|| {
+        regex::Regex::new(r"^test (\S+) \.\.\. (ok|FAILED|ignored)(?:, .*)?(?: <([\d.]+)s>)?$")
+            .unwrap()
+    });
+
+    let mut suites: Vec<SuiteTally> = Vec::new();
+    // Per-test counts, used when the suite has no summary (interrupted runs)
+    let mut counted = (0, 0, 0);
+    for line in input.lines() {
+        if (line.starts_with("running ") && line.ends_with(" tests")) || line == "running 1 test" {
+            suites.push(SuiteTally::default());
+            counted = (0, 0, 0);
+            continue;
+        }
+        if suites.is_empty() && (line.starts_with("test ") || line.starts_with("---- ")) {
+            suites.push(SuiteTally::default());
+        }
+        let suite = match suites.last_mut() {
+            Some(suite) => suite,
+            None => continue,
+        };
+
+        if let Some(caps) = summary.captures(line) {
+            suite.summarized = true;
+            suite.passed = caps[1].parse().unwrap_or(0);
+            suite.failed = caps[2].parse().unwrap_or(0);
+            suite.ignored = caps[3].parse().unwrap_or(0);
+            if let Some(secs) = caps.get(4) {
+                suite.duration_secs = secs.as_str().parse().unwrap_or(0.0);
+            }
+        } else if let Some(caps) = test_line.captures(line) {
+            let name = caps[1].to_string();
+            match &caps[2] {
+                "ok" => counted.0 += 1,
+                "FAILED" => counted.1 += 1,
+                _ => {
+                    counted.2 += 1;
+                    suite.ignored_names.push(name.clone());
+                }
+            }
+            if let Some(secs) = caps
+                .get(3)
+                .and_then(|s| s.as_str().parse::<f64>().ok())
+                .filter(|s| *s >= SLOW_TEST_SECS)
+            {
+                suite.slow.push(SlowTest {
+                    name,
+                    duration_ms: (secs * 1000.0).round() as u64,
+                });
+            }
+            if !suite.summarized {
+                (suite.passed, suite.failed, suite.ignored) = counted;
+            }
+        } else if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" has been running for over 60 seconds"))
+        {
+            suite.slow.push(SlowTest {
+                name: name.to_string(),
+                duration_ms: 60_000,
+            });
+        } else if let Some(name) = line
+            .strip_prefix("---- ")
+            .and_then(|rest| rest.strip_suffix(" stdout ----"))
+        {
+            suite.failures.push(test_failure(input, name));
+        }
+    }
+    suites
+}
+
+/// One failing test from its `---- name stdout ----` block
+fn test_failure(output: &str, name: &str) -> TestFailure {
+    let section = extract_test_section(output, name).unwrap_or_default();
+    failure_from_output(name, section.lines().skip(1))
+}
+
+/// A failing test from its captured output; `file_path` is the file of the
+/// panic location (`src/lib.rs`), whose line and column stay in the message
+fn failure_from_output<'a>(name: &str, output: impl Iterator<Item = &'a str>) -> TestFailure {
+    let body: Vec<&str> = output.collect();
+    // "thread 'x' panicked at src/lib.rs:10:5:" (older toolchains: "..., src/lib.rs:10:5")
+    let file_path = body
+        .iter()
+        .find_map(|l| l.split_once(" panicked at "))
+        .map(|(_, rest)| {
+            let rest = rest.rsplit(", ").next().unwrap_or(rest);
+            let location = rest.trim_end_matches(':').trim_matches('\'');
+            location.split(':').next().unwrap_or_default().to_string()
+        })
+        .filter(|path| path.ends_with(".rs"))
+        .unwrap_or_default();
+    let body = body.join("\n");
+    let (message, stack_trace) = match body.split_once("stack backtrace:") {
+        Some((message, stack)) => (message, Some(stack.trim_matches('\n').to_string())),
+        None => (body.as_str(), None),
+    };
+    TestFailure {
+        test_name: name.to_string(),
+        file_path,
+        error_message: message.trim().to_string(),
+        stack_trace,
+    }
+}
+
+/// Parser for rustc/clippy diagnostics (`cargo build`, `cargo check`, `cargo clippy`).
+/// `--message-format=json` output parses fully; cargo's human output is the
+/// degraded fallback.
+pub struct CargoDiagnosticsParser;
+
+impl OutputParser for CargoDiagnosticsParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        if let Some(diags) = json_diagnostics(input) {
+            let issues = diags
+                .into_iter()
+                .map(|d| LintIssue {
+                    file_path: d.file,
+                    line: d.line,
+                    column: d.column,
+                    severity: if d.is_error {
+                        LintSeverity::Error
+                    } else {
+                        LintSeverity::Warning
+                    },
+                    rule_id: d.code,
+                    message: d.message,
+                })
+                .collect();
+            return ParseResult::Full(LintResult::from_issues(issues));
+        }
+
+        static HEADER: OnceLock<regex::Regex> = OnceLock::new();
+        static LOCATION: OnceLock<regex::Regex> = OnceLock::new();
+        static LINT_NOTE: OnceLock<regex::Regex> = OnceLock::new();
+        let header = HEADER
+            .get_or_init(|| regex::Regex::new(r"^(error|warning)(?:\[(\w+)\])?: (.+)$").unwrap());
+        let location =
+            LOCATION.get_or_init(|| regex::Regex::new(r"^\s*--> (.+):(\d+):(\d+)$").unwrap());
+        let lint_note = LINT_NOTE
+            .get_or_init(|| regex::Regex::new(r"#\[(?:warn|deny|forbid)\(([\w:]+)\)\]").unwrap());
+
+        let mut issues: Vec<LintIssue> = Vec::new();
+        // Index of the diagnostic whose location/notes are being read
+        let mut current: Option<usize> = None;
+
+        for line in input.lines() {
+            if let Some(caps) = header.captures(line) {
+                let message = &caps[3];
+                if message.contains("aborting due to")
+                    || message.contains("could not compile")
+                    || (message.contains("generated") && message.contains("warning"))
+                {
+                    current = None;
+                    continue;
+                }
+                issues.push(LintIssue {
+                    file_path: String::new(),
+                    line: 0,
+                    column: 0,
+                    severity: if &caps[1] == "error" {
+                        LintSeverity::Error
+                    } else {
+                        LintSeverity::Warning
+                    },
+                    // `error[E0308]: ...`, or a trailing `[lint_name]`
+                    rule_id: caps
+                        .get(2)
+                        .map(|c| c.as_str())
+                        .or_else(|| {
+                            message
+                                .strip_suffix(']')
+                                .and_then(|m| m.rsplit_once(" ["))
+                                .map(|(_, rule)| rule)
+                        })
+                        .unwrap_or("")
+                        .to_string(),
+                    message: message.to_string(),
+                });
+                current = Some(issues.len() - 1);
+            } else if let Some(issue) = current.and_then(|i| issues.get_mut(i)) {
+                if let Some(caps) = location.captures(line) {
+                    if issue.file_path.is_empty() {
+                        issue.file_path = caps[1].to_string();
+                        issue.line = caps[2].parse().unwrap_or(0);
+                        issue.column = caps[3].parse().unwrap_or(0);
+                    }
+                } else if let Some(caps) = lint_note.captures(line) {
+                    issue.rule_id = caps[1].to_string();
+                }
+            }
+        }
+
+        let recognized = input.lines().any(|l| {
+            let t = l.trim_start();
+            t.starts_with("Finished") || t.starts_with("Compiling") || t.starts_with("Checking")
+        });
+        if issues.is_empty() && !recognized {
+            return ParseResult::Passthrough(crate::parser::truncate_output(input, 500));
+        }
+
+        let result = LintResult::from_issues(issues);
+
+        let mut warnings = vec!["parsed human-readable diagnostics (no JSON messages)".to_string()];
+        let unlocated = result
+            .issues
+            .iter()
+            .filter(|i| i.file_path.is_empty())
+            .count();
+        if unlocated > 0 {
+            warnings.push(format!(
+                "{} diagnostics without a source location",
+                unlocated
+            ));
+        }
+        ParseResult::Degraded(result, warnings)
+    }
+}
+
 /// Filter cargo clippy output - group warnings by lint rule
-fn filter_cargo_clippy(output: &str) -> String {
+pub(crate) fn filter_cargo_clippy(output: &str) -> String {
+    if let Some(diags) = json_diagnostics(output) {
+        if diags.is_empty() {
+            return "✓ cargo clippy: No issues found".to_string();
+        }
+        let errors = diags.iter().filter(|d| d.is_error).count();
+        return format!(
+            "cargo clippy: {} errors, {} warnings\n\
+             ═══════════════════════════════════════\n{}",
+            errors,
+            diags.len() - errors,
+            format_diagnostics(&diags)
+        )
+        .trim()
+        .to_string();
+    }
+
     let mut by_rule: HashMap<String, Vec<String>> = HashMap::new();
     let mut error_count = 0;
     let mut warning_count = 0;
@@ -901,9 +2103,9 @@ fn filter_cargo_clippy(output: &str) -> String {
     ));
     result.push_str("═══════════════════════════════════════\n");
 
-    // Sort rules by frequency
+    // Sort rules by frequency, then name (stable output across runs)
     let mut rule_counts: Vec<_> = by_rule.iter().collect();
-    rule_counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
+    rule_counts.sort_by(|a, b| b.1.len().cmp(&a.1.len()).then_with(|| a.0.cmp(b.0)));
 
     for (rule, locations) in rule_counts.iter().take(15) {
         result.push_str(&format!("  {} ({}x)\n", rule, locations.len()));
@@ -1025,6 +2227,46 @@ test result: FAILED. 4 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out
         assert!(result.contains("test result:"));
     }
 
+    #[test]
+    fn test_filter_cargo_test_failure_section_refs() {
+        let long_panic = "x".repeat(300);
+        let mut output = String::from("running 12 tests\n\nfailures:\n\n");
+        for i in 0..12 {
+            output.push_str(&format!(
+                "---- t{} stdout ----\nthread 't{}' panicked at '{}'\n\n",
+                i, i, long_panic
+            ));
+        }
+        output.push_str("failures:\n    t0\n\ntest result: FAILED. 0 passed; 12 failed; 0 ignored; 0 measured; 0 filtered out\n");
+
+        let result = filter_cargo_test(&output);
+        assert!(result.contains(&crate::tee::section_ref("cargo-test:t0")));
+        assert!(result.contains(&format!(
+            "  t11{}",
+            crate::tee::section_ref("cargo-test:t11")
+        )));
+
+        let section = extract_test_section(&output, "t11").unwrap();
+        assert!(section.starts_with("---- t11 stdout ----"));
+        assert!(section.contains(&long_panic));
+        assert!(!section.contains("failures:"));
+        let section = extract_test_section(&output, "t3").unwrap();
+        assert_eq!(section.lines().count(), 2);
+        assert!(extract_test_section(&output, "missing").is_none());
+    }
+
+    #[test]
+    fn test_test_stream_reports_failures_live() {
+        let output = "running 2 tests\ntest foo::test_a ... ok\ntest foo::test_b ... FAILED\n\
+                      test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n";
+        let mut stream = TestStream::default();
+        let live: Vec<String> = output.lines().flat_map(|l| stream.feed_line(l)).collect();
+        assert_eq!(live, vec!["FAILED foo::test_b"]);
+
+        let flushed = stream.finish(output);
+        assert_eq!(flushed, format!("\n{}", filter_cargo_test(output)));
+    }
+
     #[test]
     fn test_filter_cargo_test_multi_suite_all_pass() {
         let output = r#"   Compiling rtk v0.5.0
@@ -1205,6 +2447,333 @@ warning: `rtk` (bin) generated 2 warnings
         assert!(result.contains("clippy::too_many_arguments"));
     }
 
+    #[test]
+    fn test_cargo_diagnostics_parser() {
+        let output = r#"    Checking rtk v0.5.0
+error[E0308]: mismatched types
+ --> src/main.rs:10:5
+  |
+warning: this function has too many arguments (8/7)
+ --> src/git.rs:16:1
+  |
+  = note: `#[warn(clippy::too_many_arguments)]` on by default
+
+warning: unused variable: `x` [unused_variables]
+ --> src/git.rs:20:9
+
+warning: `rtk` (bin) generated 2 warnings
+error: could not compile `rtk` (bin "rtk") due to 1 previous error
+"#;
+        let result = CargoDiagnosticsParser::parse(output);
+        assert_eq!(result.tier(), 2);
+        let lint = result.unwrap();
+        assert_eq!(lint.errors, 1);
+        assert_eq!(lint.warnings, 2);
+        assert_eq!(lint.files_with_issues, 2);
+        assert_eq!(lint.issues[0].rule_id, "E0308");
+        assert_eq!(lint.issues[0].file_path, "src/main.rs");
+        assert_eq!(lint.issues[0].line, 10);
+        assert_eq!(lint.issues[1].rule_id, "clippy::too_many_arguments");
+        assert_eq!(lint.issues[2].rule_id, "unused_variables");
+
+        let clean = CargoDiagnosticsParser::parse(
+            "    Finished dev [unoptimized + debuginfo] target(s) in 1.53s
+",
+        );
+        assert_eq!(clean.tier(), 2);
+        assert_eq!(clean.unwrap().total_issues, 0);
+
+        let no_manifest = CargoDiagnosticsParser::parse(
+            "error: could not find `Cargo.toml` in `/tmp` or any parent directory
+",
+        );
+        assert_eq!(no_manifest.tier(), 2);
+        assert_eq!(
+            CargoDiagnosticsParser::parse("zsh: command not found").tier(),
+            3
+        );
+    }
+
+    /// `cargo build --message-format=json` on a crate with one error and a
+    /// warning reported twice (bin and test targets), trimmed to the fields read
+    const JSON_DIAGNOSTICS: &str = r#"{"reason":"compiler-message","message":{"rendered":"error[E0308]: mismatched types\n --> src/main.rs:13:18\n","level":"error","code":{"code":"E0308","explanation":null},"message":"mismatched types","spans":[{"file_name":"src/main.rs","line_start":13,"column_start":18,"is_primary":true,"label":"expected `u32`, found `&str`","suggested_replacement":null},{"file_name":"src/main.rs","line_start":13,"column_start":12,"is_primary":false,"label":"expected due to this","suggested_replacement":null}],"children":[]}}
+{"reason":"compiler-message","message":{"rendered":"warning: unused variable: `x`\n","level":"warning","code":{"code":"unused_variables","explanation":null},"message":"unused variable: `x`","spans":[{"file_name":"src/main.rs","line_start":5,"column_start":9,"is_primary":true,"label":null,"suggested_replacement":null}],"children":[{"level":"note","message":"`#[warn(unused_variables)]` on by default","code":null,"spans":[],"children":[],"rendered":null},{"level":"help","message":"if this is intentional, prefix it with an underscore","code":null,"spans":[{"file_name":"src/main.rs","line_start":5,"column_start":9,"is_primary":true,"label":null,"suggested_replacement":"_x"}],"children":[],"rendered":null}]}}
+{"reason":"compiler-message","message":{"rendered":"warning: unused variable: `x`\n","level":"warning","code":{"code":"unused_variables","explanation":null},"message":"unused variable: `x`","spans":[{"file_name":"src/main.rs","line_start":5,"column_start":9,"is_primary":true,"label":null,"suggested_replacement":null}],"children":[{"level":"help","message":"if this is intentional, prefix it with an underscore","code":null,"spans":[{"file_name":"src/main.rs","line_start":5,"column_start":9,"is_primary":true,"label":null,"suggested_replacement":"_x"}],"children":[],"rendered":null}]}}
+{"reason":"compiler-message","message":{"rendered":"error: aborting due to 1 previous error\n","level":"error","code":null,"message":"aborting due to 1 previous error","spans":[],"children":[]}}
+{"reason":"build-finished","success":false}
+
+   Compiling diag v0.1.0 (/tmp/diag)
+error: could not compile `diag` (bin "diag") due to 1 previous error; 1 warning emitted
+"#;
+
+    #[test]
+    fn test_cargo_diagnostics_parser_json() {
+        let result = CargoDiagnosticsParser::parse(JSON_DIAGNOSTICS);
+        assert_eq!(result.tier(), 1);
+        let lint = result.unwrap();
+        assert_eq!(lint.errors, 1);
+        assert_eq!(lint.warnings, 1);
+        assert_eq!(lint.issues[0].rule_id, "E0308");
+        assert_eq!(lint.issues[0].column, 18);
+        assert_eq!(lint.issues[1].file_path, "src/main.rs");
+        assert_eq!(lint.issues[1].line, 5);
+
+        let clean = CargoDiagnosticsParser::parse(
+            "{\"reason\":\"build-finished\",\"success\":true}\n\n    Finished `dev` profile\n",
+        );
+        assert_eq!(clean.tier(), 1);
+        assert_eq!(clean.unwrap().total_issues, 0);
+    }
+
+    #[test]
+    fn test_filter_cargo_build_json() {
+        let result = filter_cargo_build(JSON_DIAGNOSTICS);
+        assert!(result.starts_with("cargo build: 1 errors, 1 warnings (1 crates)"));
+        assert!(result.contains(
+            "E0308 (1x): mismatched types\n  src/main.rs:13:18 expected `u32`, found `&str`"
+        ));
+        assert!(result.contains(
+            "unused_variables (1x): unused variable: `x`\n  src/main.rs:5:9 → if this is intentional, prefix it with an underscore: `_x`"
+        ));
+        assert!(!result.contains("aborting"));
+        assert!(!result.contains("reason"));
+
+        let clean = "{\"reason\":\"build-finished\",\"success\":true}\n\n   Compiling a v0.1.0\n";
+        assert_eq!(
+            filter_cargo_build(clean),
+            "✓ cargo build (1 crates compiled)"
+        );
+        assert_eq!(
+            filter_cargo_clippy(clean),
+            "✓ cargo clippy: No issues found"
+        );
+    }
+
+    #[test]
+    fn test_filter_cargo_clippy_json_groups_by_code() {
+        let diag = |file: &str, line: usize, var: &str| {
+            format!(
+                r#"{{"reason":"compiler-message","message":{{"rendered":null,"level":"warning","code":{{"code":"unused_variables"}},"message":"unused variable: `{var}`","spans":[{{"file_name":"{file}","line_start":{line},"column_start":9,"is_primary":true,"label":null,"suggested_replacement":null}}],"children":[]}}}}"#
+            )
+        };
+        let output = [
+            diag("src/b.rs", 3, "x"),
+            diag("src/a.rs", 7, "y"),
+            diag("src/a.rs", 2, "x"),
+        ]
+        .join("\n");
+        let result = filter_cargo_clippy(&output);
+        assert!(result.starts_with("cargo clippy: 0 errors, 3 warnings"));
+        assert!(result.ends_with(
+            "unused_variables (3x): unused variable: `x`\n  src/a.rs:2:9\n  src/a.rs:7:9 unused variable: `y`\n  src/b.rs:3:9"
+        ));
+    }
+
+    #[test]
+    fn test_rendered_output() {
+        let human = rendered_output(JSON_DIAGNOSTICS);
+        assert!(human.starts_with("error[E0308]: mismatched types\n --> src/main.rs:13:18\n"));
+        assert!(human.contains("   Compiling diag"));
+        assert!(!human.contains("\"reason\""));
+        assert_eq!(rendered_output("plain\n"), "plain\n");
+    }
+
+    #[test]
+    fn test_cargo_test_parser() {
+        let output = r#"running 3 tests
+test a ... ok
+test b ... FAILED
+test c ... ignored
+
+failures:
+
+---- b stdout ----
+thread 'b' panicked at src/lib.rs:10:5:
+assertion `left == right` failed
+
+failures:
+    b
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.25s
+
+running 2 tests
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s
+"#;
+        let result = CargoTestParser::parse(output);
+        assert_eq!(result.tier(), 1);
+        let tests = result.unwrap().workspace;
+        assert_eq!(tests.passed, 3);
+        assert_eq!(tests.failed, 1);
+        assert_eq!(tests.skipped, 1);
+        assert_eq!(tests.total, 5);
+        assert_eq!(tests.duration_ms, Some(750));
+        assert_eq!(tests.failures.len(), 1);
+        assert_eq!(tests.failures[0].test_name, "b");
+        assert_eq!(tests.failures[0].file_path, "src/lib.rs");
+        assert!(tests.failures[0]
+            .error_message
+            .starts_with("thread 'b' panicked"));
+        assert!(tests.failures[0].error_message.contains("left == right"));
+        assert!(tests.failures[0].stack_trace.is_none());
+
+        let interrupted = CargoTestParser::parse(
+            "running 2 tests
+test a ... ok
+test b ... FAILED
+",
+        );
+        assert_eq!(interrupted.tier(), 2);
+        assert_eq!(interrupted.unwrap().workspace.failed, 1);
+
+        let no_build = CargoTestParser::parse(
+            "error: could not compile `rtk`
+",
+        );
+        assert_eq!(no_build.tier(), 2);
+        assert_eq!(CargoTestParser::parse("something else").tier(), 3);
+    }
+
+    /// `cargo test --no-fail-fast -- -Z unstable-options --format json --report-time`
+    /// on a two-crate workspace: libtest events on stdout, cargo's lines on stderr
+    const LIBTEST_JSON: &str = r#"{ "type": "suite", "event": "started", "test_count": 3 }
+{ "type": "test", "event": "started", "name": "tests::later" }
+{ "type": "test", "name": "tests::later", "event": "ignored" }
+{ "type": "test", "name": "tests::slow", "event": "ok", "exec_time": 1.10007299 }
+{ "type": "test", "name": "tests::works", "event": "ok", "exec_time": 0.000001 }
+{ "type": "suite", "event": "ok", "passed": 2, "failed": 0, "ignored": 1, "measured": 0, "filtered_out": 0, "exec_time": 1.1 }
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "api_ok", "event": "ok", "exec_time": 0.0001 }
+{ "type": "suite", "event": "ok", "passed": 1, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.001 }
+{ "type": "suite", "event": "started", "test_count": 1 }
+{ "type": "test", "name": "tests::two_is_two", "event": "failed", "exec_time": 0.008, "stdout": "\nthread 'tests::two_is_two' (8640) panicked at crates/beta/src/lib.rs:5:23:\nassertion `left == right` failed\n  left: 3\n right: 2\n" }
+{ "type": "suite", "event": "failed", "passed": 0, "failed": 1, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.008 }
+{ "type": "suite", "event": "started", "test_count": 0 }
+{ "type": "suite", "event": "ok", "passed": 0, "failed": 0, "ignored": 0, "measured": 0, "filtered_out": 0, "exec_time": 0.0 }
+
+   Compiling alpha v0.1.0 (/tmp/ws/crates/alpha)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.46s
+     Running unittests src/lib.rs (target/debug/deps/alpha-f61697eead3e4da0)
+     Running tests/api.rs (target/debug/deps/api-137c4439145982b7)
+     Running unittests src/lib.rs (target/debug/deps/beta-1f2579eae0116716)
+error: test failed, to rerun pass `-p beta --lib`
+   Doc-tests beta
+"#;
+
+    #[test]
+    fn test_test_binary_crates() {
+        assert_eq!(
+            test_binary_crates(LIBTEST_JSON),
+            vec!["alpha", "alpha", "beta", "beta"]
+        );
+        assert!(test_binary_crates("running 1 test\n").is_empty());
+    }
+
+    #[test]
+    fn test_cargo_test_parser_libtest_json() {
+        let result = CargoTestParser::parse(LIBTEST_JSON);
+        assert_eq!(result.tier(), 1);
+        let report = result.unwrap();
+        assert_eq!(report.workspace.passed, 3);
+        assert_eq!(report.workspace.failed, 1);
+        assert_eq!(report.workspace.skipped, 1);
+        assert_eq!(report.workspace.duration_ms, Some(1109));
+
+        assert_eq!(report.crates.len(), 2);
+        let alpha = &report.crates[0];
+        assert_eq!(alpha.name, "alpha");
+        assert_eq!(alpha.result.passed, 3);
+        assert_eq!(alpha.ignored, vec!["tests::later"]);
+        assert_eq!(alpha.slow.len(), 1);
+        assert_eq!(alpha.slow[0].name, "tests::slow");
+        assert_eq!(alpha.slow[0].duration_ms, 1100);
+
+        let beta = &report.crates[1];
+        assert_eq!(beta.result.failed, 1);
+        let failure = &beta.result.failures[0];
+        assert_eq!(failure.file_path, "crates/beta/src/lib.rs");
+        assert!(failure
+            .error_message
+            .starts_with("thread 'tests::two_is_two'"));
+        assert!(failure.error_message.ends_with("right: 2"));
+
+        let compact = report.format_compact();
+        assert!(compact.starts_with("cargo test: 3 passed, 1 failed, 1 ignored (2 crates, 1.11s)"));
+        assert!(compact.contains("  beta: 0 passed, 1 failed"));
+        assert!(compact.contains("1. [beta] tests::two_is_two\n   thread 'tests::two_is_two' (8640) panicked at crates/beta/src/lib.rs:5:23:"));
+        assert!(compact.contains("slow: tests::slow (1.1s)"));
+        assert!(compact.ends_with("ignored: tests::later"));
+    }
+
+    #[test]
+    fn test_test_stream_libtest_json() {
+        let mut stream = TestStream::default();
+        let live: Vec<String> = LIBTEST_JSON
+            .lines()
+            .flat_map(|l| stream.feed_line(l))
+            .collect();
+        assert_eq!(live, vec!["FAILED tests::two_is_two"]);
+        assert!(stream
+            .finish(LIBTEST_JSON)
+            .starts_with("\ncargo test: 3 passed, 1 failed"));
+    }
+
+    #[test]
+    fn test_rendered_output_libtest_json() {
+        let human = rendered_output(LIBTEST_JSON);
+        assert!(human.contains("running 3 tests\ntest tests::later ... ignored\n"));
+        assert!(human.contains("test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s"));
+        assert!(!human.contains("\"type\""));
+        assert_eq!(
+            extract_test_section(&human, "tests::two_is_two").unwrap(),
+            "---- tests::two_is_two stdout ----\nthread 'tests::two_is_two' (8640) panicked at crates/beta/src/lib.rs:5:23:\nassertion `left == right` failed\n  left: 3\n right: 2"
+        );
+        // Parses back to the same totals through the text path
+        assert_eq!(CargoTestParser::parse(&human).unwrap().workspace.failed, 1);
+    }
+
+    #[test]
+    fn test_filter_cargo_test_crate_labels() {
+        let output = "
+running 1 test
+test tests::works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+
+running 1 test
+test tests::two_is_two ... FAILED
+
+failures:
+
+---- tests::two_is_two stdout ----
+
+thread 'tests::two_is_two' panicked at crates/beta/src/lib.rs:5:23:
+assertion failed
+
+failures:
+    tests::two_is_two
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+
+running 0 tests
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+     Running unittests src/lib.rs (target/debug/deps/alpha-f61697eead3e4da0)
+     Running unittests src/lib.rs (target/debug/deps/beta-1f2579eae0116716)
+   Doc-tests beta
+";
+        let result = filter_cargo_test(output);
+        assert!(result.starts_with("FAILURES (1):"), "got: {}", result);
+        assert!(result.contains(
+            "1. [beta] ---- tests::two_is_two stdout ----\nthread 'tests::two_is_two' panicked at crates/beta/src/lib.rs:5:23:"
+        ));
+        assert!(result.contains("alpha: test result: ok. 1 passed"));
+        assert_eq!(result.matches("beta: test result:").count(), 1);
+        assert!(!result.contains("0 passed; 0 failed; 0 ignored"));
+    }
+
     #[test]
     fn test_filter_cargo_install_success() {
         let output = r#"  Installing rtk v0.11.0
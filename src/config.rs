@@ -1,6 +1,10 @@
-use anyhow::Result;
+use crate::user_filter::UserFilterConfig;
+use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Per-project config file, discovered by walking up from the cwd.
+pub const PROJECT_CONFIG_FILE: &str = ".rtk.toml";
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct Config {
@@ -12,6 +16,10 @@ pub struct Config {
     pub filters: FilterConfig,
     #[serde(default)]
     pub tee: crate::tee::TeeConfig,
+    #[serde(default)]
+    pub limits: LimitsConfig,
+    #[serde(default)]
+    pub cache: CacheConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -20,6 +28,9 @@ pub struct TrackingConfig {
     pub history_days: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub database_path: Option<PathBuf>,
+    /// Token counter used for savings: "heuristic" (chars/4) or "bpe" (exact)
+    #[serde(default)]
+    pub token_counter: crate::tokens::TokenCounterKind,
 }
 
 impl Default for TrackingConfig {
@@ -28,6 +39,7 @@ impl Default for TrackingConfig {
             enabled: true,
             history_days: 90,
             database_path: None,
+            token_counter: crate::tokens::TokenCounterKind::default(),
         }
     }
 }
@@ -50,9 +62,13 @@ impl Default for DisplayConfig {
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct FilterConfig {
     pub ignore_dirs: Vec<String>,
     pub ignore_files: Vec<String>,
+    /// User-defined output filters for `rtk run` (`[[filters.custom]]`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub custom: Vec<UserFilterConfig>,
 }
 
 impl Default for FilterConfig {
@@ -67,22 +83,47 @@ impl Default for FilterConfig {
                 "vendor".into(),
             ],
             ignore_files: vec!["*.lock".into(), "*.min.js".into(), "*.min.css".into()],
+            custom: Vec::new(),
         }
     }
 }
 
-impl Config {
-    pub fn load() -> Result<Self> {
-        let path = get_config_path()?;
+/// Limits for wrapped commands; `--timeout` / `--max-output` override them per run.
+#[derive(Debug, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Kill the command (SIGTERM, then SIGKILL) after this many seconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Stop the command once it has written this many bytes of output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_bytes: Option<usize>,
+}
 
-        if path.exists() {
-            let content = std::fs::read_to_string(&path)?;
-            let config: Config = toml::from_str(&content)?;
-            Ok(config)
-        } else {
-            Ok(Config::default())
+/// Repeated-output suppression for `rtk git status` and `rtk ls` (opt-in).
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CacheConfig {
+    /// Replace output identical to the previous run with a one-line notice
+    pub enabled: bool,
+    /// Print the full output again once the previous one is this old
+    pub max_age_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_age_secs: 600,
         }
     }
+}
+
+impl Config {
+    /// Load the global config with the nearest project `.rtk.toml` layered on top.
+    pub fn load() -> Result<Self> {
+        Ok(LayeredConfig::load()?.config)
+    }
 
     pub fn save(&self) -> Result<()> {
         let path = get_config_path()?;
@@ -103,25 +144,336 @@ impl Config {
     }
 }
 
+/// Append `[[filters.custom]]` entries to the global config.toml under a
+/// comment, leaving the rest of the file as written. Returns the config path.
+pub fn append_custom_filters(filters: &[UserFilterConfig], comment: &str) -> Result<PathBuf> {
+    let path = get_config_path()?;
+    append_custom_filters_to(&path, filters, comment)?;
+    Ok(path)
+}
+
+fn append_custom_filters_to(
+    path: &Path,
+    filters: &[UserFilterConfig],
+    comment: &str,
+) -> Result<()> {
+    let mut content = std::fs::read_to_string(path).unwrap_or_default();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("\n# {}\n", comment));
+    for filter in filters {
+        content.push_str("[[filters.custom]]\n");
+        content.push_str(&toml::to_string(filter)?);
+        content.push('\n');
+    }
+    content
+        .parse::<toml::Table>()
+        .with_context(|| format!("{} would no longer parse", path.display()))?;
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, content)?;
+    Ok(())
+}
+
 fn get_config_path() -> Result<PathBuf> {
     let config_dir = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
     Ok(config_dir.join("rtk").join("config.toml"))
 }
 
+/// Where an effective config value comes from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConfigSource {
+    Default,
+    Global,
+    Project,
+}
+
+impl ConfigSource {
+    fn label(&self) -> &'static str {
+        match self {
+            ConfigSource::Default => "default",
+            ConfigSource::Global => "global",
+            ConfigSource::Project => "project",
+        }
+    }
+}
+
+/// The effective config plus the raw layers it was merged from.
+pub struct LayeredConfig {
+    pub config: Config,
+    pub global_path: PathBuf,
+    pub project_path: Option<PathBuf>,
+    global: toml::Table,
+    project: toml::Table,
+}
+
+impl LayeredConfig {
+    /// Merge defaults, the global config.toml and the nearest `.rtk.toml`.
+    pub fn load() -> Result<Self> {
+        let global_path = get_config_path()?;
+        let project_path = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| find_project_config(&cwd));
+        Self::from_paths(global_path, project_path)
+    }
+
+    fn from_paths(global_path: PathBuf, project_path: Option<PathBuf>) -> Result<Self> {
+        let global = if global_path.exists() {
+            read_table(&global_path)?
+        } else {
+            toml::Table::new()
+        };
+
+        let project = match &project_path {
+            Some(path) => {
+                let mut table = read_table(path)?;
+                if let Some(dir) = path.parent() {
+                    resolve_relative_paths(&mut table, dir);
+                }
+                table
+            }
+            None => toml::Table::new(),
+        };
+
+        let mut merged = toml::Table::try_from(Config::default())?;
+        merge_tables(&mut merged, global.clone());
+        merge_tables(&mut merged, project.clone());
+        let config: Config = toml::Value::Table(merged)
+            .try_into()
+            .context("Invalid rtk configuration")?;
+
+        Ok(Self {
+            config,
+            global_path,
+            project_path,
+            global,
+            project,
+        })
+    }
+
+    /// Source of a dotted key such as `tee.mode`.
+    pub fn source_of(&self, key: &str) -> ConfigSource {
+        if lookup(&self.project, key).is_some() {
+            ConfigSource::Project
+        } else if lookup(&self.global, key).is_some() {
+            ConfigSource::Global
+        } else {
+            ConfigSource::Default
+        }
+    }
+}
+
+fn read_table(path: &Path) -> Result<toml::Table> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    toml::from_str(&content).with_context(|| format!("Failed to parse {}", path.display()))
+}
+
+/// Walk up from `start` looking for `.rtk.toml`.
+fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Overlay `overlay` onto `base` field by field. Nested tables merge;
+/// everything else (scalars, arrays) is replaced.
+fn merge_tables(base: &mut toml::Table, overlay: toml::Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(overlay_table)) => {
+                merge_tables(base_table, overlay_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Paths in a project config are relative to the directory holding `.rtk.toml`.
+fn resolve_relative_paths(table: &mut toml::Table, base_dir: &Path) {
+    for (section, key) in [("tracking", "database_path"), ("tee", "directory")] {
+        if let Some(toml::Value::String(path)) = table
+            .get_mut(section)
+            .and_then(|s| s.as_table_mut())
+            .and_then(|s| s.get_mut(key))
+        {
+            if Path::new(path.as_str()).is_relative() {
+                *path = base_dir.join(path.as_str()).to_string_lossy().into_owned();
+            }
+        }
+    }
+}
+
+fn lookup<'a>(table: &'a toml::Table, key: &str) -> Option<&'a toml::Value> {
+    let mut parts = key.split('.');
+    let mut value = table.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_table()?.get(part)?;
+    }
+    Some(value)
+}
+
+/// Flatten a table into `(dotted.key, value)` leaves. Arrays are leaves.
+fn flatten(table: &toml::Table, prefix: &str, out: &mut Vec<(String, toml::Value)>) {
+    for (key, value) in table {
+        let full = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            toml::Value::Table(inner) => flatten(inner, &full, out),
+            _ => out.push((full, value.clone())),
+        }
+    }
+}
+
 pub fn show_config() -> Result<()> {
-    let path = get_config_path()?;
-    println!("Config: {}", path.display());
-    println!();
+    let layered = LayeredConfig::load()?;
 
-    if path.exists() {
-        let config = Config::load()?;
-        println!("{}", toml::to_string_pretty(&config)?);
+    let global_note = if layered.global_path.exists() {
+        ""
     } else {
-        println!("(default config, file not created)");
-        println!();
-        let config = Config::default();
-        println!("{}", toml::to_string_pretty(&config)?);
+        " (not created)"
+    };
+    println!("Global:  {}{}", layered.global_path.display(), global_note);
+    match &layered.project_path {
+        Some(path) => println!("Project: {}", path.display()),
+        None => println!("Project: (no {} found)", PROJECT_CONFIG_FILE),
+    }
+    println!();
+
+    let effective = toml::Table::try_from(&layered.config)?;
+    let mut leaves = Vec::new();
+    flatten(&effective, "", &mut leaves);
+
+    let width = leaves.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+    for (key, value) in leaves {
+        println!(
+            "{:<width$} = {}  # {}",
+            key,
+            value,
+            layered.source_of(&key).label(),
+            width = width
+        );
     }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(path: &Path, content: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let tmp = tempfile::tempdir().unwrap();
+        let nested = tmp.path().join("packages").join("web");
+        fs::create_dir_all(&nested).unwrap();
+        write(&tmp.path().join(PROJECT_CONFIG_FILE), "");
+
+        assert_eq!(
+            find_project_config(&nested),
+            Some(tmp.path().join(PROJECT_CONFIG_FILE))
+        );
+    }
+
+    #[test]
+    fn test_project_overrides_field_by_field() {
+        let tmp = tempfile::tempdir().unwrap();
+        let global = tmp.path().join("global.toml");
+        let project = tmp.path().join("repo").join(PROJECT_CONFIG_FILE);
+        write(&global, "[tee]\nmode = \"always\"\nmax_files = 5\n");
+        write(
+            &project,
+            "[tee]\nmode = \"never\"\n[filters]\nignore_dirs = [\"dist\"]\n",
+        );
+
+        let layered = LayeredConfig::from_paths(global, Some(project)).unwrap();
+        let config = &layered.config;
+
+        assert_eq!(config.tee.mode, crate::tee::TeeMode::Never);
+        assert_eq!(config.tee.max_files, 5);
+        assert_eq!(config.filters.ignore_dirs, vec!["dist".to_string()]);
+        // Untouched fields keep their defaults
+        assert!(config.tracking.enabled);
+
+        assert_eq!(layered.source_of("tee.mode"), ConfigSource::Project);
+        assert_eq!(layered.source_of("tee.max_files"), ConfigSource::Global);
+        assert_eq!(layered.source_of("tracking.enabled"), ConfigSource::Default);
+    }
+
+    #[test]
+    fn test_project_relative_paths_resolved() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join(PROJECT_CONFIG_FILE);
+        write(
+            &project,
+            "[tracking]\ndatabase_path = \".rtk/history.db\"\n[tee]\ndirectory = \"/abs/tee\"\n",
+        );
+
+        let layered =
+            LayeredConfig::from_paths(tmp.path().join("missing.toml"), Some(project)).unwrap();
+
+        assert_eq!(
+            layered.config.tracking.database_path,
+            Some(tmp.path().join(".rtk/history.db"))
+        );
+        assert_eq!(
+            layered.config.tee.directory,
+            Some(PathBuf::from("/abs/tee"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_project_config_names_file() {
+        let tmp = tempfile::tempdir().unwrap();
+        let project = tmp.path().join(PROJECT_CONFIG_FILE);
+        write(&project, "[tee\n");
+
+        let err = LayeredConfig::from_paths(tmp.path().join("missing.toml"), Some(project))
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains(PROJECT_CONFIG_FILE));
+    }
+
+    #[test]
+    fn test_append_custom_filters_keeps_existing_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rtk/config.toml");
+        write(&path, "# my settings\n[tee]\nmode = \"never\"");
+        let stub = UserFilterConfig {
+            name: "make-build".to_string(),
+            command: r"^make build(\s|$)".to_string(),
+            strip: vec![r"^make\[\d+\]:".to_string()],
+            keep: Vec::new(),
+            dedup: true,
+            head: None,
+            tail: Some(30),
+            summary: None,
+        };
+        append_custom_filters_to(&path, &[stub], "Generated by rtk discover --apply").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("# my settings\n[tee]\nmode = \"never\"\n"));
+        assert!(content.contains("# Generated by rtk discover --apply\n[[filters.custom]]\n"));
+        let table: toml::Table = content.parse().unwrap();
+        let custom: Vec<UserFilterConfig> = table["filters"]["custom"].clone().try_into().unwrap();
+        assert_eq!(custom.len(), 1);
+        assert_eq!(custom[0].strip, vec![r"^make\[\d+\]:"]);
+        assert_eq!(custom[0].tail, Some(30));
+        assert_eq!(table["tee"]["mode"].as_str(), Some("never"));
+    }
+}
@@ -151,7 +151,7 @@ fn docker_images(_verbose: u8) -> Result<()> {
     Ok(())
 }
 
-fn docker_logs(args: &[String], _verbose: u8) -> Result<()> {
+fn docker_logs(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     let container = args.first().map(|s| s.as_str()).unwrap_or("");
@@ -159,19 +159,30 @@ fn docker_logs(args: &[String], _verbose: u8) -> Result<()> {
         println!("Usage: rtk docker logs <container>");
         return Ok(());
     }
+    let follow = args.iter().any(|a| a == "-f" || a == "--follow");
 
-    let output = Command::new("docker")
-        .args(["logs", "--tail", "100", container])
-        .output()
-        .context("Failed to run docker logs")?;
+    let mut cmd = Command::new("docker");
+    cmd.args(["logs", "--tail", "100", container]);
+    if follow {
+        cmd.arg("--follow");
+    }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let (raw, rtk) = if follow {
+        println!("🐳 Following logs for {}:", container);
+        let output = run_log_stream(&mut cmd, verbose).context("Failed to run docker logs")?;
+        (output.raw.clone(), output.filtered())
+    } else {
+        let output = cmd.output().context("Failed to run docker logs")?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let raw = format!("{}\n{}", stdout, stderr);
+
+        let analyzed = crate::log_cmd::run_stdin_str(&raw);
+        let rtk = format!("🐳 Logs for {}:\n{}", container, analyzed);
+        println!("{}", rtk);
+        (raw, rtk)
+    };
 
-    let analyzed = crate::log_cmd::run_stdin_str(&raw);
-    let rtk = format!("🐳 Logs for {}:\n{}", container, analyzed);
-    println!("{}", rtk);
     timer.track(
         &format!("docker logs {}", container),
         "rtk docker logs",
@@ -181,6 +192,16 @@ fn docker_logs(args: &[String], _verbose: u8) -> Result<()> {
     Ok(())
 }
 
+/// Follow mode: print new unique errors/warnings as they arrive, summary at the end.
+fn run_log_stream(cmd: &mut Command, verbose: u8) -> Result<crate::stream::StreamOutput> {
+    if verbose > 0 {
+        eprintln!("Streaming: {:?}", cmd);
+    }
+    let output = crate::stream::run(cmd, &mut crate::log_cmd::LogStream::default())?;
+    println!("{}", output.flushed);
+    Ok(output)
+}
+
 fn kubectl_pods(args: &[String], _verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
@@ -356,7 +377,7 @@ fn kubectl_services(args: &[String], _verbose: u8) -> Result<()> {
     Ok(())
 }
 
-fn kubectl_logs(args: &[String], _verbose: u8) -> Result<()> {
+fn kubectl_logs(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
     let pod = args.first().map(|s| s.as_str()).unwrap_or("");
@@ -364,6 +385,7 @@ fn kubectl_logs(args: &[String], _verbose: u8) -> Result<()> {
         println!("Usage: rtk kubectl logs <pod>");
         return Ok(());
     }
+    let follow = args.iter().any(|a| a == "-f" || a == "--follow");
 
     let mut cmd = Command::new("kubectl");
     cmd.args(["logs", "--tail", "100", pod]);
@@ -371,11 +393,19 @@ fn kubectl_logs(args: &[String], _verbose: u8) -> Result<()> {
         cmd.arg(arg);
     }
 
-    let output = cmd.output().context("Failed to run kubectl logs")?;
-    let raw = String::from_utf8_lossy(&output.stdout).to_string();
-    let analyzed = crate::log_cmd::run_stdin_str(&raw);
-    let rtk = format!("☸️  Logs for {}:\n{}", pod, analyzed);
-    println!("{}", rtk);
+    let (raw, rtk) = if follow {
+        println!("☸️  Following logs for {}:", pod);
+        let output = run_log_stream(&mut cmd, verbose).context("Failed to run kubectl logs")?;
+        (output.raw.clone(), output.filtered())
+    } else {
+        let output = cmd.output().context("Failed to run kubectl logs")?;
+        let raw = String::from_utf8_lossy(&output.stdout).to_string();
+        let analyzed = crate::log_cmd::run_stdin_str(&raw);
+        let rtk = format!("☸️  Logs for {}:\n{}", pod, analyzed);
+        println!("{}", rtk);
+        (raw, rtk)
+    };
+
     timer.track(
         &format!("kubectl logs {}", pod),
         "rtk kubectl logs",
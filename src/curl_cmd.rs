@@ -47,7 +47,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     Ok(())
 }
 
-fn filter_curl_output(output: &str) -> String {
+pub(crate) fn filter_curl_output(output: &str) -> String {
     let trimmed = output.trim();
 
     // Try JSON detection: starts with { or [
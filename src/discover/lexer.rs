@@ -0,0 +1,356 @@
+//! Shell-aware splitting of a command line into simple commands.
+//!
+//! Understands quotes, backslash escapes, `&&`, `||`, `;`, `&`, newlines,
+//! pipes, `( ... )` / `{ ...; }` groups and `$( ... )` / backtick substitutions
+//! (which are never split). Segments are returned as byte spans into the
+//! original string so callers can splice rewrites back in place.
+
+/// A simple command within a command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Segment {
+    pub start: usize,
+    pub end: usize,
+    /// stdin comes from a pipe (`b` in `a | b`)
+    pub piped_input: bool,
+    /// stdout goes into a pipe (`a` in `a | b`)
+    pub piped_output: bool,
+}
+
+impl Segment {
+    pub fn text<'a>(&self, cmd: &'a str) -> &'a str {
+        &cmd[self.start..self.end]
+    }
+}
+
+/// Keywords that introduce a command body; the command after them is what runs.
+const BODY_KEYWORDS: &[&str] = &["then", "do", "else"];
+
+/// Split a command line into simple commands.
+/// Heredocs (`<<`) are returned whole: their body is not shell syntax.
+pub fn split(cmd: &str) -> Vec<Segment> {
+    if cmd.contains("<<") {
+        return finish(cmd, vec![(0, cmd.len(), false, false)]);
+    }
+
+    let bytes = cmd.as_bytes();
+    let len = bytes.len();
+    let mut raw: Vec<(usize, usize, bool, bool)> = Vec::new();
+    let mut start = 0;
+    let mut piped_input = false;
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut in_backtick = false;
+    let mut subst_depth = 0usize;
+    let mut group_depth = 0usize;
+    let mut i = 0;
+
+    // Close the current segment at `end`; an empty segment before a pipe means
+    // the pipe applies to the preceding group, so mark that segment instead.
+    let push = |raw: &mut Vec<(usize, usize, bool, bool)>,
+                start: usize,
+                end: usize,
+                piped_input: bool,
+                piped_output: bool| {
+        if cmd[start..end].trim().is_empty() {
+            if piped_output {
+                if let Some(last) = raw.last_mut() {
+                    last.3 = true;
+                }
+            }
+        } else {
+            raw.push((start, end, piped_input, piped_output));
+        }
+    };
+
+    while i < len {
+        let b = bytes[i];
+
+        if in_single {
+            if b == b'\'' {
+                in_single = false;
+            }
+            i += 1;
+            continue;
+        }
+        if b == b'\\' {
+            i += 2;
+            continue;
+        }
+        if in_double {
+            if b == b'"' {
+                in_double = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match b {
+            b'\'' => in_single = true,
+            b'"' => in_double = true,
+            b'`' => in_backtick = !in_backtick,
+            _ if in_backtick => {}
+            b'$' if i + 1 < len && bytes[i + 1] == b'(' => {
+                subst_depth += 1;
+                i += 2;
+                continue;
+            }
+            b'(' if subst_depth > 0 => subst_depth += 1,
+            b')' if subst_depth > 0 => subst_depth -= 1,
+            _ if subst_depth > 0 => {}
+            b'#' if i == 0 || bytes[i - 1].is_ascii_whitespace() => {
+                // Comment: runs to end of line
+                push(&mut raw, start, i, piped_input, false);
+                piped_input = false;
+                while i < len && bytes[i] != b'\n' {
+                    i += 1;
+                }
+                start = i;
+                continue;
+            }
+            b'(' if cmd[start..i].trim().is_empty() => {
+                group_depth += 1;
+                start = i + 1;
+            }
+            b'{' | b'}' if cmd[start..i].trim().is_empty() => {
+                // Brace group opener/closer in command position
+                start = i + 1;
+            }
+            b')' if group_depth > 0 => {
+                group_depth -= 1;
+                push(&mut raw, start, i, piped_input, false);
+                piped_input = false;
+                start = i + 1;
+            }
+            b'&' if i + 1 < len && bytes[i + 1] == b'&' => {
+                push(&mut raw, start, i, piped_input, false);
+                piped_input = false;
+                i += 2;
+                start = i;
+                continue;
+            }
+            b'&' if (i + 1 < len && bytes[i + 1] == b'>')
+                || (i > 0 && matches!(bytes[i - 1], b'>' | b'<')) =>
+            {
+                // Redirection (`&>file`, `2>&1`), not a separator
+            }
+            b'|' if i + 1 < len && bytes[i + 1] == b'|' => {
+                push(&mut raw, start, i, piped_input, false);
+                piped_input = false;
+                i += 2;
+                start = i;
+                continue;
+            }
+            b'|' => {
+                push(&mut raw, start, i, piped_input, true);
+                piped_input = true;
+                // `|&` also pipes stderr
+                i += if i + 1 < len && bytes[i + 1] == b'&' {
+                    2
+                } else {
+                    1
+                };
+                start = i;
+                continue;
+            }
+            b'&' | b';' | b'\n' => {
+                push(&mut raw, start, i, piped_input, false);
+                piped_input = false;
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    if start < len {
+        push(&mut raw, start, len, piped_input, false);
+    }
+
+    finish(cmd, raw)
+}
+
+/// Trim whitespace and leading body keywords from raw spans.
+fn finish(cmd: &str, raw: Vec<(usize, usize, bool, bool)>) -> Vec<Segment> {
+    raw.into_iter()
+        .filter_map(|(mut start, mut end, piped_input, piped_output)| {
+            loop {
+                let text = &cmd[start..end];
+                let trimmed = text.trim_start();
+                start += text.len() - trimmed.len();
+                match BODY_KEYWORDS.iter().find(|kw| {
+                    trimmed
+                        .strip_prefix(**kw)
+                        .is_some_and(|rest| rest.starts_with(char::is_whitespace))
+                }) {
+                    Some(kw) => start += kw.len(),
+                    None => break,
+                }
+            }
+            end = start + cmd[start..end].trim_end().len();
+            (start < end).then_some(Segment {
+                start,
+                end,
+                piped_input,
+                piped_output,
+            })
+        })
+        .collect()
+}
+
+/// Whether a simple command sends its stdout to a file (`> out`, `>> out`, `&> out`).
+/// Redirects to /dev/null, to another fd (`>&2`) and of stderr only (`2>`) don't count.
+pub fn writes_stdout_to_file(segment: &str) -> bool {
+    let bytes = segment.as_bytes();
+    let len = bytes.len();
+    let mut in_single = false;
+    let mut in_double = false;
+    let mut i = 0;
+
+    while i < len {
+        match bytes[i] {
+            b'\\' if !in_single => {
+                i += 2;
+                continue;
+            }
+            b'\'' if !in_double => in_single = !in_single,
+            b'"' if !in_single => in_double = !in_double,
+            b'>' if !in_single && !in_double => {
+                let fd = if i > 0 { bytes[i - 1] } else { b' ' };
+                let mut j = i + 1;
+                while j < len && matches!(bytes[j], b'>' | b'|') {
+                    j += 1;
+                }
+                let target = segment[j..].split_whitespace().next().unwrap_or("");
+                let is_stdout = !(fd.is_ascii_digit() && fd != b'1');
+                if is_stdout && !target.starts_with('&') && target != "/dev/null" {
+                    return true;
+                }
+                i = j;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(cmd: &str) -> Vec<&str> {
+        split(cmd).iter().map(|s| s.text(cmd)).collect()
+    }
+
+    #[test]
+    fn test_split_operators() {
+        assert_eq!(texts("a && b || c ; d"), vec!["a", "b", "c", "d"]);
+        assert_eq!(texts("a & b"), vec!["a", "b"]);
+        assert_eq!(texts("a\nb"), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_split_quotes_and_escapes() {
+        assert_eq!(
+            texts(r#"echo "a && b" && c"#),
+            vec![r#"echo "a && b""#, "c"]
+        );
+        assert_eq!(texts("echo 'a; b'; c"), vec!["echo 'a; b'", "c"]);
+        assert_eq!(
+            texts(r"find . -exec rm {} \; && ls"),
+            vec![r"find . -exec rm {} \;", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_split_redirects_are_not_separators() {
+        assert_eq!(
+            texts("cargo test 2>&1 && ls"),
+            vec!["cargo test 2>&1", "ls"]
+        );
+        assert_eq!(
+            texts("make &> build.log; ls"),
+            vec!["make &> build.log", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_split_subshells_and_groups() {
+        assert_eq!(
+            texts("(cd web && npm test) && cargo test"),
+            vec!["cd web", "npm test", "cargo test"]
+        );
+        assert_eq!(
+            texts("{ git status; git diff; }"),
+            vec!["git status", "git diff"]
+        );
+        assert_eq!(texts("arr=(1 2) && ls"), vec!["arr=(1 2)", "ls"]);
+    }
+
+    #[test]
+    fn test_split_substitution_not_split() {
+        assert_eq!(
+            texts("echo $(git rev-parse HEAD && date) && ls"),
+            vec!["echo $(git rev-parse HEAD && date)", "ls"]
+        );
+        assert_eq!(texts("echo `a && b`; c"), vec!["echo `a && b`", "c"]);
+        assert_eq!(
+            texts("echo $((1 + 2)) && ls"),
+            vec!["echo $((1 + 2))", "ls"]
+        );
+    }
+
+    #[test]
+    fn test_split_pipes() {
+        let segs = split("git log | grep fix && cargo test");
+        assert_eq!(segs.len(), 3);
+        assert!(segs[0].piped_output && !segs[0].piped_input);
+        assert!(segs[1].piped_input && !segs[1].piped_output);
+        assert!(!segs[2].piped_input && !segs[2].piped_output);
+    }
+
+    #[test]
+    fn test_split_group_piped_output() {
+        let segs = split("(a; b) | c");
+        assert_eq!(segs.len(), 3);
+        assert!(segs[1].piped_output);
+        assert!(segs[2].piped_input);
+    }
+
+    #[test]
+    fn test_split_body_keywords() {
+        assert_eq!(
+            texts("if true; then cargo test; else cargo build; fi"),
+            vec!["if true", "cargo test", "cargo build", "fi"]
+        );
+        assert_eq!(
+            texts("for f in *.rs; do cat $f; done"),
+            vec!["for f in *.rs", "cat $f", "done"]
+        );
+    }
+
+    #[test]
+    fn test_split_comment() {
+        assert_eq!(texts("git status # && rm -rf /"), vec!["git status"]);
+        assert_eq!(texts("# setup\ncargo test"), vec!["cargo test"]);
+    }
+
+    #[test]
+    fn test_split_heredoc_whole() {
+        let cmd = "cat <<'EOF'\nhello && world\nEOF";
+        assert_eq!(texts(cmd), vec![cmd]);
+    }
+
+    #[test]
+    fn test_writes_stdout_to_file() {
+        assert!(writes_stdout_to_file("git diff > patch.diff"));
+        assert!(writes_stdout_to_file("cargo test >> log.txt"));
+        assert!(writes_stdout_to_file("make &> build.log"));
+        assert!(!writes_stdout_to_file("cargo test 2>&1"));
+        assert!(!writes_stdout_to_file("cargo test 2> err.log"));
+        assert!(!writes_stdout_to_file("cargo build > /dev/null"));
+        assert!(!writes_stdout_to_file("echo 'a > b'"));
+    }
+}
@@ -1,13 +1,18 @@
+mod lexer;
 pub mod provider;
 pub mod registry;
 mod report;
+mod stubs;
 
 use anyhow::Result;
 use std::collections::HashMap;
 
-use provider::{ClaudeProvider, SessionProvider};
-use registry::{category_avg_tokens, classify_command, split_command_chain, Classification};
-use report::{DiscoverReport, SupportedEntry, UnsupportedEntry};
+use crate::replay::{self, Measurement};
+use provider::SessionScope;
+use registry::{
+    category_avg_tokens, classify_command, split_command_chain, strip_env_prefix, Classification,
+};
+use report::{DiscoverReport, FilterStubEntry, MeasuredSavings, SupportedEntry, UnsupportedEntry};
 
 /// Aggregation bucket for supported commands.
 struct SupportedBucket {
@@ -16,6 +21,8 @@ struct SupportedBucket {
     count: usize,
     total_output_tokens: usize,
     savings_pct: f64,
+    /// Recorded outputs replayed through the rtk filter
+    measurements: Vec<Measurement>,
     // For display: the most common raw command
     command_counts: HashMap<String, usize>,
 }
@@ -24,32 +31,19 @@ struct SupportedBucket {
 struct UnsupportedBucket {
     count: usize,
     example: String,
+    /// Outputs of the command run on its own, for `--apply`
+    samples: Vec<String>,
 }
 
 pub fn run(
-    project: Option<&str>,
-    all: bool,
-    since_days: u64,
+    scope: &SessionScope,
     limit: usize,
     format: &str,
+    apply: bool,
     verbose: u8,
 ) -> Result<()> {
-    let provider = ClaudeProvider;
-
-    // Determine project filter
-    let project_filter = if all {
-        None
-    } else if let Some(p) = project {
-        Some(p.to_string())
-    } else {
-        // Default: current working directory
-        let cwd = std::env::current_dir()?;
-        let cwd_str = cwd.to_string_lossy().to_string();
-        let encoded = ClaudeProvider::encode_project_path(&cwd_str);
-        Some(encoded)
-    };
-
-    let sessions = provider.discover_sessions(project_filter.as_deref(), Some(since_days))?;
+    let since_days = scope.since_days;
+    let (provider, sessions) = scope.discover()?;
 
     if verbose > 0 {
         eprintln!("Scanning {} session files...", sessions.len());
@@ -78,6 +72,16 @@ pub fn run(
 
         for ext_cmd in &extracted {
             let parts = split_command_chain(&ext_cmd.command);
+            // The output belongs to the whole chain (or the last command of a
+            // pipe): only sample commands run on their own
+            let sample = match (parts.as_slice(), &ext_cmd.output_content) {
+                ([part], Some(output))
+                    if part.trim() == ext_cmd.command.trim() && !output.trim().is_empty() =>
+                {
+                    Some(output)
+                }
+                _ => None,
+            };
             for part in parts {
                 total_commands += 1;
 
@@ -95,24 +99,31 @@ pub fn run(
                                 count: 0,
                                 total_output_tokens: 0,
                                 savings_pct: estimated_savings_pct,
+                                measurements: Vec::new(),
                                 command_counts: HashMap::new(),
                             }
                         });
 
                         bucket.count += 1;
 
-                        // Estimate tokens for this command
-                        let output_tokens = if let Some(len) = ext_cmd.output_len {
-                            // Real: from tool_result content length
-                            len / 4
+                        // Measured: the recorded output replayed through the rtk filter
+                        let measured = sample
+                            .and_then(|output| replay::measure(strip_env_prefix(part), output));
+                        let savings = if let Some(measurement) = measured {
+                            bucket.measurements.push(measurement);
+                            measurement.saved_tokens()
                         } else {
-                            // Fallback: category average
-                            let subcmd = extract_subcmd(part);
-                            category_avg_tokens(category, subcmd)
+                            // Estimate tokens for this command
+                            let output_tokens = if let Some(len) = ext_cmd.output_len {
+                                // Real: from tool_result content length
+                                len / 4
+                            } else {
+                                // Fallback: category average
+                                let subcmd = extract_subcmd(part);
+                                category_avg_tokens(category, subcmd)
+                            };
+                            (output_tokens as f64 * estimated_savings_pct / 100.0) as usize
                         };
-
-                        let savings =
-                            (output_tokens as f64 * estimated_savings_pct / 100.0) as usize;
                         bucket.total_output_tokens += savings;
 
                         // Track the display name with status
@@ -128,9 +139,15 @@ pub fn run(
                             UnsupportedBucket {
                                 count: 0,
                                 example: part.to_string(),
+                                samples: Vec::new(),
                             }
                         });
                         bucket.count += 1;
+                        if let Some(output) = sample {
+                            if bucket.samples.len() < stubs::MAX_SAMPLES {
+                                bucket.samples.push(output.clone());
+                            }
+                        }
                     }
                     Classification::Ignored => {
                         // Check if it starts with "rtk "
@@ -177,6 +194,7 @@ pub fn run(
                 category: bucket.category,
                 estimated_savings_tokens: bucket.total_output_tokens,
                 estimated_savings_pct: bucket.savings_pct,
+                measured: summarize_measurements(&bucket.measurements),
                 rtk_status: status,
             }
         })
@@ -185,18 +203,29 @@ pub fn run(
     // Sort by estimated savings descending
     supported.sort_by(|a, b| b.estimated_savings_tokens.cmp(&a.estimated_savings_tokens));
 
+    let mut samples: HashMap<String, Vec<String>> = HashMap::new();
     let mut unsupported: Vec<UnsupportedEntry> = unsupported_map
         .into_iter()
-        .map(|(base, bucket)| UnsupportedEntry {
-            base_command: base,
-            count: bucket.count,
-            example: bucket.example,
+        .map(|(base, bucket)| {
+            samples.insert(base.clone(), bucket.samples);
+            UnsupportedEntry {
+                base_command: base,
+                count: bucket.count,
+                example: bucket.example,
+            }
         })
         .collect();
 
     // Sort by count descending
     unsupported.sort_by(|a, b| b.count.cmp(&a.count));
 
+    let (filter_stubs, stubs_path) = if apply {
+        let (stubs, path) = write_filter_stubs(&unsupported, &samples)?;
+        (Some(stubs), path.map(|p| p.display().to_string()))
+    } else {
+        (None, None)
+    };
+
     let report = DiscoverReport {
         sessions_scanned: sessions.len(),
         total_commands,
@@ -205,6 +234,8 @@ pub fn run(
         supported,
         unsupported,
         parse_errors,
+        filter_stubs,
+        stubs_path,
     };
 
     match format {
@@ -215,6 +246,81 @@ pub fn run(
     Ok(())
 }
 
+/// Infer filter stubs for the most frequent unhandled commands and append
+/// them to the global config. Commands already matched by a custom filter
+/// are skipped. Returns the stubs and the config path (None if none written).
+fn write_filter_stubs(
+    unsupported: &[UnsupportedEntry],
+    samples: &HashMap<String, Vec<String>>,
+) -> Result<(Vec<FilterStubEntry>, Option<std::path::PathBuf>)> {
+    let config = crate::config::Config::load()?;
+    let existing = crate::user_filter::load_filters(&config.filters.custom)?;
+
+    let mut entries = Vec::new();
+    let mut filters = Vec::new();
+    for entry in unsupported {
+        if filters.len() >= stubs::MAX_STUBS {
+            break;
+        }
+        if crate::user_filter::find_filter(&existing, &entry.example).is_some() {
+            continue;
+        }
+        let sampled = samples
+            .get(&entry.base_command)
+            .map_or(&[][..], Vec::as_slice);
+        let stub = match stubs::propose(&entry.base_command, sampled) {
+            Some(s) => s,
+            None => continue,
+        };
+        if config
+            .filters
+            .custom
+            .iter()
+            .any(|f| f.name == stub.filter.name)
+        {
+            continue;
+        }
+        entries.push(FilterStubEntry {
+            name: stub.filter.name.clone(),
+            base_command: entry.base_command.clone(),
+            count: entry.count,
+            samples: sampled.len(),
+            reduction_pct: stub.reduction_pct,
+            example: entry.example.clone(),
+        });
+        filters.push(stub.filter);
+    }
+
+    if filters.is_empty() {
+        return Ok((entries, None));
+    }
+    let comment = format!(
+        "Generated by `rtk discover --apply` on {} -- review, then use `rtk run <cmd>`",
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+    let path = crate::config::append_custom_filters(&filters, &comment)?;
+    Ok((entries, Some(path)))
+}
+
+/// Totals and spread of the per-run savings of replayed outputs.
+fn summarize_measurements(measurements: &[Measurement]) -> Option<MeasuredSavings> {
+    if measurements.is_empty() {
+        return None;
+    }
+    let mut pcts: Vec<f64> = measurements.iter().map(Measurement::savings_pct).collect();
+    pcts.sort_by(|a, b| a.total_cmp(b));
+    let n = pcts.len();
+    let median_pct = (pcts[(n - 1) / 2] + pcts[n / 2]) / 2.0;
+    Some(MeasuredSavings {
+        runs: measurements.len(),
+        input_tokens: measurements.iter().map(|m| m.input_tokens).sum(),
+        saved_tokens: measurements.iter().map(Measurement::saved_tokens).sum(),
+        min_pct: pcts[0],
+        median_pct,
+        max_pct: pcts[n - 1],
+    })
+}
+
 /// Extract the subcommand from a command string (second word).
 fn extract_subcmd(cmd: &str) -> &str {
     let parts: Vec<&str> = cmd.trim().splitn(3, char::is_whitespace).collect();
@@ -236,3 +342,34 @@ fn truncate_command(cmd: &str) -> String {
         _ => format!("{} {}", parts[0], parts[1]),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(input_tokens: usize, output_tokens: usize) -> Measurement {
+        Measurement {
+            input_tokens,
+            output_tokens,
+        }
+    }
+
+    #[test]
+    fn test_summarize_measurements_spread() {
+        assert!(summarize_measurements(&[]).is_none());
+
+        let summary = summarize_measurements(&[
+            measurement(100, 10),
+            measurement(100, 50),
+            measurement(200, 200),
+            measurement(400, 100),
+        ])
+        .unwrap();
+        assert_eq!(summary.runs, 4);
+        assert_eq!(summary.input_tokens, 800);
+        assert_eq!(summary.saved_tokens, 90 + 50 + 300);
+        assert_eq!(summary.min_pct, 0.0);
+        assert_eq!(summary.median_pct, 62.5);
+        assert_eq!(summary.max_pct, 90.0);
+    }
+}
@@ -1,4 +1,5 @@
 use anyhow::{Context, Result};
+use regex::Regex;
 use std::collections::HashMap;
 use std::fs;
 use std::io::{BufRead, BufReader};
@@ -6,6 +7,9 @@ use std::path::{Path, PathBuf};
 use std::time::{Duration, SystemTime};
 use walkdir::WalkDir;
 
+/// Names accepted by `--provider`, default first.
+pub const PROVIDER_NAMES: [&str; 3] = ["claude", "codex", "aider"];
+
 /// A command extracted from a session file.
 #[derive(Debug)]
 pub struct ExtractedCommand {
@@ -13,7 +17,7 @@ pub struct ExtractedCommand {
     pub output_len: Option<usize>,
     #[allow(dead_code)]
     pub session_id: String,
-    /// Actual output content (first ~1000 chars for error detection)
+    /// Full output of the command, as the agent saw it
     pub output_content: Option<String>,
     /// Whether the tool_result indicated an error
     pub is_error: bool,
@@ -21,8 +25,12 @@ pub struct ExtractedCommand {
     pub sequence_index: usize,
 }
 
-/// Trait for session providers (Claude Code, future: Cursor, Windsurf).
+/// Trait for session providers (Claude Code, Codex CLI, Aider; future: Cursor, Windsurf).
 pub trait SessionProvider {
+    /// Tool name for messages ("Claude Code")
+    fn name(&self) -> &'static str;
+    /// Project filter matching sessions recorded in `cwd` (the default scope)
+    fn project_filter(&self, cwd: &Path) -> String;
     fn discover_sessions(
         &self,
         project_filter: Option<&str>,
@@ -31,6 +39,63 @@ pub trait SessionProvider {
     fn extract_commands(&self, path: &Path) -> Result<Vec<ExtractedCommand>>;
 }
 
+/// Provider selected with `--provider` (one of [`PROVIDER_NAMES`]).
+pub fn from_name(name: &str) -> Result<Box<dyn SessionProvider>> {
+    match name {
+        "claude" => Ok(Box::new(ClaudeProvider)),
+        "codex" => Ok(Box::new(CodexProvider)),
+        "aider" => Ok(Box::new(AiderProvider)),
+        _ => anyhow::bail!(
+            "unknown provider '{}' (expected one of: {})",
+            name,
+            PROVIDER_NAMES.join(", ")
+        ),
+    }
+}
+
+/// Which sessions to scan, from `--provider`, `--project` / `--all` and `--since`.
+pub struct SessionScope<'a> {
+    pub provider: &'a str,
+    pub project: Option<&'a str>,
+    pub all: bool,
+    pub since_days: u64,
+}
+
+impl SessionScope<'_> {
+    /// The selected provider and its session files in scope. Without
+    /// `--project` or `--all`, only sessions of the current directory.
+    pub fn discover(&self) -> Result<(Box<dyn SessionProvider>, Vec<PathBuf>)> {
+        let provider = from_name(self.provider)?;
+        let project_filter = if self.all {
+            None
+        } else if let Some(p) = self.project {
+            Some(p.to_string())
+        } else {
+            let cwd = std::env::current_dir()?;
+            Some(provider.project_filter(&cwd))
+        };
+        let sessions =
+            provider.discover_sessions(project_filter.as_deref(), Some(self.since_days))?;
+        Ok((provider, sessions))
+    }
+}
+
+fn mtime_cutoff(since_days: Option<u64>) -> Option<SystemTime> {
+    since_days.map(|days| {
+        SystemTime::now()
+            .checked_sub(Duration::from_secs(days * 86400))
+            .unwrap_or(SystemTime::UNIX_EPOCH)
+    })
+}
+
+/// True unless the file is known to be older than `cutoff`.
+fn modified_after(path: &Path, cutoff: Option<SystemTime>) -> bool {
+    match (cutoff, fs::metadata(path).and_then(|m| m.modified())) {
+        (Some(cutoff), Ok(mtime)) => mtime >= cutoff,
+        _ => true,
+    }
+}
+
 pub struct ClaudeProvider;
 
 impl ClaudeProvider {
@@ -55,17 +120,21 @@ impl ClaudeProvider {
 }
 
 impl SessionProvider for ClaudeProvider {
+    fn name(&self) -> &'static str {
+        "Claude Code"
+    }
+
+    fn project_filter(&self, cwd: &Path) -> String {
+        Self::encode_project_path(&cwd.to_string_lossy())
+    }
+
     fn discover_sessions(
         &self,
         project_filter: Option<&str>,
         since_days: Option<u64>,
     ) -> Result<Vec<PathBuf>> {
         let projects_dir = Self::projects_dir()?;
-        let cutoff = since_days.map(|days| {
-            SystemTime::now()
-                .checked_sub(Duration::from_secs(days * 86400))
-                .unwrap_or(SystemTime::UNIX_EPOCH)
-        });
+        let cutoff = mtime_cutoff(since_days);
 
         let mut sessions = Vec::new();
 
@@ -99,14 +168,8 @@ impl SessionProvider for ClaudeProvider {
                 }
 
                 // Apply mtime filter
-                if let Some(cutoff_time) = cutoff {
-                    if let Ok(meta) = fs::metadata(file_path) {
-                        if let Ok(mtime) = meta.modified() {
-                            if mtime < cutoff_time {
-                                continue;
-                            }
-                        }
-                    }
+                if !modified_after(file_path, cutoff) {
+                    continue;
                 }
 
                 sessions.push(file_path.to_path_buf());
@@ -196,13 +259,9 @@ impl SessionProvider for ClaudeProvider {
                                         .and_then(|e| e.as_bool())
                                         .unwrap_or(false);
 
-                                    // Store first ~1000 chars of content for error detection
-                                    let content_preview: String =
-                                        content.chars().take(1000).collect();
-
                                     tool_results.insert(
                                         id.to_string(),
-                                        (output_len, content_preview, is_error),
+                                        (output_len, content.to_string(), is_error),
                                     );
                                 }
                             }
@@ -234,6 +293,374 @@ impl SessionProvider for ClaudeProvider {
     }
 }
 
+/// Codex CLI: rollout JSONL files under `$CODEX_HOME/sessions` (default
+/// `~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl`). The first line's
+/// `session_meta` records the working directory; shell calls are
+/// `function_call` / `local_shell_call` items paired with their
+/// `*_output` by `call_id`.
+pub struct CodexProvider;
+
+/// Codex tools that run a shell command.
+const CODEX_SHELL_TOOLS: [&str; 5] = [
+    "shell",
+    "shell_command",
+    "exec_command",
+    "container.exec",
+    "local_shell",
+];
+
+impl CodexProvider {
+    fn sessions_dir() -> Result<PathBuf> {
+        let home = match std::env::var_os("CODEX_HOME") {
+            Some(dir) => PathBuf::from(dir),
+            None => dirs::home_dir()
+                .context("could not determine home directory")?
+                .join(".codex"),
+        };
+        let dir = home.join("sessions");
+        if !dir.exists() {
+            anyhow::bail!(
+                "Codex sessions directory not found: {}\nMake sure Codex CLI has been used at least once.",
+                dir.display()
+            );
+        }
+        Ok(dir)
+    }
+
+    /// Working directory from the `session_meta` line at the top of a rollout.
+    fn session_cwd(path: &Path) -> Option<String> {
+        let file = fs::File::open(path).ok()?;
+        BufReader::new(file)
+            .lines()
+            .take(5)
+            .map_while(|l| l.ok())
+            .filter_map(|l| serde_json::from_str::<serde_json::Value>(&l).ok())
+            .find_map(|entry| {
+                entry
+                    .pointer("/payload/cwd")
+                    .and_then(|c| c.as_str())
+                    .map(str::to_string)
+            })
+    }
+
+    /// Command line from a shell call: `["bash", "-lc", "git status"]` →
+    /// `git status`, other argv joined with spaces.
+    fn command_text(value: &serde_json::Value) -> Option<String> {
+        if let Some(text) = value.as_str() {
+            return Some(text.to_string());
+        }
+        let argv: Vec<&str> = value
+            .as_array()?
+            .iter()
+            .filter_map(|a| a.as_str())
+            .collect();
+        match argv.as_slice() {
+            [shell, "-lc" | "-c", script]
+                if matches!(shell.rsplit('/').next(), Some("bash" | "sh" | "zsh")) =>
+            {
+                Some(script.to_string())
+            }
+            [] => None,
+            _ => Some(argv.join(" ")),
+        }
+    }
+
+    /// Output text and exit code of a call. Codex wraps output either as
+    /// JSON (`{"output": ..., "metadata": {"exit_code": N}}`) or as text
+    /// headed by `Exit code: N` ... `Output:`.
+    fn parse_output(raw: &str) -> (String, Option<i64>) {
+        lazy_static::lazy_static! {
+            static ref EXIT_CODE: Regex = Regex::new(r"^Exit code: (-?\d+)").unwrap();
+        }
+        if let Ok(wrapped) = serde_json::from_str::<serde_json::Value>(raw) {
+            if let Some(output) = wrapped.get("output").and_then(|o| o.as_str()) {
+                let exit_code = wrapped
+                    .pointer("/metadata/exit_code")
+                    .and_then(|c| c.as_i64());
+                return (output.to_string(), exit_code);
+            }
+        }
+        if let Some(caps) = EXIT_CODE.captures(raw) {
+            let output = raw.split_once("\nOutput:\n").map_or("", |(_, o)| o);
+            return (output.to_string(), caps[1].parse().ok());
+        }
+        (raw.to_string(), None)
+    }
+}
+
+impl SessionProvider for CodexProvider {
+    fn name(&self) -> &'static str {
+        "Codex CLI"
+    }
+
+    fn project_filter(&self, cwd: &Path) -> String {
+        cwd.to_string_lossy().to_string()
+    }
+
+    fn discover_sessions(
+        &self,
+        project_filter: Option<&str>,
+        since_days: Option<u64>,
+    ) -> Result<Vec<PathBuf>> {
+        let sessions_dir = Self::sessions_dir()?;
+        let cutoff = mtime_cutoff(since_days);
+
+        let mut sessions = Vec::new();
+        for entry in WalkDir::new(&sessions_dir)
+            .follow_links(false)
+            .into_iter()
+            .filter_map(|e| e.ok())
+        {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("jsonl")
+                || !modified_after(path, cutoff)
+            {
+                continue;
+            }
+            // Apply project filter: substring match on the recorded cwd
+            if let Some(filter) = project_filter {
+                match Self::session_cwd(path) {
+                    Some(cwd) if cwd.contains(filter) => {}
+                    _ => continue,
+                }
+            }
+            sessions.push(path.to_path_buf());
+        }
+        sessions.sort();
+        Ok(sessions)
+    }
+
+    fn extract_commands(&self, path: &Path) -> Result<Vec<ExtractedCommand>> {
+        let file =
+            fs::File::open(path).with_context(|| format!("failed to open {}", path.display()))?;
+        let session_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+
+        let mut calls: Vec<(String, String)> = Vec::new(); // (call_id, command)
+        let mut outputs: HashMap<String, (String, Option<i64>)> = HashMap::new();
+
+        for line in BufReader::new(file).lines() {
+            let line = match line {
+                Ok(l) => l,
+                Err(_) => continue,
+            };
+            if !line.contains("call") {
+                continue;
+            }
+            let entry: serde_json::Value = match serde_json::from_str(&line) {
+                Ok(v) => v,
+                Err(_) => continue,
+            };
+            // Current rollouts wrap items in {"type":"response_item","payload":{...}}
+            let item = entry.get("payload").unwrap_or(&entry);
+            let call_id = item
+                .get("call_id")
+                .and_then(|c| c.as_str())
+                .unwrap_or("")
+                .to_string();
+
+            match item.get("type").and_then(|t| t.as_str()).unwrap_or("") {
+                "function_call" => {
+                    let name = item.get("name").and_then(|n| n.as_str()).unwrap_or("");
+                    if !CODEX_SHELL_TOOLS.contains(&name) {
+                        continue;
+                    }
+                    let args: serde_json::Value = item
+                        .get("arguments")
+                        .and_then(|a| a.as_str())
+                        .and_then(|a| serde_json::from_str(a).ok())
+                        .unwrap_or_default();
+                    let command = args
+                        .get("command")
+                        .or_else(|| args.get("cmd"))
+                        .and_then(Self::command_text);
+                    if let Some(command) = command {
+                        calls.push((call_id, command));
+                    }
+                }
+                "local_shell_call" => {
+                    if let Some(command) =
+                        item.pointer("/action/command").and_then(Self::command_text)
+                    {
+                        calls.push((call_id, command));
+                    }
+                }
+                "function_call_output" | "local_shell_call_output" => {
+                    let raw = match item.get("output") {
+                        Some(serde_json::Value::String(s)) => s.clone(),
+                        Some(other) => other
+                            .get("content")
+                            .and_then(|c| c.as_str())
+                            .unwrap_or("")
+                            .to_string(),
+                        None => continue,
+                    };
+                    outputs.insert(call_id, Self::parse_output(&raw));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(calls
+            .into_iter()
+            .enumerate()
+            .map(|(sequence_index, (call_id, command))| {
+                let result = outputs.get(&call_id);
+                ExtractedCommand {
+                    command,
+                    output_len: result.map(|(output, _)| output.len()),
+                    session_id: session_id.clone(),
+                    output_content: result.map(|(output, _)| output.clone()),
+                    is_error: matches!(result, Some((_, Some(code))) if *code != 0),
+                    sequence_index,
+                }
+            })
+            .collect())
+    }
+}
+
+/// Aider: `.aider.chat.history.md`, kept per project (found from the
+/// project directory upwards, or `$AIDER_CHAT_HISTORY_FILE`). Commands are
+/// the user's `#### /run <cmd>`, `#### /test <cmd>` and `#### !<cmd>`, and
+/// suggested shell commands the user accepted (`> Running <cmd>`); their
+/// output follows as `> ` quoted lines. Aider does not record exit codes, so
+/// `is_error` is inferred from the output.
+pub struct AiderProvider;
+
+const AIDER_HISTORY_FILE: &str = ".aider.chat.history.md";
+
+impl AiderProvider {
+    /// Nearest history file at or above `dir`.
+    fn find_history(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(AIDER_HISTORY_FILE))
+            .find(|f| f.is_file())
+    }
+
+    /// Aider prompts echoed into the history, not command output.
+    fn is_prompt(line: &str) -> bool {
+        line.contains("(Y)es/(N)o") || line.contains("(Y)es/(n)o")
+    }
+
+    fn looks_failed(output: &str) -> bool {
+        lazy_static::lazy_static! {
+            static ref FAILURE: Regex = Regex::new(
+                r"(?im)^(error\b|fatal:|traceback)|command not found|no such file or directory|\bFAILED\b|\bexit (code|status) [1-9]"
+            )
+            .unwrap();
+        }
+        FAILURE.is_match(output)
+    }
+}
+
+impl SessionProvider for AiderProvider {
+    fn name(&self) -> &'static str {
+        "Aider"
+    }
+
+    fn project_filter(&self, cwd: &Path) -> String {
+        cwd.to_string_lossy().to_string()
+    }
+
+    fn discover_sessions(
+        &self,
+        project_filter: Option<&str>,
+        since_days: Option<u64>,
+    ) -> Result<Vec<PathBuf>> {
+        let history = match std::env::var_os("AIDER_CHAT_HISTORY_FILE") {
+            Some(file) => Some(PathBuf::from(file)).filter(|f| f.is_file()),
+            None => {
+                // Aider keeps history per project: start from the filter when it
+                // names a directory, otherwise from the current directory
+                let start = match project_filter.map(Path::new) {
+                    Some(dir) if dir.is_dir() => dir.to_path_buf(),
+                    _ => std::env::current_dir()?,
+                };
+                Self::find_history(&start)
+            }
+        };
+        let history = match history {
+            Some(h) => h,
+            None => anyhow::bail!(
+                "Aider chat history not found: no {} in this directory or its parents",
+                AIDER_HISTORY_FILE
+            ),
+        };
+
+        let matches_filter = match project_filter {
+            Some(filter) if !Path::new(filter).is_dir() => {
+                history.to_string_lossy().contains(filter)
+            }
+            _ => true,
+        };
+        if !matches_filter || !modified_after(&history, mtime_cutoff(since_days)) {
+            return Ok(Vec::new());
+        }
+        Ok(vec![history])
+    }
+
+    fn extract_commands(&self, path: &Path) -> Result<Vec<ExtractedCommand>> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+
+        let mut commands = Vec::new();
+        let mut session_id = "unknown".to_string();
+        // (command, output lines) of the command whose output is being read
+        let mut current: Option<(String, Vec<&str>)> = None;
+        let mut finish = |current: &mut Option<(String, Vec<&str>)>, session_id: &str| {
+            if let Some((command, lines)) = current.take() {
+                let output = lines.join("\n").trim_end().to_string();
+                commands.push(ExtractedCommand {
+                    is_error: Self::looks_failed(&output),
+                    output_len: Some(output.len()),
+                    output_content: Some(output),
+                    command,
+                    session_id: session_id.to_string(),
+                    sequence_index: 0,
+                });
+            }
+        };
+
+        for line in content.lines() {
+            if let Some(started) = line.strip_prefix("# aider chat started at ") {
+                finish(&mut current, &session_id);
+                session_id = started.trim().to_string();
+            } else if let Some(input) = line.strip_prefix("#### ") {
+                finish(&mut current, &session_id);
+                let input = input.trim();
+                let command = input
+                    .strip_prefix("/run ")
+                    .or_else(|| input.strip_prefix("/test "))
+                    .or_else(|| input.strip_prefix('!'));
+                if let Some(command) = command.map(str::trim).filter(|c| !c.is_empty()) {
+                    current = Some((command.to_string(), Vec::new()));
+                }
+            } else if let Some(command) = line.strip_prefix("> Running ") {
+                finish(&mut current, &session_id);
+                current = Some((command.trim().to_string(), Vec::new()));
+            } else if let Some(quoted) = line.strip_prefix('>') {
+                if let Some((_, lines)) = current.as_mut() {
+                    if !Self::is_prompt(quoted) {
+                        lines.push(quoted.strip_prefix(' ').unwrap_or(quoted));
+                    }
+                }
+            } else if !line.trim().is_empty() {
+                // Assistant text ends the quoted output
+                finish(&mut current, &session_id);
+            }
+        }
+        finish(&mut current, &session_id);
+
+        for (i, command) in commands.iter_mut().enumerate() {
+            command.sequence_index = i;
+        }
+        Ok(commands)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,4 +812,136 @@ mod tests {
         assert_eq!(cmds[1].command, "second");
         assert_eq!(cmds[2].command, "third");
     }
+
+    fn fixture(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("src/fixtures")
+            .join(name)
+    }
+
+    #[test]
+    fn test_codex_extract_commands() {
+        let cmds = CodexProvider
+            .extract_commands(&fixture("codex_session.jsonl"))
+            .unwrap();
+        let names: Vec<&str> = cmds.iter().map(|c| c.command.as_str()).collect();
+        // apply_patch is not a shell call
+        assert_eq!(
+            names,
+            ["git status --short", "cargo test --lib", "gti log -1"]
+        );
+
+        assert_eq!(
+            cmds[0].output_content.as_deref(),
+            Some(" M src/cart.rs\n?? notes.txt\n")
+        );
+        assert!(!cmds[0].is_error);
+        assert!(cmds[1].is_error);
+        assert!(cmds[1]
+            .output_content
+            .as_deref()
+            .unwrap()
+            .contains("test cart::total ... FAILED"));
+        // Plain-text output: "Exit code: N" header stripped
+        assert!(cmds[2].is_error);
+        assert_eq!(
+            cmds[2].output_content.as_deref(),
+            Some("bash: gti: command not found\n")
+        );
+        assert_eq!(cmds[2].sequence_index, 2);
+    }
+
+    #[test]
+    fn test_codex_session_cwd_and_project_filter() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("sessions/2026/03/02");
+        fs::create_dir_all(&day).unwrap();
+        fs::copy(
+            fixture("codex_session.jsonl"),
+            day.join("rollout-2026-03-02T10-00-00-0199a1b2.jsonl"),
+        )
+        .unwrap();
+        assert_eq!(
+            CodexProvider::session_cwd(&fixture("codex_session.jsonl")).as_deref(),
+            Some("/home/dev/shop")
+        );
+
+        std::env::set_var("CODEX_HOME", dir.path());
+        let matching = CodexProvider.discover_sessions(Some("shop"), None).unwrap();
+        let other = CodexProvider
+            .discover_sessions(Some("/srv/api"), None)
+            .unwrap();
+        let all = CodexProvider.discover_sessions(None, Some(30)).unwrap();
+        std::env::remove_var("CODEX_HOME");
+        assert_eq!(matching.len(), 1);
+        assert!(other.is_empty());
+        assert_eq!(all.len(), 1);
+    }
+
+    #[test]
+    fn test_codex_command_text() {
+        let argv = serde_json::json!(["/bin/zsh", "-lc", "ls -la"]);
+        assert_eq!(CodexProvider::command_text(&argv).unwrap(), "ls -la");
+        let plain = serde_json::json!(["rg", "-n", "TODO"]);
+        assert_eq!(CodexProvider::command_text(&plain).unwrap(), "rg -n TODO");
+        assert!(CodexProvider::command_text(&serde_json::json!([])).is_none());
+    }
+
+    #[test]
+    fn test_aider_extract_commands() {
+        let cmds = AiderProvider
+            .extract_commands(&fixture("aider.chat.history.md"))
+            .unwrap();
+        let names: Vec<&str> = cmds.iter().map(|c| c.command.as_str()).collect();
+        assert_eq!(
+            names,
+            ["git status --short", "pytest tests/test_cart.py", "ls src"]
+        );
+
+        // Prompts are not output; leading space of the output is kept
+        assert_eq!(
+            cmds[0].output_content.as_deref(),
+            Some(" M src/cart.py\n?? notes.txt")
+        );
+        assert!(!cmds[0].is_error);
+        assert!(cmds[1].is_error);
+        assert!(cmds[1]
+            .output_content
+            .as_deref()
+            .unwrap()
+            .ends_with("1 failed, 1 passed in 0.05s ========================="));
+        assert_eq!(cmds[1].session_id, "2026-03-02 09:12:44");
+        assert_eq!(cmds[2].session_id, "2026-03-03 14:00:02");
+        assert_eq!(
+            cmds[2].output_content.as_deref(),
+            Some("cart.py\n__init__.py")
+        );
+        assert_eq!(cmds[2].sequence_index, 2);
+    }
+
+    #[test]
+    fn test_aider_find_history_walks_up() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("src/deep");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(AiderProvider::find_history(&nested), None);
+        fs::write(dir.path().join(AIDER_HISTORY_FILE), "").unwrap();
+        assert_eq!(
+            AiderProvider::find_history(&nested),
+            Some(dir.path().join(AIDER_HISTORY_FILE))
+        );
+    }
+
+    #[test]
+    fn test_provider_from_name() {
+        for name in PROVIDER_NAMES {
+            assert!(from_name(name).is_ok());
+        }
+        assert_eq!(from_name("codex").unwrap().name(), "Codex CLI");
+        assert!(from_name("cursor").is_err());
+        assert_eq!(
+            ClaudeProvider.project_filter(Path::new("/Users/foo/bar")),
+            "-Users-foo-bar"
+        );
+    }
 }
@@ -1,9 +1,14 @@
 use lazy_static::lazy_static;
 use regex::{Regex, RegexSet};
 
+use super::lexer;
+
 /// A rule mapping a shell command pattern to its RTK equivalent.
 struct RtkRule {
     rtk_cmd: &'static str,
+    /// Raw command prefixes and their rtk replacement, used by the hook rewriter.
+    /// The longest matching prefix wins; an empty list means "classify only".
+    rewrites: &'static [(&'static str, &'static str)],
     category: &'static str,
     savings_pct: f64,
     subcmd_savings: &'static [(&'static str, f64)],
@@ -50,31 +55,37 @@ pub fn category_avg_tokens(category: &str, subcmd: &str) -> usize {
 // Patterns ordered to match RTK_RULES indices exactly.
 const PATTERNS: &[&str] = &[
     r"^git\s+(status|log|diff|show|add|commit|push|pull|branch|fetch|stash|worktree)",
-    r"^gh\s+(pr|issue|run|repo|api)",
-    r"^cargo\s+(build|test|clippy|check|fmt)",
+    r"^gh\s+(pr|issue|run|repo|api|release)",
+    r"^cargo\s+(build|test|clippy|check|fmt|install|nextest)",
     r"^pnpm\s+(list|ls|outdated|install)",
-    r"^npm\s+(run|exec)",
+    r"^npm\s+(run|exec|test)",
     r"^npx\s+",
     r"^(cat|head|tail)\s+",
     r"^(rg|grep)\s+",
     r"^ls(\s|$)",
     r"^find\s+",
-    r"^(npx\s+|pnpm\s+)?tsc(\s|$)",
+    r"^(npx\s+|pnpm\s+)?(vue-)?tsc(\s|$)",
     r"^(npx\s+|pnpm\s+)?(eslint|biome|lint)(\s|$)",
     r"^(npx\s+|pnpm\s+)?prettier",
     r"^(npx\s+|pnpm\s+)?next\s+build",
     r"^(pnpm\s+|npx\s+)?(vitest|jest|test)(\s|$)",
     r"^(npx\s+|pnpm\s+)?playwright",
     r"^(npx\s+|pnpm\s+)?prisma",
-    r"^docker\s+(ps|images|logs)",
-    r"^kubectl\s+(get|logs)",
+    r"^docker\s+(ps|images|logs|compose|run|build|exec)",
+    r"^kubectl\s+(get|logs|describe|apply)",
     r"^curl\s+",
     r"^wget\s+",
+    r"^(?:python3?\s+-m\s+)?pytest(\s|$)",
+    r"^ruff\s+(check|format)",
+    r"^(?:uv\s+)?pip\s+(list|outdated|install|show)",
+    r"^go\s+(test|build|vet)",
+    r"^golangci-lint(\s|$)",
 ];
 
 const RULES: &[RtkRule] = &[
     RtkRule {
         rtk_cmd: "rtk git",
+        rewrites: &[("git", "rtk git")],
         category: "Git",
         savings_pct: 70.0,
         subcmd_savings: &[
@@ -87,6 +98,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk gh",
+        rewrites: &[("gh", "rtk gh")],
         category: "GitHub",
         savings_pct: 82.0,
         subcmd_savings: &[("pr", 87.0), ("run", 82.0), ("issue", 80.0)],
@@ -94,6 +106,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk cargo",
+        rewrites: &[("cargo", "rtk cargo")],
         category: "Cargo",
         savings_pct: 80.0,
         subcmd_savings: &[("test", 90.0), ("check", 80.0)],
@@ -101,6 +114,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk pnpm",
+        rewrites: &[("pnpm", "rtk pnpm")],
         category: "PackageManager",
         savings_pct: 80.0,
         subcmd_savings: &[],
@@ -108,6 +122,8 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk npm",
+        // `rtk npm <script>` runs `npm run <script>`, so `npm exec` is not rewritten
+        rewrites: &[("npm run", "rtk npm"), ("npm test", "rtk npm test")],
         category: "PackageManager",
         savings_pct: 70.0,
         subcmd_savings: &[],
@@ -115,6 +131,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk npx",
+        rewrites: &[],
         category: "PackageManager",
         savings_pct: 70.0,
         subcmd_savings: &[],
@@ -122,6 +139,8 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk read",
+        // `head -N file` is handled separately by rewrite_head()
+        rewrites: &[("cat", "rtk read")],
         category: "Files",
         savings_pct: 60.0,
         subcmd_savings: &[],
@@ -129,6 +148,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk grep",
+        rewrites: &[("rg", "rtk grep"), ("grep", "rtk grep")],
         category: "Files",
         savings_pct: 75.0,
         subcmd_savings: &[],
@@ -136,6 +156,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk ls",
+        rewrites: &[("ls", "rtk ls")],
         category: "Files",
         savings_pct: 65.0,
         subcmd_savings: &[],
@@ -143,6 +164,8 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk find",
+        // rtk find takes `<pattern> [path]`, not find(1) expressions
+        rewrites: &[],
         category: "Files",
         savings_pct: 70.0,
         subcmd_savings: &[],
@@ -150,6 +173,13 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk tsc",
+        rewrites: &[
+            ("tsc", "rtk tsc"),
+            ("npx tsc", "rtk tsc"),
+            ("pnpm tsc", "rtk tsc"),
+            ("vue-tsc", "rtk tsc"),
+            ("npx vue-tsc", "rtk tsc"),
+        ],
         category: "Build",
         savings_pct: 83.0,
         subcmd_savings: &[],
@@ -157,6 +187,11 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk lint",
+        rewrites: &[
+            ("eslint", "rtk lint"),
+            ("npx eslint", "rtk lint"),
+            ("pnpm lint", "rtk lint"),
+        ],
         category: "Build",
         savings_pct: 84.0,
         subcmd_savings: &[],
@@ -164,6 +199,10 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk prettier",
+        rewrites: &[
+            ("prettier", "rtk prettier"),
+            ("npx prettier", "rtk prettier"),
+        ],
         category: "Build",
         savings_pct: 70.0,
         subcmd_savings: &[],
@@ -171,6 +210,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk next",
+        rewrites: &[],
         category: "Build",
         savings_pct: 87.0,
         subcmd_savings: &[],
@@ -178,6 +218,15 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk vitest",
+        rewrites: &[
+            ("vitest", "rtk vitest run"),
+            ("vitest run", "rtk vitest run"),
+            ("npx vitest", "rtk vitest run"),
+            ("npx vitest run", "rtk vitest run"),
+            ("pnpm vitest", "rtk vitest run"),
+            ("pnpm vitest run", "rtk vitest run"),
+            ("pnpm test", "rtk vitest run"),
+        ],
         category: "Tests",
         savings_pct: 99.0,
         subcmd_savings: &[],
@@ -185,6 +234,11 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk playwright",
+        rewrites: &[
+            ("playwright", "rtk playwright"),
+            ("npx playwright", "rtk playwright"),
+            ("pnpm playwright", "rtk playwright"),
+        ],
         category: "Tests",
         savings_pct: 94.0,
         subcmd_savings: &[],
@@ -192,6 +246,7 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk prisma",
+        rewrites: &[("prisma", "rtk prisma"), ("npx prisma", "rtk prisma")],
         category: "Build",
         savings_pct: 88.0,
         subcmd_savings: &[],
@@ -199,20 +254,30 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk docker",
+        rewrites: &[("docker", "rtk docker")],
         category: "Infra",
         savings_pct: 85.0,
         subcmd_savings: &[],
-        subcmd_status: &[],
+        subcmd_status: &[
+            ("run", super::report::RtkStatus::Passthrough),
+            ("build", super::report::RtkStatus::Passthrough),
+            ("exec", super::report::RtkStatus::Passthrough),
+        ],
     },
     RtkRule {
         rtk_cmd: "rtk kubectl",
+        rewrites: &[("kubectl", "rtk kubectl")],
         category: "Infra",
         savings_pct: 85.0,
         subcmd_savings: &[],
-        subcmd_status: &[],
+        subcmd_status: &[
+            ("describe", super::report::RtkStatus::Passthrough),
+            ("apply", super::report::RtkStatus::Passthrough),
+        ],
     },
     RtkRule {
         rtk_cmd: "rtk curl",
+        rewrites: &[("curl", "rtk curl")],
         category: "Network",
         savings_pct: 70.0,
         subcmd_savings: &[],
@@ -220,11 +285,57 @@ const RULES: &[RtkRule] = &[
     },
     RtkRule {
         rtk_cmd: "rtk wget",
+        // rtk wget takes a single URL, most wget flags don't map
+        rewrites: &[],
         category: "Network",
         savings_pct: 65.0,
         subcmd_savings: &[],
         subcmd_status: &[],
     },
+    RtkRule {
+        rtk_cmd: "rtk pytest",
+        rewrites: &[
+            ("pytest", "rtk pytest"),
+            ("python -m pytest", "rtk pytest"),
+            ("python3 -m pytest", "rtk pytest"),
+        ],
+        category: "Tests",
+        savings_pct: 90.0,
+        subcmd_savings: &[],
+        subcmd_status: &[],
+    },
+    RtkRule {
+        rtk_cmd: "rtk ruff",
+        rewrites: &[("ruff", "rtk ruff")],
+        category: "Build",
+        savings_pct: 80.0,
+        subcmd_savings: &[],
+        subcmd_status: &[],
+    },
+    RtkRule {
+        rtk_cmd: "rtk pip",
+        rewrites: &[("pip", "rtk pip"), ("uv pip", "rtk pip")],
+        category: "PackageManager",
+        savings_pct: 70.0,
+        subcmd_savings: &[("outdated", 85.0)],
+        subcmd_status: &[],
+    },
+    RtkRule {
+        rtk_cmd: "rtk go",
+        rewrites: &[("go", "rtk go")],
+        category: "Tests",
+        savings_pct: 90.0,
+        subcmd_savings: &[("build", 80.0), ("vet", 80.0)],
+        subcmd_status: &[],
+    },
+    RtkRule {
+        rtk_cmd: "rtk golangci-lint",
+        rewrites: &[("golangci-lint", "rtk golangci-lint")],
+        category: "Build",
+        savings_pct: 85.0,
+        subcmd_savings: &[],
+        subcmd_status: &[],
+    },
 ];
 
 /// Commands to ignore (shell builtins, trivial, already rtk).
@@ -292,6 +403,10 @@ lazy_static! {
         .collect();
     static ref ENV_PREFIX: Regex =
         Regex::new(r"^(?:sudo\s+|env\s+|[A-Z_][A-Z0-9_]*=[^\s]*\s+)+").unwrap();
+    static ref ENV_ASSIGN_PREFIX: Regex =
+        Regex::new(r"^(?:[A-Za-z_][A-Za-z0-9_]*=[^\s]*\s+)+").unwrap();
+    static ref HEAD_LINES: Regex =
+        Regex::new(r"^head\s+(?:-(\d+)|--lines=(\d+))\s+(\S+)$").unwrap();
 }
 
 /// Classify a single (already-split) command.
@@ -313,16 +428,12 @@ pub fn classify_command(cmd: &str) -> Classification {
         }
     }
 
-    // Strip env prefixes (sudo, env VAR=val, VAR=val)
-    let stripped = ENV_PREFIX.replace(trimmed, "");
-    let cmd_clean = stripped.trim();
+    let cmd_clean = strip_env_prefix(trimmed);
     if cmd_clean.is_empty() {
         return Classification::Ignored;
     }
 
-    // Fast check with RegexSet — take the last (most specific) match
-    let matches: Vec<usize> = REGEX_SET.matches(cmd_clean).into_iter().collect();
-    if let Some(&idx) = matches.last() {
+    if let Some(idx) = match_rule(cmd_clean) {
         let rule = &RULES[idx];
 
         // Extract subcommand for savings override and status detection
@@ -372,6 +483,105 @@ pub fn classify_command(cmd: &str) -> Classification {
     }
 }
 
+/// Index of the rule matching `cmd`, using the RegexSet for a fast check.
+/// Takes the last (most specific) match.
+fn match_rule(cmd: &str) -> Option<usize> {
+    REGEX_SET.matches(cmd).into_iter().next_back()
+}
+
+/// Rewrite every supported simple command in a command line to its rtk equivalent.
+///
+/// Each segment of a chain (`&&`, `||`, `;`, subshells) is rewritten on its own.
+/// Pipe consumers are never rewritten, and a producer is only rewritten when it
+/// pipes into a display-only consumer (`head`, `tail`, ...) since anything else
+/// may parse its raw output. Segments redirecting stdout to a file are kept raw.
+/// Returns None when nothing was rewritten.
+pub fn rewrite_command(cmd: &str) -> Option<String> {
+    if cmd.contains("<<") {
+        return None;
+    }
+
+    let segments = lexer::split(cmd);
+    let mut rewritten = cmd.to_string();
+    let mut changed = false;
+
+    // Splice from the end so earlier byte offsets stay valid
+    for (idx, seg) in segments.iter().enumerate().rev() {
+        let text = seg.text(cmd);
+        if seg.piped_input || lexer::writes_stdout_to_file(text) {
+            continue;
+        }
+        if seg.piped_output
+            && !segments
+                .get(idx + 1)
+                .is_some_and(|next| is_display_consumer(next.text(cmd)))
+        {
+            continue;
+        }
+        if let Some(replacement) = rewrite_segment(text) {
+            rewritten.replace_range(seg.start..seg.end, &replacement);
+            changed = true;
+        }
+    }
+
+    changed.then_some(rewritten)
+}
+
+/// Pipe consumers that only display their input, so filtered output is fine.
+fn is_display_consumer(cmd: &str) -> bool {
+    matches!(
+        cmd.split_whitespace().next(),
+        Some("head" | "tail" | "less" | "more" | "cat")
+    )
+}
+
+/// Rewrite a single simple command using the rule table.
+///
+/// Leading `VAR=value` assignments are kept in front of the rewritten command.
+/// Returns None for ignored commands (including `rtk ...`) and rules without
+/// a rewrite mapping.
+fn rewrite_segment(cmd: &str) -> Option<String> {
+    let trimmed = cmd.trim();
+    if trimmed.is_empty() {
+        return None;
+    }
+
+    let env_len = ENV_ASSIGN_PREFIX
+        .find(trimmed)
+        .map(|m| m.end())
+        .unwrap_or(0);
+    let (env, body) = trimmed.split_at(env_len);
+
+    if !matches!(classify_command(body), Classification::Supported { .. }) {
+        return None;
+    }
+    let rule = &RULES[match_rule(body)?];
+
+    if rule.rtk_cmd == "rtk read" {
+        if let Some(rewritten) = rewrite_head(body) {
+            return Some(format!("{}{}", env, rewritten));
+        }
+    }
+
+    let (prefix, replacement) = rule
+        .rewrites
+        .iter()
+        .filter(|(prefix, _)| {
+            body.strip_prefix(prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+        })
+        .max_by_key(|(prefix, _)| prefix.len())?;
+
+    Some(format!("{}{}{}", env, replacement, &body[prefix.len()..]))
+}
+
+/// `head -N file` / `head --lines=N file` → `rtk read file --max-lines N`
+fn rewrite_head(cmd: &str) -> Option<String> {
+    let caps = HEAD_LINES.captures(cmd)?;
+    let lines = caps.get(1).or_else(|| caps.get(2))?.as_str();
+    Some(format!("rtk read {} --max-lines {}", &caps[3], lines))
+}
+
 /// Extract the base command (first word, or first two if it looks like a subcommand pattern).
 fn extract_base_command(cmd: &str) -> &str {
     let parts: Vec<&str> = cmd.splitn(3, char::is_whitespace).collect();
@@ -401,89 +611,23 @@ fn extract_base_command(cmd: &str) -> &str {
     }
 }
 
-/// Split a command chain on `&&`, `||`, `;` outside quotes.
-/// For pipes `|`, only keep the first command.
-/// Lines with `<<` (heredoc) or `$((` are returned whole.
-pub fn split_command_chain(cmd: &str) -> Vec<&str> {
+/// Strip env prefixes (sudo, env VAR=val, VAR=val) from a single command.
+pub fn strip_env_prefix(cmd: &str) -> &str {
     let trimmed = cmd.trim();
-    if trimmed.is_empty() {
-        return vec![];
-    }
-
-    // Heredoc or arithmetic expansion: treat as single command
-    if trimmed.contains("<<") || trimmed.contains("$((") {
-        return vec![trimmed];
-    }
-
-    let mut results = Vec::new();
-    let mut start = 0;
-    let bytes = trimmed.as_bytes();
-    let len = bytes.len();
-    let mut i = 0;
-    let mut in_single = false;
-    let mut in_double = false;
-    let mut pipe_seen = false;
-
-    while i < len {
-        let b = bytes[i];
-        match b {
-            b'\'' if !in_double => {
-                in_single = !in_single;
-                i += 1;
-            }
-            b'"' if !in_single => {
-                in_double = !in_double;
-                i += 1;
-            }
-            b'|' if !in_single && !in_double => {
-                if i + 1 < len && bytes[i + 1] == b'|' {
-                    // ||
-                    let segment = trimmed[start..i].trim();
-                    if !segment.is_empty() {
-                        results.push(segment);
-                    }
-                    i += 2;
-                    start = i;
-                } else {
-                    // pipe: keep only first command
-                    let segment = trimmed[start..i].trim();
-                    if !segment.is_empty() {
-                        results.push(segment);
-                    }
-                    pipe_seen = true;
-                    break;
-                }
-            }
-            b'&' if !in_single && !in_double && i + 1 < len && bytes[i + 1] == b'&' => {
-                let segment = trimmed[start..i].trim();
-                if !segment.is_empty() {
-                    results.push(segment);
-                }
-                i += 2;
-                start = i;
-            }
-            b';' if !in_single && !in_double => {
-                let segment = trimmed[start..i].trim();
-                if !segment.is_empty() {
-                    results.push(segment);
-                }
-                i += 1;
-                start = i;
-            }
-            _ => {
-                i += 1;
-            }
-        }
-    }
-
-    if !pipe_seen && start < len {
-        let segment = trimmed[start..].trim();
-        if !segment.is_empty() {
-            results.push(segment);
-        }
+    match ENV_PREFIX.find(trimmed) {
+        Some(m) => trimmed[m.end()..].trim(),
+        None => trimmed,
     }
+}
 
-    results
+/// Split a command chain into the simple commands that produce output.
+/// Pipe consumers are dropped (`a | b` → `a`); heredocs are returned whole.
+pub fn split_command_chain(cmd: &str) -> Vec<&str> {
+    lexer::split(cmd)
+        .into_iter()
+        .filter(|seg| !seg.piped_input)
+        .map(|seg| seg.text(cmd))
+        .collect()
 }
 
 #[cfg(test)]
@@ -699,6 +843,226 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rewrite_basic_commands() {
+        for (input, expected) in [
+            ("git status", "rtk git status"),
+            ("git log --oneline -10", "rtk git log --oneline -10"),
+            ("gh pr list", "rtk gh pr list"),
+            ("gh api repos/owner/repo", "rtk gh api repos/owner/repo"),
+            ("gh release list", "rtk gh release list"),
+            ("cargo test", "rtk cargo test"),
+            ("cargo fmt", "rtk cargo fmt"),
+            ("ls -la", "rtk ls -la"),
+            ("ls", "rtk ls"),
+            ("cat package.json", "rtk read package.json"),
+            ("grep -rn pattern src/", "rtk grep -rn pattern src/"),
+            ("rg pattern src/", "rtk grep pattern src/"),
+            (
+                "curl -s https://example.com",
+                "rtk curl -s https://example.com",
+            ),
+            ("npx prisma migrate", "rtk prisma migrate"),
+            ("npx playwright test", "rtk playwright test"),
+            ("pytest -x", "rtk pytest -x"),
+            ("python -m pytest tests/", "rtk pytest tests/"),
+            ("ruff check .", "rtk ruff check ."),
+            ("uv pip list", "rtk pip list"),
+            ("go test ./...", "rtk go test ./..."),
+            ("golangci-lint run", "rtk golangci-lint run"),
+        ] {
+            assert_eq!(rewrite_command(input).as_deref(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_js_tooling() {
+        for (input, expected) in [
+            ("npm run test:e2e", "rtk npm test:e2e"),
+            ("npm run build", "rtk npm build"),
+            ("npm test", "rtk npm test"),
+            ("vue-tsc -b", "rtk tsc -b"),
+            ("npx vue-tsc --noEmit", "rtk tsc --noEmit"),
+            ("npx tsc --noEmit", "rtk tsc --noEmit"),
+            ("pnpm lint", "rtk lint"),
+            ("npx eslint src", "rtk lint src"),
+        ] {
+            assert_eq!(rewrite_command(input).as_deref(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_vitest_no_double_run() {
+        for (input, expected) in [
+            ("vitest", "rtk vitest run"),
+            ("vitest run", "rtk vitest run"),
+            (
+                "vitest run --reporter=verbose",
+                "rtk vitest run --reporter=verbose",
+            ),
+            ("npx vitest run", "rtk vitest run"),
+            ("pnpm vitest run --coverage", "rtk vitest run --coverage"),
+            ("pnpm test", "rtk vitest run"),
+        ] {
+            assert_eq!(rewrite_command(input).as_deref(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_containers() {
+        for (input, expected) in [
+            ("docker compose up -d", "rtk docker compose up -d"),
+            (
+                "docker compose logs postgrest",
+                "rtk docker compose logs postgrest",
+            ),
+            ("docker run --rm postgres", "rtk docker run --rm postgres"),
+            ("docker exec -it db psql", "rtk docker exec -it db psql"),
+            ("kubectl describe pod foo", "rtk kubectl describe pod foo"),
+            (
+                "kubectl apply -f deploy.yaml",
+                "rtk kubectl apply -f deploy.yaml",
+            ),
+        ] {
+            assert_eq!(rewrite_command(input).as_deref(), Some(expected), "{input}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_preserves_env_prefix() {
+        assert_eq!(
+            rewrite_command("TEST_SESSION_ID=2 npx playwright test --config=foo").as_deref(),
+            Some("TEST_SESSION_ID=2 rtk playwright test --config=foo")
+        );
+        assert_eq!(
+            rewrite_command("GIT_PAGER=cat git log --oneline -10").as_deref(),
+            Some("GIT_PAGER=cat rtk git log --oneline -10")
+        );
+        assert_eq!(
+            rewrite_command("NODE_ENV=test CI=1 npx vitest run").as_deref(),
+            Some("NODE_ENV=test CI=1 rtk vitest run")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_head_to_read() {
+        assert_eq!(
+            rewrite_command("head -20 src/main.rs").as_deref(),
+            Some("rtk read src/main.rs --max-lines 20")
+        );
+        assert_eq!(
+            rewrite_command("head --lines=5 README.md").as_deref(),
+            Some("rtk read README.md --max-lines 5")
+        );
+        assert_eq!(rewrite_command("head src/main.rs"), None);
+        assert_eq!(rewrite_command("tail -f app.log"), None);
+    }
+
+    #[test]
+    fn test_rewrite_skips() {
+        for input in [
+            "rtk git status",
+            "cat <<'EOF'\nhello\nEOF",
+            "echo hello world",
+            "cd /tmp",
+            "mkdir -p foo/bar",
+            "python3 script.py",
+            "node -e 'console.log(1)'",
+            "find . -name '*.ts'",
+            "tree src/",
+            "wget https://example.com/file",
+            "npm exec foo",
+            "sudo docker ps",
+            "lsof -i :8080",
+            "",
+        ] {
+            assert_eq!(rewrite_command(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_skips_leading_global_flags() {
+        // rtk git/docker/kubectl reject these flags and `rtk cargo +toolchain`
+        // is an unfiltered passthrough, so the commands are left as they are
+        for input in [
+            "git --no-pager log",
+            "git -C ../other status",
+            "git -c color.ui=never diff",
+            "cargo +nightly test",
+            "docker --context prod ps",
+            "kubectl -n kube-system get pods",
+            "kubectl --context staging logs api",
+        ] {
+            assert_eq!(rewrite_command(input), None, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn test_rewrite_every_chain_segment() {
+        assert_eq!(
+            rewrite_command("cargo fmt && cargo clippy && cargo test").as_deref(),
+            Some("rtk cargo fmt && rtk cargo clippy && rtk cargo test")
+        );
+        assert_eq!(
+            rewrite_command("git add . ; git commit -m 'a && b' || git status").as_deref(),
+            Some("rtk git add . ; rtk git commit -m 'a && b' || rtk git status")
+        );
+        assert_eq!(
+            rewrite_command("cd web && NODE_ENV=test npm test").as_deref(),
+            Some("cd web && NODE_ENV=test rtk npm test")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_subshell_segments() {
+        assert_eq!(
+            rewrite_command("(cd api && cargo test) && git status").as_deref(),
+            Some("(cd api && rtk cargo test) && rtk git status")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_pipes() {
+        // Consumer parses raw output: leave the whole pipeline alone
+        assert_eq!(rewrite_command("git log --oneline | grep fix"), None);
+        assert_eq!(rewrite_command("git status --porcelain | wc -l"), None);
+        // Display-only consumer: filtered output is fine
+        assert_eq!(
+            rewrite_command("cargo test 2>&1 | tail -20").as_deref(),
+            Some("rtk cargo test 2>&1 | tail -20")
+        );
+        // Later segments after a pipeline are still rewritten
+        assert_eq!(
+            rewrite_command("git log | grep fix && cargo test").as_deref(),
+            Some("git log | grep fix && rtk cargo test")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_skips_stdout_redirect() {
+        assert_eq!(rewrite_command("git diff > patch.diff"), None);
+        assert_eq!(
+            rewrite_command("cargo build 2> err.log").as_deref(),
+            Some("rtk cargo build 2> err.log")
+        );
+    }
+
+    #[test]
+    fn test_rewrite_control_flow_bodies() {
+        assert_eq!(
+            rewrite_command("if true; then cargo test; fi").as_deref(),
+            Some("if true; then rtk cargo test; fi")
+        );
+    }
+
+    #[test]
+    fn test_split_chain_continues_after_pipe() {
+        assert_eq!(
+            split_command_chain("git log | head && cargo test"),
+            vec!["git log", "cargo test"]
+        );
+    }
+
     #[test]
     fn test_split_chain_and() {
         assert_eq!(split_command_chain("a && b"), vec!["a", "b"]);
@@ -30,9 +30,25 @@ pub struct SupportedEntry {
     pub category: &'static str,
     pub estimated_savings_tokens: usize,
     pub estimated_savings_pct: f64,
+    /// Savings on recorded outputs replayed through the rtk filter
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub measured: Option<MeasuredSavings>,
     pub rtk_status: RtkStatus,
 }
 
+/// Savings measured by replaying recorded outputs through the rtk filter.
+#[derive(Debug, Serialize)]
+pub struct MeasuredSavings {
+    /// Outputs replayed
+    pub runs: usize,
+    pub input_tokens: usize,
+    pub saved_tokens: usize,
+    /// Spread of the per-run reduction
+    pub min_pct: f64,
+    pub median_pct: f64,
+    pub max_pct: f64,
+}
+
 /// An unsupported command not yet handled by RTK.
 #[derive(Debug, Serialize)]
 pub struct UnsupportedEntry {
@@ -41,6 +57,19 @@ pub struct UnsupportedEntry {
     pub example: String,
 }
 
+/// A filter stub written by `--apply` for an unhandled command.
+#[derive(Debug, Serialize)]
+pub struct FilterStubEntry {
+    pub name: String,
+    pub base_command: String,
+    pub count: usize,
+    /// Sampled outputs the stub was inferred from
+    pub samples: usize,
+    /// Token reduction on those samples
+    pub reduction_pct: f64,
+    pub example: String,
+}
+
 /// Full discover report.
 #[derive(Debug, Serialize)]
 pub struct DiscoverReport {
@@ -51,6 +80,12 @@ pub struct DiscoverReport {
     pub supported: Vec<SupportedEntry>,
     pub unsupported: Vec<UnsupportedEntry>,
     pub parse_errors: usize,
+    /// Stubs written with `--apply` (None without it)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter_stubs: Option<Vec<FilterStubEntry>>,
+    /// Config file the stubs were appended to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stubs_path: Option<String>,
 }
 
 impl DiscoverReport {
@@ -64,6 +99,16 @@ impl DiscoverReport {
     pub fn total_supported_count(&self) -> usize {
         self.supported.iter().map(|s| s.count).sum()
     }
+
+    /// Saved tokens and runs over all replayed outputs.
+    pub fn total_measured(&self) -> (usize, usize) {
+        self.supported
+            .iter()
+            .filter_map(|s| s.measured.as_ref())
+            .fold((0, 0), |(saved, runs), m| {
+                (saved + m.saved_tokens, runs + m.runs)
+            })
+    }
 }
 
 /// Format report as text.
@@ -95,31 +140,53 @@ pub fn format_text(report: &DiscoverReport, limit: usize, verbose: bool) -> Stri
     // Missed savings
     if !report.supported.is_empty() {
         out.push_str("\nMISSED SAVINGS -- Commands RTK already handles\n");
-        out.push_str(&"-".repeat(72));
+        out.push_str(&"-".repeat(100));
         out.push('\n');
         out.push_str(&format!(
-            "{:<24} {:>5}    {:<18} {:<13} {:>12}\n",
-            "Command", "Count", "RTK Equivalent", "Status", "Est. Savings"
+            "{:<24} {:>5}    {:<18} {:<13} {:>14}    {}\n",
+            "Command", "Count", "RTK Equivalent", "Status", "Savings", "Measured"
         ));
 
         for entry in report.supported.iter().take(limit) {
+            // "~" unless every run was replayed
+            let approx = match &entry.measured {
+                Some(m) if m.runs == entry.count => "",
+                _ => "~",
+            };
             out.push_str(&format!(
-                "{:<24} {:>5}    {:<18} {:<13} ~{}\n",
+                "{:<24} {:>5}    {:<18} {:<13} {:>14}    {}\n",
                 truncate_str(&entry.command, 23),
                 entry.count,
                 entry.rtk_equivalent,
                 entry.rtk_status.as_str(),
-                format_tokens(entry.estimated_savings_tokens),
+                format!(
+                    "{}{}",
+                    approx,
+                    format_tokens(entry.estimated_savings_tokens)
+                ),
+                entry
+                    .measured
+                    .as_ref()
+                    .map(format_measured)
+                    .unwrap_or_default(),
             ));
         }
 
-        out.push_str(&"-".repeat(72));
+        out.push_str(&"-".repeat(100));
         out.push('\n');
         out.push_str(&format!(
             "Total: {} commands -> ~{} saveable\n",
             report.total_supported_count(),
             format_tokens(report.total_saveable_tokens()),
         ));
+        let (measured_tokens, measured_runs) = report.total_measured();
+        if measured_runs > 0 {
+            out.push_str(&format!(
+                "Measured: {} saved on {} replayed outputs\n",
+                format_tokens(measured_tokens),
+                measured_runs
+            ));
+        }
     }
 
     // Unhandled
@@ -146,7 +213,11 @@ pub fn format_text(report: &DiscoverReport, limit: usize, verbose: bool) -> Stri
         out.push_str("-> github.com/rtk-ai/rtk/issues\n");
     }
 
-    out.push_str("\n~estimated from tool_result output sizes\n");
+    if let Some(stubs) = &report.filter_stubs {
+        format_stubs(&mut out, stubs, report.stubs_path.as_deref());
+    }
+
+    out.push_str("\n~estimated from tool_result output sizes; measured: recorded outputs replayed through the rtk filter\n");
 
     if verbose && report.parse_errors > 0 {
         out.push_str(&format!("Parse errors skipped: {}\n", report.parse_errors));
@@ -155,6 +226,52 @@ pub fn format_text(report: &DiscoverReport, limit: usize, verbose: bool) -> Stri
     out
 }
 
+fn format_stubs(out: &mut String, stubs: &[FilterStubEntry], path: Option<&str>) {
+    if stubs.is_empty() {
+        out.push_str(
+            "\nNo new filter stubs: unhandled commands already have a custom filter or too little repetitive output.\n",
+        );
+        return;
+    }
+    out.push_str(&format!(
+        "\nFILTER STUBS -- appended to {} for review\n",
+        path.unwrap_or("config.toml")
+    ));
+    out.push_str(&"-".repeat(52));
+    out.push('\n');
+    out.push_str(&format!(
+        "{:<24} {:>5} {:>8}    {}\n",
+        "Filter", "Count", "Samples", "Reduction"
+    ));
+    for stub in stubs {
+        out.push_str(&format!(
+            "{:<24} {:>5} {:>8}    -{:.0}%\n",
+            truncate_str(&stub.name, 23),
+            stub.count,
+            stub.samples,
+            stub.reduction_pct,
+        ));
+    }
+    out.push_str(&"-".repeat(52));
+    out.push('\n');
+    out.push_str(&format!(
+        "-> try: rtk run {}\n",
+        truncate_str(&stubs[0].example, 60)
+    ));
+}
+
+/// "-62% (41-88%, 12 runs)": median reduction and its spread
+fn format_measured(m: &MeasuredSavings) -> String {
+    format!(
+        "-{:.0}% ({:.0}-{:.0}%, {} {})",
+        m.median_pct,
+        m.min_pct,
+        m.max_pct,
+        m.runs,
+        if m.runs == 1 { "run" } else { "runs" }
+    )
+}
+
 /// Format report as JSON.
 pub fn format_json(report: &DiscoverReport) -> String {
     serde_json::to_string_pretty(report).unwrap_or_else(|_| "{}".to_string())
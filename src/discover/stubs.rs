@@ -0,0 +1,259 @@
+//! Filter stubs for frequent unsupported commands (`rtk discover --apply`).
+//!
+//! Sampled outputs of a command from the session history are scanned for
+//! line prefixes that make up a large share of the output (progress, INFO
+//! logging, "Compiling x" lines). Those become `strip` patterns of a
+//! `[[filters.custom]]` entry; errors and warnings are always kept through
+//! `summary`. A stub is only proposed when it actually shrinks the samples.
+
+use crate::tracking::estimate_tokens;
+use crate::user_filter::{UserFilter, UserFilterConfig};
+use regex::Regex;
+use std::collections::{HashMap, HashSet};
+
+/// Unsupported commands turned into stubs, most frequent first
+pub const MAX_STUBS: usize = 5;
+
+/// Outputs sampled per unsupported command
+pub const MAX_SAMPLES: usize = 20;
+
+/// Strip patterns per stub
+const MAX_STRIP: usize = 5;
+
+/// A prefix is stripped when it starts at least this many lines...
+const STRIP_MIN_LINES: usize = 3;
+
+/// ...and at least this share of all sampled lines
+const STRIP_MIN_SHARE: f64 = 0.1;
+
+/// Outputs longer than this (after stripping) keep only their tail
+const LONG_OUTPUT_LINES: usize = 40;
+const TAIL_LINES: usize = 30;
+
+/// Stubs must cut the sampled output by at least this much
+const MIN_REDUCTION_PCT: f64 = 20.0;
+
+/// Shown whatever the other patterns drop
+const SUMMARY: &str = r"(?i)\b(error|fail(ed|ure)?|warn(ing)?|panic(ked)?|fatal)\b";
+
+/// A proposed filter and how it did on the sampled outputs.
+#[derive(Debug)]
+pub struct FilterStub {
+    pub filter: UserFilterConfig,
+    /// Token reduction on the samples
+    pub reduction_pct: f64,
+}
+
+/// Propose a filter for `base_command` from its sampled outputs, or `None`
+/// when nothing in the samples is worth stripping.
+pub fn propose(base_command: &str, samples: &[String]) -> Option<FilterStub> {
+    lazy_static::lazy_static! {
+        static ref SUMMARY_RE: Regex = Regex::new(SUMMARY).unwrap();
+    }
+    let samples: Vec<&String> = samples.iter().filter(|s| !s.trim().is_empty()).collect();
+    if samples.is_empty() {
+        return None;
+    }
+
+    // Lines and samples per leading token
+    let mut lines_per_prefix: HashMap<&str, usize> = HashMap::new();
+    let mut samples_per_prefix: HashMap<&str, usize> = HashMap::new();
+    let mut summary_prefixes: HashSet<&str> = HashSet::new();
+    let mut total_lines = 0;
+    for sample in &samples {
+        let mut seen = HashSet::new();
+        for line in sample.lines() {
+            total_lines += 1;
+            let prefix = match line_prefix(line) {
+                Some(p) => p,
+                None => continue,
+            };
+            *lines_per_prefix.entry(prefix).or_insert(0) += 1;
+            if seen.insert(prefix) {
+                *samples_per_prefix.entry(prefix).or_insert(0) += 1;
+            }
+            if SUMMARY_RE.is_match(line) {
+                summary_prefixes.insert(prefix);
+            }
+        }
+    }
+
+    let min_samples = samples.len().min(2);
+    let mut frequent: Vec<(&str, usize)> = lines_per_prefix
+        .into_iter()
+        .filter(|(prefix, lines)| {
+            *lines >= STRIP_MIN_LINES
+                && *lines as f64 >= total_lines as f64 * STRIP_MIN_SHARE
+                && samples_per_prefix[prefix] >= min_samples
+                && !summary_prefixes.contains(prefix)
+        })
+        .collect();
+    frequent.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+    let mut filter = UserFilterConfig {
+        name: slug(base_command),
+        command: format!("^{}(\\s|$)", regex::escape(base_command)),
+        strip: frequent
+            .iter()
+            .take(MAX_STRIP)
+            .map(|(prefix, _)| strip_pattern(prefix))
+            .collect(),
+        keep: Vec::new(),
+        dedup: samples.iter().any(|s| has_repeated_lines(s)),
+        head: None,
+        tail: None,
+        summary: Some(SUMMARY.to_string()),
+    };
+
+    // Long outputs: keep the end, where results and summaries usually are
+    let stripped = UserFilter::compile(&filter).ok()?;
+    let avg_lines = samples
+        .iter()
+        .map(|s| stripped.apply(s).lines().count())
+        .sum::<usize>()
+        / samples.len();
+    if avg_lines > LONG_OUTPUT_LINES {
+        filter.tail = Some(TAIL_LINES);
+    }
+    if filter.strip.is_empty() && !filter.dedup && filter.tail.is_none() {
+        return None;
+    }
+
+    let compiled = UserFilter::compile(&filter).ok()?;
+    let before: usize = samples.iter().map(|s| estimate_tokens(s)).sum();
+    let after: usize = samples
+        .iter()
+        .map(|s| estimate_tokens(&compiled.apply(s)))
+        .sum();
+    if before == 0 {
+        return None;
+    }
+    let reduction_pct = (before.saturating_sub(after)) as f64 * 100.0 / before as f64;
+    (reduction_pct >= MIN_REDUCTION_PCT).then_some(FilterStub {
+        filter,
+        reduction_pct,
+    })
+}
+
+/// Leading token of an unindented line, up to its first `:` ("INFO:",
+/// "Compiling", "[download]"); `None` for tokens without letters.
+fn line_prefix(line: &str) -> Option<&str> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let token = line.split_whitespace().next()?;
+    let token = match token.find(':') {
+        Some(i) => &token[..=i],
+        None => token,
+    };
+    (token.len() >= 2 && token.chars().any(char::is_alphabetic)).then_some(token)
+}
+
+fn strip_pattern(prefix: &str) -> String {
+    let escaped = regex::escape(prefix);
+    if prefix.ends_with(char::is_alphanumeric) {
+        format!("^{}\\b", escaped)
+    } else {
+        format!("^{}", escaped)
+    }
+}
+
+fn has_repeated_lines(output: &str) -> bool {
+    let lines: Vec<&str> = output.lines().collect();
+    lines
+        .windows(2)
+        .any(|pair| pair[0] == pair[1] && !pair[0].trim().is_empty())
+}
+
+/// Filter name from a command: "make build" → "make-build"
+fn slug(command: &str) -> String {
+    let slug: String = command
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    slug.split('-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bazel_sample(target: &str, failed: bool) -> String {
+        let mut lines = vec![
+            "Loading: 0 packages loaded".to_string(),
+            "Loading: 3 packages loaded".to_string(),
+            format!("INFO: Analyzed target {} (12 packages loaded).", target),
+            "INFO: Found 1 target...".to_string(),
+        ];
+        for i in 0..10 {
+            lines.push(format!(
+                "[{} / 40] Compiling src/file{}.cc; 1s linux-sandbox",
+                i, i
+            ));
+        }
+        if failed {
+            lines.push("ERROR: /src/BUILD:3:10: Compiling src/a.cc failed".to_string());
+        }
+        lines.push("INFO: Elapsed time: 4.2s, Critical Path: 3.1s".to_string());
+        lines.join("\n")
+    }
+
+    #[test]
+    fn test_propose_strips_frequent_prefixes() {
+        let samples = vec![
+            bazel_sample("//app:main", false),
+            bazel_sample("//lib:core", true),
+        ];
+        let stub = propose("bazel build", &samples).unwrap();
+        assert_eq!(stub.filter.name, "bazel-build");
+        assert_eq!(stub.filter.command, r"^bazel build(\s|$)");
+        assert!(stub.filter.strip.contains(&"^Loading:".to_string()));
+        assert!(stub.filter.strip.contains(&"^INFO:".to_string()));
+        assert!(stub.reduction_pct >= MIN_REDUCTION_PCT);
+
+        // Errors survive the stub
+        let filter = UserFilter::compile(&stub.filter).unwrap();
+        assert!(filter.apply(&samples[1]).contains("ERROR: /src/BUILD:3:10"));
+    }
+
+    #[test]
+    fn test_propose_keeps_prefixes_of_error_lines() {
+        let sample = "error: one\nerror: two\nerror: three\nerror: four\ndone";
+        assert!(propose("lint", &[sample.to_string()]).is_none());
+    }
+
+    #[test]
+    fn test_propose_needs_samples_and_savings() {
+        assert!(propose("make", &[]).is_none());
+        assert!(propose("make", &["   ".to_string()]).is_none());
+        // Nothing repeated: no stub
+        let varied = "alpha one\nbeta two\ngamma three".to_string();
+        assert!(propose("make", &[varied]).is_none());
+    }
+
+    #[test]
+    fn test_propose_tails_long_outputs() {
+        let long: String = (0..200)
+            .map(|i| format!("key{} = {}", i, i * 7))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let stub = propose("terraform output", &[long.clone(), long]).unwrap();
+        assert!(stub.filter.strip.is_empty());
+        assert_eq!(stub.filter.tail, Some(TAIL_LINES));
+    }
+
+    #[test]
+    fn test_line_prefix_and_slug() {
+        assert_eq!(line_prefix("INFO: Build ok"), Some("INFO:"));
+        assert_eq!(line_prefix("Compiling foo v1.0"), Some("Compiling"));
+        assert_eq!(line_prefix("  indented"), None);
+        assert_eq!(line_prefix("[1/40] step"), None);
+        assert_eq!(strip_pattern("Compiling"), r"^Compiling\b");
+        assert_eq!(strip_pattern("[INFO]"), r"^\[INFO\]");
+        assert_eq!(slug("./scripts/deploy.sh"), "scripts-deploy-sh");
+    }
+}
@@ -0,0 +1,16 @@
+-- Unversioned database (user_version 0) after the ad-hoc exec_time_ms ALTER
+CREATE TABLE commands (
+    id INTEGER PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    original_cmd TEXT NOT NULL,
+    rtk_cmd TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    saved_tokens INTEGER NOT NULL,
+    savings_pct REAL NOT NULL
+);
+CREATE INDEX idx_timestamp ON commands(timestamp);
+ALTER TABLE commands ADD COLUMN exec_time_ms INTEGER DEFAULT 0;
+INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct, exec_time_ms) VALUES
+    ('2026-02-01T10:00:00+00:00', 'git log', 'rtk git log', 800, 200, 600, 75.0, 100),
+    ('2026-02-01T10:05:00+00:00', 'npm test', 'rtk npm test', 2000, 400, 1600, 80.0, 250);
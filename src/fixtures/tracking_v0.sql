@@ -0,0 +1,16 @@
+-- Tracking database as created by the first release (no exec_time_ms, user_version 0)
+CREATE TABLE commands (
+    id INTEGER PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    original_cmd TEXT NOT NULL,
+    rtk_cmd TEXT NOT NULL,
+    input_tokens INTEGER NOT NULL,
+    output_tokens INTEGER NOT NULL,
+    saved_tokens INTEGER NOT NULL,
+    savings_pct REAL NOT NULL
+);
+CREATE INDEX idx_timestamp ON commands(timestamp);
+INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct) VALUES
+    ('2026-01-20T09:15:00+00:00', 'git status', 'rtk git status', 400, 100, 300, 75.0),
+    ('2026-01-20T09:16:00+00:00', 'cargo test', 'rtk cargo test', 1200, 200, 1000, 83.33),
+    ('2026-01-21T14:02:00+00:00', 'ls -la', 'rtk ls', 500, 100, 400, 80.0);
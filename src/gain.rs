@@ -1,29 +1,70 @@
 use crate::display_helpers::{format_duration, print_period_table};
-use crate::tracking::{DayStats, MonthStats, Tracker, WeekStats};
+use crate::tracking::{
+    DayStats, MonthStats, ProjectStats, Scope, Tracker, WeekStats, SCHEMA_VERSION,
+};
 use crate::utils::format_tokens;
 use anyhow::{Context, Result};
 use colored::Colorize; // added: terminal colors
 use serde::Serialize;
+use std::collections::BTreeMap;
 use std::io::IsTerminal; // added: TTY detection for graceful degradation
 
-pub fn run(
-    graph: bool,
-    history: bool,
-    quota: bool,
-    tier: &str,
-    daily: bool,
-    weekly: bool,
-    monthly: bool,
-    all: bool,
-    format: &str,
-    _verbose: u8,
-) -> Result<()> {
-    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+/// What `rtk gain` reports, from its command-line flags.
+#[derive(Debug, Clone, Copy)]
+pub struct GainOptions<'a> {
+    pub graph: bool,
+    pub history: bool,
+    pub quota: bool,
+    pub tier: &'a str,
+    pub daily: bool,
+    pub weekly: bool,
+    pub monthly: bool,
+    pub all: bool,
+    pub by_project: bool,
+    /// text, json or csv
+    pub format: &'a str,
+    /// Only commands run inside the current git toplevel
+    pub project: bool,
+    /// Only commands that exited non-zero
+    pub failed_only: bool,
+}
+
+pub fn run(options: &GainOptions, _verbose: u8) -> Result<()> {
+    let GainOptions {
+        graph,
+        history,
+        quota,
+        tier,
+        daily,
+        weekly,
+        monthly,
+        all,
+        by_project,
+        format,
+        project,
+        failed_only,
+    } = *options;
+
+    let scope = Scope {
+        project_root: if project {
+            Some(
+                crate::tracking::current_project_root()
+                    .context("--project: not inside a git repository")?,
+            )
+        } else {
+            None
+        },
+        failed_only,
+    };
+    let title = scope_title(&scope);
+    let tracker = Tracker::new()
+        .context("Failed to initialize tracking database")?
+        .with_scope(scope);
 
     // Handle export formats
     match format {
-        "json" => return export_json(&tracker, daily, weekly, monthly, all),
-        "csv" => return export_csv(&tracker, daily, weekly, monthly, all),
+        "json" => return export_json(&tracker, daily, weekly, monthly, all, by_project),
+        "csv" => return export_csv(&tracker, daily, weekly, monthly, all, by_project),
         _ => {} // Continue with text format
     }
 
@@ -38,9 +79,9 @@ pub fn run(
     }
 
     // Default view (summary)
-    if !daily && !weekly && !monthly && !all {
+    if !daily && !weekly && !monthly && !all && !by_project {
         // added: styled header with bold title
-        println!("{}", styled("RTK Token Savings (Global Scope)", true));
+        println!("{}", styled(&title, true));
         println!("{}", "═".repeat(60));
         println!();
 
@@ -64,6 +105,7 @@ pub fn run(
                 format_duration(summary.avg_time_ms)
             ),
         );
+        print_kpi("Token counter", format_counters(&summary.by_counter));
         print_efficiency_meter(summary.avg_savings_pct); // added: visual meter
         println!();
 
@@ -234,11 +276,64 @@ pub fn run(
         print_monthly(&tracker)?;
     }
 
+    if by_project {
+        print_by_project(&tracker)?;
+    }
+
     Ok(())
 }
 
 // ── Display helpers (TTY-aware) ── // added: entire section
 
+/// `rtk gain --db-info`: database location, schema version and row count.
+pub fn show_db_info(format: &str) -> Result<()> {
+    let tracker = Tracker::new().context("Failed to initialize tracking database")?;
+    let info = tracker.db_info()?;
+
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&info)?);
+        return Ok(());
+    }
+
+    println!("{}", styled("RTK Tracking Database", true));
+    println!("{}", "═".repeat(60));
+    println!();
+    print_kpi("Path", info.path.display().to_string());
+    print_kpi(
+        "Schema version",
+        format!("v{} (this rtk: v{})", info.schema_version, SCHEMA_VERSION),
+    );
+    print_kpi("Rows", info.rows.to_string());
+    if let (Some(oldest), Some(newest)) = (&info.oldest, &info.newest) {
+        print_kpi("Oldest", oldest.clone());
+        print_kpi("Newest", newest.clone());
+    }
+    print_kpi("Size", crate::ls::human_size(info.size_bytes));
+
+    Ok(())
+}
+
+/// Report title naming the active scope.
+fn scope_title(scope: &Scope) -> String {
+    let mut title = match &scope.project_root {
+        Some(root) => format!("RTK Token Savings (Project: {})", root),
+        None => "RTK Token Savings (Global Scope)".to_string(),
+    };
+    if scope.failed_only {
+        title.push_str(" — failed commands only (where exit codes are recorded)");
+    }
+    title
+}
+
+/// "heuristic (120), cl100k_base (30)" — which counters produced the totals above.
+fn format_counters(by_counter: &[(String, usize)]) -> String {
+    by_counter
+        .iter()
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 /// Format text with bold styling (TTY-aware). // added
 fn styled(text: &str, strong: bool) -> String {
     if !std::io::stdout().is_terminal() {
@@ -379,6 +474,54 @@ fn print_monthly(tracker: &Tracker) -> Result<()> {
     Ok(())
 }
 
+/// Label for a project row: its root path, or "(no project)" outside any repository.
+fn project_label(project: &ProjectStats) -> String {
+    match &project.project {
+        Some(root) => root.clone(),
+        None => "(no project)".to_string(),
+    }
+}
+
+fn print_by_project(tracker: &Tracker) -> Result<()> {
+    let projects = tracker.get_by_project()?;
+    if projects.is_empty() {
+        println!("No project data available.");
+        return Ok(());
+    }
+
+    let separator_width = 74;
+    println!("\n📁 By Project ({} projects)", projects.len());
+    println!("{}", "═".repeat(separator_width));
+
+    for project in &projects {
+        println!(
+            "{}  {} cmds, {} saved ({:.1}%), avg {}",
+            styled(&project_label(project), true),
+            project.commands,
+            format_tokens(project.saved_tokens),
+            project.savings_pct,
+            format_duration(project.avg_time_ms)
+        );
+        for cmd in project.by_command.iter().take(5) {
+            println!(
+                "  {:<34} {:>5}x {:>10} {:>6.1}% {:>8}",
+                truncate_for_column(&cmd.command, 34),
+                cmd.commands,
+                format_tokens(cmd.saved_tokens),
+                cmd.savings_pct,
+                format_duration(cmd.avg_time_ms)
+            );
+        }
+        if project.by_command.len() > 5 {
+            println!("  ... +{} more commands", project.by_command.len() - 5);
+        }
+        println!("{}", "─".repeat(separator_width));
+    }
+    println!();
+
+    Ok(())
+}
+
 #[derive(Serialize)]
 struct ExportData {
     summary: ExportSummary,
@@ -388,6 +531,8 @@ struct ExportData {
     weekly: Option<Vec<WeekStats>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     monthly: Option<Vec<MonthStats>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    by_project: Option<Vec<ProjectStats>>,
 }
 
 #[derive(Serialize)]
@@ -399,6 +544,8 @@ struct ExportSummary {
     avg_savings_pct: f64,
     total_time_ms: u64,
     avg_time_ms: u64,
+    /// Commands per token counter name
+    token_counters: BTreeMap<String, usize>,
 }
 
 fn export_json(
@@ -407,6 +554,7 @@ fn export_json(
     weekly: bool,
     monthly: bool,
     all: bool,
+    by_project: bool,
 ) -> Result<()> {
     let summary = tracker
         .get_summary()
@@ -421,6 +569,7 @@ fn export_json(
             avg_savings_pct: summary.avg_savings_pct,
             total_time_ms: summary.total_time_ms,
             avg_time_ms: summary.avg_time_ms,
+            token_counters: summary.by_counter.into_iter().collect(),
         },
         daily: if all || daily {
             Some(tracker.get_all_days()?)
@@ -437,6 +586,11 @@ fn export_json(
         } else {
             None
         },
+        by_project: if by_project {
+            Some(tracker.get_by_project()?)
+        } else {
+            None
+        },
     };
 
     let json = serde_json::to_string_pretty(&export)?;
@@ -451,6 +605,7 @@ fn export_csv(
     weekly: bool,
     monthly: bool,
     all: bool,
+    by_project: bool,
 ) -> Result<()> {
     if all || daily {
         let days = tracker.get_all_days()?;
@@ -512,7 +667,56 @@ fn export_csv(
                 month.avg_time_ms
             );
         }
+        println!();
+    }
+
+    if by_project {
+        let projects = tracker.get_by_project()?;
+        println!("# Project Data");
+        println!("project,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms");
+        for project in &projects {
+            println!(
+                "{},{},{},{},{},{:.2},{},{}",
+                csv_field(project.project.as_deref().unwrap_or("")),
+                project.commands,
+                project.input_tokens,
+                project.output_tokens,
+                project.saved_tokens,
+                project.savings_pct,
+                project.total_time_ms,
+                project.avg_time_ms
+            );
+        }
+        println!();
+
+        println!("# Project Command Data");
+        println!("project,command,commands,input_tokens,output_tokens,saved_tokens,savings_pct,total_time_ms,avg_time_ms");
+        for project in &projects {
+            for cmd in &project.by_command {
+                println!(
+                    "{},{},{},{},{},{},{:.2},{},{}",
+                    csv_field(project.project.as_deref().unwrap_or("")),
+                    csv_field(&cmd.command),
+                    cmd.commands,
+                    cmd.input_tokens,
+                    cmd.output_tokens,
+                    cmd.saved_tokens,
+                    cmd.savings_pct,
+                    cmd.total_time_ms,
+                    cmd.avg_time_ms
+                );
+            }
+        }
     }
 
     Ok(())
 }
+
+/// Quote a free-text CSV field (paths and command lines may contain commas or quotes).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
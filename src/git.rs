@@ -1,3 +1,4 @@
+use crate::output_cache;
 use crate::tracking;
 use anyhow::{Context, Result};
 use std::ffi::OsString;
@@ -372,7 +373,7 @@ fn run_log(args: &[String], _max_lines: Option<usize>, verbose: u8) -> Result<()
 }
 
 /// Filter git log output: truncate long messages, cap lines
-fn filter_log_output(output: &str, limit: usize) -> String {
+pub(crate) fn filter_log_output(output: &str, limit: usize) -> String {
     let lines: Vec<&str> = output.lines().collect();
     let capped: Vec<String> = lines
         .iter()
@@ -391,7 +392,7 @@ fn filter_log_output(output: &str, limit: usize) -> String {
 }
 
 /// Format porcelain output into compact RTK status display
-fn format_status_output(porcelain: &str) -> String {
+pub(crate) fn format_status_output(porcelain: &str) -> String {
     let lines: Vec<&str> = porcelain.lines().collect();
 
     if lines.is_empty() {
@@ -487,7 +488,7 @@ fn format_status_output(porcelain: &str) -> String {
 }
 
 /// Minimal filtering for git status with user-provided args
-fn filter_status_with_args(output: &str) -> String {
+pub(crate) fn filter_status_with_args(output: &str) -> String {
     let mut result = Vec::new();
 
     for line in output.lines() {
@@ -542,7 +543,12 @@ fn run_status(args: &[String], verbose: u8) -> Result<()> {
         }
 
         // Apply minimal filtering: strip ANSI, remove hints, empty lines
-        let filtered = filter_status_with_args(&stdout);
+        let filtered = output_cache::dedupe(
+            &format!("git status {}", args.join(" ")),
+            output_cache::git_fingerprint(),
+            filter_status_with_args(&stdout),
+            verbose,
+        );
         print!("{}", filtered);
 
         timer.track(
@@ -574,7 +580,12 @@ fn run_status(args: &[String], verbose: u8) -> Result<()> {
     let formatted = if !stderr.is_empty() && stderr.contains("not a git repository") {
         "Not a git repository".to_string()
     } else {
-        format_status_output(&stdout)
+        output_cache::dedupe(
+            "git status",
+            output_cache::git_fingerprint(),
+            format_status_output(&stdout),
+            verbose,
+        )
     };
 
     println!("{}", formatted);
@@ -973,7 +984,7 @@ fn run_branch(args: &[String], verbose: u8) -> Result<()> {
     Ok(())
 }
 
-fn filter_branch_output(output: &str) -> String {
+pub(crate) fn filter_branch_output(output: &str) -> String {
     let mut current = String::new();
     let mut local: Vec<String> = Vec::new();
     let mut remote: Vec<String> = Vec::new();
@@ -1188,7 +1199,7 @@ fn run_stash(subcommand: Option<&str>, args: &[String], verbose: u8) -> Result<(
     Ok(())
 }
 
-fn filter_stash_list(output: &str) -> String {
+pub(crate) fn filter_stash_list(output: &str) -> String {
     // Format: "stash@{0}: WIP on main: abc1234 commit message"
     let mut result = Vec::new();
     for line in output.lines() {
@@ -1272,7 +1283,7 @@ fn run_worktree(args: &[String], verbose: u8) -> Result<()> {
     Ok(())
 }
 
-fn filter_worktree_list(output: &str) -> String {
+pub(crate) fn filter_worktree_list(output: &str) -> String {
     let home = dirs::home_dir()
         .map(|h| h.to_string_lossy().to_string())
         .unwrap_or_default();
@@ -1,3 +1,6 @@
+use crate::parser::{
+    render_with, truncate_output, OutputParser, ParseResult, TestFailure, TestResult,
+};
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
@@ -50,21 +53,20 @@ pub fn run_test(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: go test -json {}", args.join(" "));
     }
 
-    let output = cmd
-        .output()
-        .context("Failed to run go test. Is Go installed?")?;
+    let output =
+        crate::stream::capture(&mut cmd).context("Failed to run go test. Is Go installed?")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let stderr = &output.stderr;
+    let raw = &output.raw;
 
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    let filtered = filter_go_test_json(&stdout);
+    let exit_code = output.exit_code;
+    let parse_result = GoTestParser::parse(&output.stdout);
+    let tier = parse_result.tier();
+    let filtered = output.annotate(&render_with("go test", parse_result, verbose, || {
+        filter_go_test_json(&output.stdout)
+    }));
 
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "go_test", exit_code) {
+    if let Some(hint) = crate::tee::tee_and_hint(raw, "go_test", exit_code) {
         println!("{}\n{}", filtered, hint);
     } else {
         println!("{}", filtered);
@@ -75,15 +77,16 @@ pub fn run_test(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("{}", stderr.trim());
     }
 
-    timer.track(
+    timer.track_with(
         &format!("go test {}", args.join(" ")),
         &format!("rtk go test {}", args.join(" ")),
-        &raw,
+        raw,
         &filtered,
+        tracking::RunContext::exited(exit_code).with_tier(tier),
     );
 
     // Preserve exit code for CI/CD
-    if !output.status.success() {
+    if exit_code != 0 {
         std::process::exit(exit_code);
     }
 
@@ -128,11 +131,12 @@ pub fn run_build(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track(
+    timer.track_with(
         &format!("go build {}", args.join(" ")),
         &format!("rtk go build {}", args.join(" ")),
         &raw,
         &filtered,
+        tracking::RunContext::exited(exit_code),
     );
 
     // Preserve exit code for CI/CD
@@ -181,11 +185,12 @@ pub fn run_vet(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track(
+    timer.track_with(
         &format!("go vet {}", args.join(" ")),
         &format!("rtk go vet {}", args.join(" ")),
         &raw,
         &filtered,
+        tracking::RunContext::exited(exit_code),
     );
 
     // Preserve exit code for CI/CD
@@ -241,8 +246,106 @@ pub fn run_other(args: &[OsString], verbose: u8) -> Result<()> {
     Ok(())
 }
 
+/// Parser for `go test -json` events (NDJSON)
+pub struct GoTestParser;
+
+impl OutputParser for GoTestParser {
+    type Output = TestResult;
+
+    fn parse(input: &str) -> ParseResult<TestResult> {
+        let mut result = TestResult {
+            total: 0,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration_ms: None,
+            failures: Vec::new(),
+        };
+        let mut outputs: HashMap<(String, String), Vec<String>> = HashMap::new();
+        let mut events = 0;
+        let mut elapsed_secs = 0.0;
+
+        for line in input.lines() {
+            let event: GoTestEvent = match serde_json::from_str(line.trim()) {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            events += 1;
+            let package = event.package.unwrap_or_default();
+            let test = match event.test {
+                Some(test) => test,
+                None => {
+                    // Package-level pass/fail carries the package's wall time
+                    if matches!(event.action.as_str(), "pass" | "fail") {
+                        elapsed_secs += event.elapsed.unwrap_or(0.0);
+                    }
+                    continue;
+                }
+            };
+            match event.action.as_str() {
+                "pass" => result.passed += 1,
+                "skip" => result.skipped += 1,
+                "fail" => {
+                    result.failed += 1;
+                    let lines = outputs
+                        .remove(&(package.clone(), test.clone()))
+                        .unwrap_or_default();
+                    result.failures.push(TestFailure {
+                        test_name: test,
+                        file_path: package,
+                        error_message: lines.join("\n"),
+                        stack_trace: None,
+                    });
+                }
+                "output" => {
+                    if let Some(text) = event.output {
+                        outputs
+                            .entry((package, test))
+                            .or_default()
+                            .push(text.trim_end().to_string());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        result.total = result.passed + result.failed + result.skipped;
+        result.duration_ms = Some((elapsed_secs * 1000.0).round() as u64);
+        if events > 0 {
+            return ParseResult::Full(result);
+        }
+
+        // Plain `go test` output (e.g. -json stripped by a wrapper script)
+        for line in input.lines().map(str::trim) {
+            if let Some(rest) = line.strip_prefix("--- FAIL: ") {
+                result.failed += 1;
+                result.failures.push(TestFailure {
+                    test_name: rest.split_whitespace().next().unwrap_or(rest).to_string(),
+                    file_path: String::new(),
+                    error_message: String::new(),
+                    stack_trace: None,
+                });
+            } else if line.starts_with("--- PASS: ") {
+                result.passed += 1;
+            } else if line.starts_with("--- SKIP: ") {
+                result.skipped += 1;
+            }
+        }
+        result.total = result.passed + result.failed + result.skipped;
+        result.duration_ms = None;
+        if result.total > 0 {
+            ParseResult::Degraded(
+                result,
+                vec!["no -json events, counted --- PASS/FAIL/SKIP lines".to_string()],
+            )
+        } else {
+            ParseResult::Passthrough(truncate_output(input, 500))
+        }
+    }
+}
+
 /// Parse go test -json output (NDJSON format)
-fn filter_go_test_json(output: &str) -> String {
+pub(crate) fn filter_go_test_json(output: &str) -> String {
     let mut packages: HashMap<String, PackageResult> = HashMap::new();
     let mut current_test_output: HashMap<(String, String), Vec<String>> = HashMap::new(); // (package, test) -> outputs
 
@@ -366,7 +469,7 @@ fn filter_go_test_json(output: &str) -> String {
 }
 
 /// Filter go build output - show only errors
-fn filter_go_build(output: &str) -> String {
+pub(crate) fn filter_go_build(output: &str) -> String {
     let mut errors: Vec<String> = Vec::new();
 
     for line in output.lines() {
@@ -409,7 +512,7 @@ fn filter_go_build(output: &str) -> String {
 }
 
 /// Filter go vet output - show issues
-fn filter_go_vet(output: &str) -> String {
+pub(crate) fn filter_go_vet(output: &str) -> String {
     let mut issues: Vec<String> = Vec::new();
 
     for line in output.lines() {
@@ -454,6 +557,35 @@ fn compact_package_name(package: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_go_test_parser() {
+        let output = r#"{"Action":"run","Package":"example.com/foo","Test":"TestA"}
+{"Action":"pass","Package":"example.com/foo","Test":"TestA","Elapsed":0.01}
+{"Action":"output","Package":"example.com/foo","Test":"TestB","Output":"    foo_test.go:12: expected 5, got 3\n"}
+{"Action":"fail","Package":"example.com/foo","Test":"TestB","Elapsed":0.02}
+{"Action":"skip","Package":"example.com/foo","Test":"TestC","Elapsed":0}
+{"Action":"fail","Package":"example.com/foo","Elapsed":0.5}"#;
+        let result = GoTestParser::parse(output);
+        assert_eq!(result.tier(), 1);
+        let tests = result.unwrap();
+        assert_eq!((tests.passed, tests.failed, tests.skipped), (1, 1, 1));
+        assert_eq!(tests.duration_ms, Some(500));
+        assert_eq!(tests.failures[0].test_name, "TestB");
+        assert_eq!(tests.failures[0].file_path, "example.com/foo");
+        assert!(tests.failures[0]
+            .error_message
+            .contains("expected 5, got 3"));
+
+        let text = GoTestParser::parse("--- PASS: TestA (0.00s)\n--- FAIL: TestB (0.01s)\nFAIL\n");
+        assert_eq!(text.tier(), 2);
+        assert_eq!(text.unwrap().failures[0].test_name, "TestB");
+
+        assert_eq!(
+            GoTestParser::parse("# example.com/foo\n./foo.go:3: undefined: x").tier(),
+            3
+        );
+    }
+
     #[test]
     fn test_filter_go_test_all_pass() {
         let output = r#"{"Time":"2024-01-01T10:00:00Z","Action":"run","Package":"example.com/foo","Test":"TestBar"}
@@ -58,11 +58,12 @@ pub fn run(
         }
         let msg = format!("🔍 0 for '{}'", pattern);
         println!("{}", msg);
-        timer.track(
+        timer.track_with(
             &format!("grep -rn '{}' {}", pattern, path),
             "rtk grep",
             &raw_output,
             &msg,
+            tracking::RunContext::exited(exit_code),
         );
         if exit_code != 0 {
             std::process::exit(exit_code);
@@ -125,11 +126,12 @@ pub fn run(
     }
 
     print!("{}", rtk_output);
-    timer.track(
+    timer.track_with(
         &format!("grep -rn '{}' {}", pattern, path),
         "rtk grep",
         &raw_output,
         &rtk_output,
+        tracking::RunContext::exited(exit_code),
     );
 
     if exit_code != 0 {
@@ -4,7 +4,7 @@ use std::io::Write;
 use std::path::{Path, PathBuf};
 use tempfile::NamedTempFile;
 
-// Embedded hook script (guards before set -euo pipefail)
+// Embedded hook shim (guard, then delegates to `rtk rewrite`)
 const REWRITE_HOOK: &str = include_str!("../hooks/rtk-rewrite.sh");
 
 // Embedded slim RTK awareness instructions
@@ -1000,16 +1000,21 @@ pub fn show_config() -> Result<()> {
             let is_executable = perms.mode() & 0o111 != 0;
 
             let hook_content = fs::read_to_string(&hook_path)?;
-            let has_guards =
-                hook_content.contains("command -v rtk") && hook_content.contains("command -v jq");
+            let has_guards = hook_content.contains("command -v rtk");
+            let is_native = hook_content.contains("rtk rewrite");
 
-            if is_executable && has_guards {
-                println!("✅ Hook: {} (executable, with guards)", hook_path.display());
+            if is_executable && has_guards && is_native {
+                println!("✅ Hook: {} (executable, rtk rewrite)", hook_path.display());
             } else if !is_executable {
                 println!(
                     "⚠️  Hook: {} (NOT executable - run: chmod +x)",
                     hook_path.display()
                 );
+            } else if !is_native {
+                println!(
+                    "⚠️  Hook: {} (legacy shell rewriter - run: rtk init -g)",
+                    hook_path.display()
+                );
             } else {
                 println!("⚠️  Hook: {} (no guards - outdated)", hook_path.display());
             }
@@ -1135,14 +1140,17 @@ mod tests {
     #[test]
     fn test_hook_has_guards() {
         assert!(REWRITE_HOOK.contains("command -v rtk"));
-        assert!(REWRITE_HOOK.contains("command -v jq"));
-        // Guards must be BEFORE set -euo pipefail
+        // Guard must come before delegating to the binary
         let guard_pos = REWRITE_HOOK.find("command -v rtk").unwrap();
-        let set_pos = REWRITE_HOOK.find("set -euo pipefail").unwrap();
-        assert!(
-            guard_pos < set_pos,
-            "Guards must come before set -euo pipefail"
-        );
+        let exec_pos = REWRITE_HOOK.find("exec rtk rewrite").unwrap();
+        assert!(guard_pos < exec_pos, "Guard must come before exec");
+    }
+
+    #[test]
+    fn test_hook_delegates_to_native_rewriter() {
+        // No jq/sed rewrite logic left in the shell hook
+        assert!(!REWRITE_HOOK.contains("jq"));
+        assert!(REWRITE_HOOK.contains("rtk rewrite"));
     }
 
     #[test]
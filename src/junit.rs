@@ -0,0 +1,601 @@
+//! JUnit XML test reports.
+//!
+//! Jest, gradle, maven, dotnet, ctest, gtest and many others can write JUnit
+//! XML, so `rtk test --junit <path-or-glob> -- <cmd>` runs the command and
+//! reads the reports it wrote instead of scraping its console output.
+//! Reports are read with quick-xml, leniently: namespace prefixes are
+//! ignored, mismatched end tags are tolerated, and a malformed report keeps
+//! whatever was read before the error.
+
+use crate::parser::{truncate_output, OutputParser, ParseResult, TestFailure, TestResult};
+use anyhow::{Context, Result};
+use encoding_rs::{Encoding, UTF_8};
+use globset::GlobBuilder;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::{Decoder, Reader};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// Reports modified this long before the run started still count as written
+/// by it (coarse filesystem timestamps).
+const MTIME_SLACK: Duration = Duration::from_secs(1);
+
+pub struct JunitParser;
+
+impl OutputParser for JunitParser {
+    type Output = TestResult;
+
+    /// Parse one or more concatenated JUnit XML documents.
+    fn parse(input: &str) -> ParseResult<TestResult> {
+        let report = Report::scan(input);
+        if report.cases > 0 {
+            ParseResult::Full(report.result)
+        } else if report.suites > 0 {
+            // Summary-only reports: counts from <testsuite> attributes
+            ParseResult::Degraded(
+                report.suite_totals,
+                vec!["no <testcase> elements, counts from <testsuite> attributes".to_string()],
+            )
+        } else {
+            ParseResult::Passthrough(truncate_output(input, 500))
+        }
+    }
+}
+
+/// Report files matching `pattern` (a file, a directory of `.xml` files, or a
+/// glob) modified after `since`, sorted by path.
+pub fn find_reports(pattern: &str, since: SystemTime) -> Result<Vec<PathBuf>> {
+    let since = since.checked_sub(MTIME_SLACK).unwrap_or(since);
+    let path = Path::new(pattern);
+    let mut candidates = if path.is_file() {
+        vec![path.to_path_buf()]
+    } else if path.is_dir() {
+        walk(path)
+            .filter(|p| p.extension().is_some_and(|e| e == "xml"))
+            .collect()
+    } else {
+        let glob = GlobBuilder::new(pattern.trim_start_matches("./"))
+            .literal_separator(true)
+            .build()
+            .with_context(|| format!("Invalid --junit pattern: {}", pattern))?
+            .compile_matcher();
+        walk(&glob_base(pattern))
+            .filter(|p| glob.is_match(p.strip_prefix(".").unwrap_or(p)))
+            .collect()
+    };
+    candidates.retain(|p| {
+        std::fs::metadata(p)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified >= since)
+    });
+    candidates.sort();
+    Ok(candidates)
+}
+
+/// Read a report as text, decoding UTF-16 (BOM or not) and 8-bit encodings
+/// named in the XML declaration, such as ISO-8859-1.
+pub fn read_report(path: &Path) -> Result<String> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    Ok(decode_report(&bytes))
+}
+
+fn decode_report(bytes: &[u8]) -> String {
+    let (detected, bom) = quick_xml::encoding::detect_encoding(bytes).unwrap_or((UTF_8, 0));
+    let body = &bytes[bom..];
+    let encoding = if detected == UTF_8 {
+        declared_encoding(body).unwrap_or(UTF_8)
+    } else {
+        detected
+    };
+    encoding.decode_without_bom_handling(body).0.into_owned()
+}
+
+fn declared_encoding(bytes: &[u8]) -> Option<&'static Encoding> {
+    match Reader::from_reader(bytes).read_event() {
+        Ok(Event::Decl(decl)) => decl.encoder(),
+        _ => None,
+    }
+}
+
+/// Leading directories of a glob without metacharacters, where the walk starts.
+fn glob_base(pattern: &str) -> PathBuf {
+    let parts: Vec<&str> = pattern.split('/').collect();
+    let dirs = &parts[..parts.len() - 1];
+    let literal = dirs
+        .iter()
+        .take_while(|part| !part.contains(['*', '?', '[', '{']))
+        .count();
+    let base = dirs[..literal].join("/");
+    if !base.is_empty() {
+        PathBuf::from(base)
+    } else if pattern.starts_with('/') {
+        PathBuf::from("/")
+    } else {
+        PathBuf::from(".")
+    }
+}
+
+fn walk(dir: &Path) -> impl Iterator<Item = PathBuf> {
+    walkdir::WalkDir::new(dir)
+        .into_iter()
+        .filter_entry(|e| e.file_name() != ".git" && e.file_name() != "node_modules")
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+}
+
+/// Everything read from the reports in one pass.
+struct Report {
+    result: TestResult,
+    /// Totals from top-level `<testsuite>` attributes
+    suite_totals: TestResult,
+    suites: usize,
+    cases: usize,
+}
+
+/// The `<testcase>` being read.
+#[derive(Default)]
+struct Case {
+    name: String,
+    classname: String,
+    /// Path only, like the other test parsers' `file_path`
+    file: String,
+    time: Option<f64>,
+    skipped: bool,
+    /// `message` attribute and body of `<failure>` / `<error>`
+    failure: Option<(String, String)>,
+}
+
+impl Report {
+    fn scan(xml: &str) -> Self {
+        let mut report = Report {
+            result: empty_result(),
+            suite_totals: empty_result(),
+            suites: 0,
+            cases: 0,
+        };
+        let mut suite_files: Vec<String> = Vec::new();
+        let mut suite_depth = 0usize;
+        let mut suite_secs: Option<f64> = None;
+        let mut case_secs: Option<f64> = None;
+        let mut case: Option<Case> = None;
+        let mut in_failure = false;
+
+        for token in tokens(xml) {
+            match token {
+                Token::Open { name, attrs, empty } => {
+                    let attr = |key: &str| attr(&attrs, key);
+                    match name.as_str() {
+                        "testsuite" => {
+                            report.suites += 1;
+                            if suite_depth == 0 {
+                                report.add_suite_totals(&attrs);
+                                if let Some(secs) = attr("time").and_then(parse_secs) {
+                                    *suite_secs.get_or_insert(0.0) += secs;
+                                }
+                            }
+                            if !empty {
+                                suite_depth += 1;
+                                let inherited = suite_files.last().cloned().unwrap_or_default();
+                                suite_files
+                                    .push(attr("file").map(str::to_string).unwrap_or(inherited));
+                            }
+                        }
+                        "testcase" => {
+                            let file = attr("file")
+                                .map(str::to_string)
+                                .or_else(|| suite_files.last().cloned())
+                                .unwrap_or_default();
+                            let new_case = Case {
+                                name: attr("name").unwrap_or_default().to_string(),
+                                classname: attr("classname").unwrap_or_default().to_string(),
+                                file,
+                                time: attr("time").and_then(parse_secs),
+                                skipped: matches!(
+                                    attr("status").or(attr("result")),
+                                    Some("notrun" | "disabled" | "skipped")
+                                ),
+                                failure: None,
+                            };
+                            if empty {
+                                report.finish(new_case, &mut case_secs);
+                            } else {
+                                case = Some(new_case);
+                            }
+                        }
+                        "failure" | "error" => {
+                            if let Some(case) = case.as_mut() {
+                                let message = attr("message").unwrap_or_default().to_string();
+                                case.failure.get_or_insert((message, String::new()));
+                                in_failure = !empty;
+                            }
+                        }
+                        "skipped" => {
+                            if let Some(case) = case.as_mut() {
+                                case.skipped = true;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Token::Close(name) => match name.as_str() {
+                    "testsuite" => {
+                        suite_depth = suite_depth.saturating_sub(1);
+                        suite_files.pop();
+                    }
+                    "testcase" => {
+                        if let Some(done) = case.take() {
+                            report.finish(done, &mut case_secs);
+                        }
+                        in_failure = false;
+                    }
+                    "failure" | "error" => in_failure = false,
+                    _ => {}
+                },
+                Token::Text(text) => {
+                    if let (true, Some((_, body))) =
+                        (in_failure, case.as_mut().and_then(|c| c.failure.as_mut()))
+                    {
+                        body.push_str(&text);
+                    }
+                }
+            }
+        }
+
+        let secs = suite_secs.or(case_secs);
+        report.result.duration_ms = secs.map(to_ms);
+        report.suite_totals.duration_ms = secs.map(to_ms);
+        report
+    }
+
+    fn add_suite_totals(&mut self, attrs: &[(String, String)]) {
+        let count = |key: &str| {
+            attr(attrs, key)
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or(0)
+        };
+        let totals = &mut self.suite_totals;
+        let failed = count("failures") + count("errors");
+        let skipped = count("skipped") + count("disabled");
+        totals.total += count("tests");
+        totals.failed += failed;
+        totals.skipped += skipped;
+        totals.passed = totals.total.saturating_sub(totals.failed + totals.skipped);
+    }
+
+    fn finish(&mut self, case: Case, case_secs: &mut Option<f64>) {
+        self.cases += 1;
+        if let Some(secs) = case.time {
+            *case_secs.get_or_insert(0.0) += secs;
+        }
+        let result = &mut self.result;
+        result.total += 1;
+        match case.failure {
+            Some((message, body)) => {
+                result.failed += 1;
+                let body = body.trim();
+                let error_message = if message.trim().is_empty() {
+                    body.lines().next().unwrap_or("").trim().to_string()
+                } else {
+                    message.trim().to_string()
+                };
+                result.failures.push(TestFailure {
+                    test_name: test_name(&case.classname, &case.name),
+                    file_path: case.file,
+                    error_message,
+                    stack_trace: (!body.is_empty()).then(|| body.to_string()),
+                });
+            }
+            None if case.skipped => result.skipped += 1,
+            None => result.passed += 1,
+        }
+    }
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+fn empty_result() -> TestResult {
+    TestResult {
+        total: 0,
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration_ms: None,
+        failures: Vec::new(),
+    }
+}
+
+/// `classname.name`, unless the class name already spells out the test
+/// (jest-junit writes "<describe> <title>" there).
+fn test_name(classname: &str, name: &str) -> String {
+    if classname.is_empty() {
+        name.to_string()
+    } else if name.is_empty() || classname.contains(name) {
+        classname.to_string()
+    } else {
+        format!("{}.{}", classname, name)
+    }
+}
+
+fn parse_secs(value: &str) -> Option<f64> {
+    // Some tools write "1,234.5"
+    value.replace(',', "").parse().ok()
+}
+
+fn to_ms(secs: f64) -> u64 {
+    (secs * 1000.0).round() as u64
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Open {
+        /// Local name, without a namespace prefix
+        name: String,
+        attrs: Vec<(String, String)>,
+        empty: bool,
+    },
+    Close(String),
+    Text(String),
+}
+
+/// Elements and (entity-decoded) text of one or more concatenated XML
+/// documents; declarations, processing instructions, comments and doctypes
+/// are skipped. Stops at the first syntax error.
+fn tokens(xml: &str) -> Vec<Token> {
+    let mut reader = Reader::from_str(xml);
+    let config = reader.config_mut();
+    config.check_end_names = false;
+    config.allow_unmatched_ends = true;
+
+    let mut tokens = Vec::new();
+    loop {
+        match reader.read_event() {
+            Ok(Event::Start(e)) => tokens.push(open(&e, reader.decoder(), false)),
+            Ok(Event::Empty(e)) => tokens.push(open(&e, reader.decoder(), true)),
+            Ok(Event::End(e)) => tokens.push(Token::Close(lossy(e.local_name().as_ref()))),
+            Ok(Event::Text(t)) => tokens.push(Token::Text(match t.unescape() {
+                Ok(text) => text.into_owned(),
+                // Undeclared entities such as &nbsp;: keep the text as written
+                Err(_) => lossy(&t),
+            })),
+            Ok(Event::CData(c)) => tokens.push(Token::Text(lossy(&c))),
+            Ok(Event::Eof) | Err(_) => break,
+            Ok(_) => {}
+        }
+    }
+    tokens
+}
+
+fn open(e: &BytesStart, decoder: Decoder, empty: bool) -> Token {
+    let attrs = e
+        .attributes()
+        .with_checks(false)
+        .flatten()
+        .map(|a| {
+            let value = match a.decode_and_unescape_value(decoder) {
+                Ok(value) => value.into_owned(),
+                Err(_) => lossy(&a.value),
+            };
+            (lossy(a.key.local_name().as_ref()), value)
+        })
+        .collect();
+    Token::Open {
+        name: lossy(e.local_name().as_ref()),
+        attrs,
+        empty,
+    }
+}
+
+fn lossy(bytes: &[u8]) -> String {
+    String::from_utf8_lossy(bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SUREFIRE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- generated by maven-surefire -->
+<testsuites time="1.5">
+  <testsuite name="com.acme.CartTest" tests="4" failures="1" errors="1" skipped="1" time="1.25">
+    <properties><property name="java.version" value="21"/></properties>
+    <testcase classname="com.acme.CartTest" name="addsItem" time="0.010"/>
+    <testcase classname="com.acme.CartTest" name="appliesDiscount" time="0.020">
+      <failure message="expected: &lt;90&gt; but was: &lt;100&gt;" type="org.opentest4j.AssertionFailedError"><![CDATA[org.opentest4j.AssertionFailedError: expected: <90> but was: <100>
+	at com.acme.CartTest.appliesDiscount(CartTest.java:42)]]></failure>
+      <system-out>noise</system-out>
+    </testcase>
+    <testcase classname="com.acme.CartTest" name="checksOut" time="0.5">
+      <error type="java.lang.NullPointerException">java.lang.NullPointerException
+	at com.acme.Cart.total(Cart.java:7)</error>
+    </testcase>
+    <testcase classname="com.acme.CartTest" name="refunds"><skipped message="todo"/></testcase>
+  </testsuite>
+</testsuites>
+"#;
+
+    #[test]
+    fn test_parse_surefire_report() {
+        let result = JunitParser::parse(SUREFIRE);
+        assert_eq!(result.tier(), 1);
+        let data = result.unwrap();
+        assert_eq!(
+            (data.total, data.passed, data.failed, data.skipped),
+            (4, 1, 2, 1)
+        );
+        assert_eq!(data.duration_ms, Some(1250));
+
+        let failure = &data.failures[0];
+        assert_eq!(failure.test_name, "com.acme.CartTest.appliesDiscount");
+        assert_eq!(failure.error_message, "expected: <90> but was: <100>");
+        assert!(failure
+            .stack_trace
+            .as_deref()
+            .unwrap()
+            .ends_with("(CartTest.java:42)"));
+        // <error> without a message: first line of the body
+        assert_eq!(
+            data.failures[1].error_message,
+            "java.lang.NullPointerException"
+        );
+    }
+
+    #[test]
+    fn test_parse_jest_junit_locations() {
+        let xml = r#"<testsuites name="jest tests" tests="2" failures="1">
+  <testsuite name="cart" file="src/cart.test.ts" tests="2" failures="1" time="0.3">
+    <testcase classname="cart adds items" name="cart adds items" time="0.1"/>
+    <testcase classname="cart totals" name="cart totals" time="0.2" line="12">
+      <failure>Error: expect(received).toBe(expected)
+
+Expected: 3
+Received: 4</failure>
+    </testcase>
+  </testsuite>
+</testsuites>"#;
+        let data = JunitParser::parse(xml).unwrap();
+        assert_eq!((data.passed, data.failed), (1, 1));
+        let failure = &data.failures[0];
+        assert_eq!(failure.test_name, "cart totals");
+        assert_eq!(failure.file_path, "src/cart.test.ts");
+        assert_eq!(
+            failure.error_message,
+            "Error: expect(received).toBe(expected)"
+        );
+    }
+
+    #[test]
+    fn test_parse_concatenated_reports_and_status_skips() {
+        let a = r#"<?xml version="1.0"?><testsuite name="a" tests="1" time="2"><testcase name="one" time="2"/></testsuite>"#;
+        let b = r#"<?xml version="1.0"?><testsuite name="b" tests="2" time="1"><testcase name="two" status="notrun"/><testcase name="three"><failure message="x"/></testcase></testsuite>"#;
+        let data = JunitParser::parse(&format!("{}\n{}", a, b)).unwrap();
+        assert_eq!(
+            (data.total, data.passed, data.failed, data.skipped),
+            (3, 1, 1, 1)
+        );
+        assert_eq!(data.duration_ms, Some(3000));
+    }
+
+    #[test]
+    fn test_parse_summary_only_is_degraded() {
+        let xml = r#"<testsuite name="ctest" tests="5" failures="2" skipped="1"></testsuite>"#;
+        let result = JunitParser::parse(xml);
+        assert_eq!(result.tier(), 2);
+        let data = result.unwrap();
+        assert_eq!((data.total, data.passed, data.failed), (5, 2, 2));
+
+        assert_eq!(JunitParser::parse("not xml at all").tier(), 3);
+    }
+
+    fn open_tag(name: &str, attrs: &[(&str, &str)], empty: bool) -> Token {
+        Token::Open {
+            name: name.to_string(),
+            attrs: attrs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            empty,
+        }
+    }
+
+    #[test]
+    fn test_tokens_quotes_and_entities() {
+        let tokens = tokens(r#"<a msg='1 > 0' x="&amp;&#65;&#x42;"/>t&lt;"#);
+        assert_eq!(
+            tokens,
+            vec![
+                open_tag("a", &[("msg", "1 > 0"), ("x", "&AB")], true),
+                Token::Text("t<".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokens_namespaces_and_processing_instructions() {
+        let xml = r#"<?xml version="1.0"?>
+<?xml-stylesheet type="text/xsl" href="junit.xsl?a>b"?>
+<!DOCTYPE testsuites>
+<j:testsuites xmlns:j="urn:junit"><j:testcase j:name="n"/></j:testsuites>"#;
+        let tokens: Vec<Token> = tokens(xml)
+            .into_iter()
+            .filter(|t| !matches!(t, Token::Text(text) if text.trim().is_empty()))
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                open_tag("testsuites", &[("j", "urn:junit")], false),
+                open_tag("testcase", &[("name", "n")], true),
+                Token::Close("testsuites".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_tolerates_odd_reports() {
+        // Undeclared entity, mismatched end tag, and a truncated second document
+        let xml = r#"<testsuite name="s" tests="2">
+  <testcase name="a"><failure message="x &gt; y">got&nbsp;1</failure></testcase>
+  <testcase name="b"></TestCase></testcase>
+</testsuite>
+<testsuite name="t"><testcase name="c"/><testcase name="d"#;
+        let data = JunitParser::parse(xml).unwrap();
+        assert_eq!((data.total, data.passed, data.failed), (3, 2, 1));
+        assert_eq!(data.failures[0].error_message, "x > y");
+        assert_eq!(data.failures[0].stack_trace.as_deref(), Some("got&nbsp;1"));
+    }
+
+    #[test]
+    fn test_decode_report_encodings() {
+        let xml =
+            r#"<?xml version="1.0" encoding="UTF-16"?><testsuite><testcase name="é"/></testsuite>"#;
+        let mut utf16 = vec![0xFF, 0xFE];
+        utf16.extend(xml.encode_utf16().flat_map(u16::to_le_bytes));
+        assert_eq!(decode_report(&utf16), xml);
+
+        let latin1 = b"<?xml version=\"1.0\" encoding=\"ISO-8859-1\"?><testcase name=\"caf\xe9\"/>";
+        assert!(decode_report(latin1).contains("name=\"caf\u{e9}\""));
+
+        let bom_utf8 = b"\xEF\xBB\xBF<testsuite/>";
+        assert_eq!(decode_report(bom_utf8), "<testsuite/>");
+    }
+
+    #[test]
+    fn test_find_reports_glob_and_freshness() {
+        let dir = tempfile::tempdir().unwrap();
+        let reports = dir.path().join("build/test-results");
+        std::fs::create_dir_all(reports.join("unit")).unwrap();
+        std::fs::write(reports.join("unit/TEST-a.xml"), SUREFIRE).unwrap();
+        std::fs::write(reports.join("TEST-b.xml"), SUREFIRE).unwrap();
+        std::fs::write(reports.join("notes.txt"), "").unwrap();
+        let root = dir.path().to_string_lossy().to_string();
+        let long_ago = SystemTime::UNIX_EPOCH;
+
+        let glob = format!("{}/build/**/TEST-*.xml", root);
+        assert_eq!(find_reports(&glob, long_ago).unwrap().len(), 2);
+        let single_level = format!("{}/build/test-results/*.xml", root);
+        assert_eq!(
+            find_reports(&single_level, long_ago).unwrap(),
+            vec![reports.join("TEST-b.xml")]
+        );
+        let dir_pattern = reports.to_string_lossy().to_string();
+        assert_eq!(find_reports(&dir_pattern, long_ago).unwrap().len(), 2);
+
+        // Reports left over from an earlier run are ignored
+        let later = SystemTime::now() + Duration::from_secs(60);
+        assert!(find_reports(&glob, later).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_glob_base() {
+        assert_eq!(glob_base("**/junit.xml"), PathBuf::from("."));
+        assert_eq!(glob_base("junit.xml"), PathBuf::from("."));
+        assert_eq!(glob_base("target/*/TEST-*.xml"), PathBuf::from("target"));
+        assert_eq!(glob_base("/tmp/r/*.xml"), PathBuf::from("/tmp/r"));
+    }
+}
@@ -1,40 +1,31 @@
 pub mod detector;
 pub mod report;
 
-use crate::discover::provider::{ClaudeProvider, SessionProvider};
+use crate::discover::provider::SessionScope;
 use anyhow::Result;
 use detector::{deduplicate_corrections, find_corrections, CommandExecution};
 use report::{format_console_report, write_rules_file};
 
+/// Output characters kept per command for error detection
+const OUTPUT_PREVIEW_CHARS: usize = 1000;
+
 pub fn run(
-    project: Option<String>,
-    all: bool,
-    since: u64,
+    scope: &SessionScope,
     format: String,
     write_rules: bool,
     min_confidence: f64,
     min_occurrences: usize,
 ) -> Result<()> {
-    let provider = ClaudeProvider;
-
-    // Determine project filter (same logic as discover)
-    let project_filter = if all {
-        None
-    } else if let Some(p) = project {
-        Some(p)
-    } else {
-        // Default: current working directory
-        let cwd = std::env::current_dir()?;
-        let cwd_str = cwd.to_string_lossy().to_string();
-        let encoded = ClaudeProvider::encode_project_path(&cwd_str);
-        Some(encoded)
-    };
-
-    // Discover sessions
-    let sessions = provider.discover_sessions(project_filter.as_deref(), Some(since))?;
+    let since = scope.since_days;
+    // Discover sessions (same scope logic as discover)
+    let (provider, sessions) = scope.discover()?;
 
     if sessions.is_empty() {
-        println!("No Claude Code sessions found in the last {} days.", since);
+        println!(
+            "No {} sessions found in the last {} days.",
+            provider.name(),
+            since
+        );
         return Ok(());
     }
 
@@ -53,7 +44,8 @@ pub fn run(
                 all_commands.push(CommandExecution {
                     command: ext_cmd.command,
                     is_error: ext_cmd.is_error,
-                    output,
+                    // Error detection only looks at the start of the output
+                    output: output.chars().take(OUTPUT_PREVIEW_CHARS).collect(),
                 });
             }
         }
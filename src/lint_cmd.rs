@@ -1,3 +1,6 @@
+use crate::parser::{
+    render_with, truncate_output, LintIssue, LintResult, LintSeverity, OutputParser, ParseResult,
+};
 use crate::ruff_cmd;
 use crate::tracking;
 use crate::utils::{package_manager_exec, truncate};
@@ -37,7 +40,6 @@ struct PylintDiagnostic {
     #[allow(dead_code)]
     obj: String,
     line: usize,
-    #[allow(dead_code)]
     column: usize,
     path: String,
     symbol: String, // rule code like "unused-variable"
@@ -135,70 +137,192 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: {} with structured output", linter);
     }
 
-    let output = cmd.output().context(format!(
+    let output = crate::stream::capture(&mut cmd).context(format!(
         "Failed to run {}. Is it installed? Try: pip install {} (or npm/pnpm for JS linters)",
         linter, linter
     ))?;
 
     // Check if process was killed by signal (SIGABRT, SIGKILL, etc.)
-    if !output.status.success() && output.status.code().is_none() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
+    if output.signaled {
         eprintln!("⚠️  Linter process terminated abnormally (possibly out of memory)");
-        if !stderr.is_empty() {
+        if !output.stderr.is_empty() {
             eprintln!(
                 "stderr: {}",
-                stderr.lines().take(5).collect::<Vec<_>>().join("\n")
+                output.stderr.lines().take(5).collect::<Vec<_>>().join("\n")
             );
         }
         return Ok(());
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let stdout = &output.stdout;
+    let raw = &output.raw;
+
+    // Structured parse for linters we know; the tool filter below stays the compact text
+    let parse_result = match linter {
+        "eslint" => Some(EslintParser::parse(stdout)),
+        "ruff" => Some(ruff_cmd::RuffCheckParser::parse(stdout)),
+        "pylint" => Some(PylintParser::parse(stdout)),
+        "mypy" => Some(MypyParser::parse(raw)),
+        _ => None,
+    };
+    let tier = parse_result.as_ref().map(|r| r.tier());
 
     // Dispatch to appropriate filter based on linter
-    let filtered = match linter {
-        "eslint" => filter_eslint_json(&stdout),
+    let filter = || match linter {
+        "eslint" => filter_eslint_json(stdout),
         "ruff" => {
             // Reuse ruff_cmd's JSON parser
             if !stdout.trim().is_empty() {
-                ruff_cmd::filter_ruff_check_json(&stdout)
+                ruff_cmd::filter_ruff_check_json(stdout)
             } else {
                 "✓ Ruff: No issues found".to_string()
             }
         }
-        "pylint" => filter_pylint_json(&stdout),
-        "mypy" => filter_mypy_output(&raw),
-        _ => filter_generic_lint(&raw),
+        "pylint" => filter_pylint_json(stdout),
+        "mypy" => filter_mypy_output(raw),
+        _ => filter_generic_lint(raw),
     };
+    let filtered = output.annotate(&match parse_result {
+        Some(result) => render_with(linter, result, verbose, filter),
+        None => filter(),
+    });
 
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "lint", exit_code) {
+    let exit_code = output.exit_code;
+    if let Some(hint) = crate::tee::tee_and_hint(raw, "lint", exit_code) {
         println!("{}\n{}", filtered, hint);
     } else {
         println!("{}", filtered);
     }
 
-    timer.track(
+    timer.track_with(
         &format!("{} {}", linter, args.join(" ")),
         &format!("rtk lint {} {}", linter, args.join(" ")),
-        &raw,
+        raw,
         &filtered,
+        match tier {
+            Some(tier) => tracking::RunContext::exited(exit_code).with_tier(tier),
+            None => tracking::RunContext::exited(exit_code),
+        },
     );
 
-    if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+    if exit_code != 0 {
+        std::process::exit(exit_code);
     }
 
     Ok(())
 }
 
+/// Parser for `eslint -f json`
+pub struct EslintParser;
+
+impl OutputParser for EslintParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        let results: Vec<EslintResult> = match serde_json::from_str(input) {
+            Ok(r) => r,
+            Err(_) => return ParseResult::Passthrough(truncate_output(input, 500)),
+        };
+        let total_files = results.len();
+        let issues = results
+            .into_iter()
+            .flat_map(|r| {
+                let file_path = r.file_path;
+                r.messages.into_iter().map(move |m| LintIssue {
+                    file_path: file_path.clone(),
+                    line: m.line,
+                    column: m.column,
+                    severity: match m.severity {
+                        2 => LintSeverity::Error,
+                        1 => LintSeverity::Warning,
+                        _ => LintSeverity::Info,
+                    },
+                    rule_id: m.rule_id.unwrap_or_default(),
+                    message: m.message,
+                })
+            })
+            .collect();
+        ParseResult::Full(LintResult {
+            total_files,
+            ..LintResult::from_issues(issues)
+        })
+    }
+}
+
+/// Parser for `pylint --output-format=json2`
+pub struct PylintParser;
+
+impl OutputParser for PylintParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        let diagnostics: Vec<PylintDiagnostic> = match serde_json::from_str(input) {
+            Ok(d) => d,
+            Err(_) => return ParseResult::Passthrough(truncate_output(input, 500)),
+        };
+        let issues = diagnostics
+            .into_iter()
+            .map(|d| LintIssue {
+                file_path: d.path,
+                line: d.line,
+                column: d.column,
+                severity: match d.msg_type.as_str() {
+                    "error" | "fatal" => LintSeverity::Error,
+                    "warning" => LintSeverity::Warning,
+                    _ => LintSeverity::Info,
+                },
+                rule_id: format!("{} ({})", d.symbol, d.message_id),
+                message: d.message,
+            })
+            .collect();
+        ParseResult::Full(LintResult::from_issues(issues))
+    }
+}
+
+/// Parser for mypy's `file.py:line: error: message [code]` text output
+pub struct MypyParser;
+
+impl OutputParser for MypyParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        lazy_static::lazy_static! {
+            static ref MYPY_LINE: Regex =
+                Regex::new(r"^(.+\.pyi?):(\d+):(?:(\d+):)? (error|warning|note): (.+?)(?:  \[(.+?)\])?$")
+                    .unwrap();
+        }
+
+        let issues: Vec<LintIssue> = input
+            .lines()
+            .filter_map(|line| MYPY_LINE.captures(line))
+            .map(|caps| LintIssue {
+                file_path: caps[1].to_string(),
+                line: caps[2].parse().unwrap_or(0),
+                column: caps
+                    .get(3)
+                    .and_then(|c| c.as_str().parse().ok())
+                    .unwrap_or(0),
+                severity: match &caps[4] {
+                    "error" => LintSeverity::Error,
+                    "warning" => LintSeverity::Warning,
+                    _ => LintSeverity::Info,
+                },
+                rule_id: caps.get(6).map(|c| c.as_str()).unwrap_or("").to_string(),
+                message: caps[5].to_string(),
+            })
+            .collect();
+
+        // "Success: no issues found in N source files" / "Found N errors in M files"
+        let summarized = input.contains("Success: no issues found") || input.contains("Found ");
+        if issues.is_empty() && !summarized {
+            return ParseResult::Passthrough(truncate_output(input, 500));
+        }
+        ParseResult::Full(LintResult::from_issues(issues))
+    }
+}
+
 /// Filter ESLint JSON output - group by rule and file
-fn filter_eslint_json(output: &str) -> String {
+pub(crate) fn filter_eslint_json(output: &str) -> String {
     let results: Result<Vec<EslintResult>, _> = serde_json::from_str(output);
 
     let results = match results {
@@ -290,7 +414,7 @@ fn filter_eslint_json(output: &str) -> String {
 }
 
 /// Filter pylint JSON2 output - group by symbol and file
-fn filter_pylint_json(output: &str) -> String {
+pub(crate) fn filter_pylint_json(output: &str) -> String {
     let diagnostics: Result<Vec<PylintDiagnostic>, _> = serde_json::from_str(output);
 
     let diagnostics = match diagnostics {
@@ -407,7 +531,7 @@ fn filter_pylint_json(output: &str) -> String {
 }
 
 /// Filter mypy text output - parse and group by error code and file
-fn filter_mypy_output(output: &str) -> String {
+pub(crate) fn filter_mypy_output(output: &str) -> String {
     // Regex pattern: path/to/file.py:line: error: message [error-code]
     let re = Regex::new(r"^(.+\.py):(\d+): (error|warning|note): (.+?) \[(.+?)\]").unwrap();
 
@@ -581,6 +705,56 @@ fn compact_path(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_eslint_parser() {
+        let json = r#"[
+            {"filePath": "/src/a.ts", "errorCount": 1, "warningCount": 1, "messages": [
+                {"ruleId": "no-unused-vars", "severity": 2, "message": "x is unused", "line": 3, "column": 7},
+                {"ruleId": "semi", "severity": 1, "message": "Missing semicolon", "line": 4, "column": 1}
+            ]},
+            {"filePath": "/src/b.ts", "errorCount": 0, "warningCount": 0, "messages": []}
+        ]"#;
+        let result = EslintParser::parse(json);
+        assert_eq!(result.tier(), 1);
+        let lint = result.unwrap();
+        assert_eq!(lint.total_files, 2);
+        assert_eq!(lint.files_with_issues, 1);
+        assert_eq!((lint.errors, lint.warnings), (1, 1));
+        assert_eq!(lint.issues[0].rule_id, "no-unused-vars");
+        assert_eq!(EslintParser::parse("Oops! Something went wrong").tier(), 3);
+    }
+
+    #[test]
+    fn test_pylint_parser() {
+        let json = r#"[{"type": "warning", "module": "app", "obj": "", "line": 5, "column": 4,
+            "path": "app.py", "symbol": "unused-variable", "message": "Unused variable 'x'",
+            "message-id": "W0612"}]"#;
+        let lint = PylintParser::parse(json).unwrap();
+        assert_eq!(lint.warnings, 1);
+        assert_eq!(lint.issues[0].rule_id, "unused-variable (W0612)");
+    }
+
+    #[test]
+    fn test_mypy_parser() {
+        let output = "\
+app.py:10: error: Incompatible types in assignment  [assignment]
+app.py:12:5: note: See https://mypy.readthedocs.io
+Found 1 error in 1 file (checked 3 source files)
+";
+        let result = MypyParser::parse(output);
+        assert_eq!(result.tier(), 1);
+        let lint = result.unwrap();
+        assert_eq!(lint.errors, 1);
+        assert_eq!(lint.total_issues, 2);
+        assert_eq!(lint.issues[0].rule_id, "assignment");
+        assert_eq!(lint.issues[0].message, "Incompatible types in assignment");
+        assert_eq!(lint.issues[1].column, 5);
+
+        let clean = MypyParser::parse("Success: no issues found in 3 source files\n");
+        assert_eq!(clean.unwrap().total_issues, 0);
+        assert_eq!(MypyParser::parse("mypy: can't read file").tier(), 3);
+    }
+
     #[test]
     fn test_filter_eslint_json() {
         let json = r#"[
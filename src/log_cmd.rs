@@ -6,6 +6,18 @@ use std::fs;
 use std::io::{self, BufRead};
 use std::path::Path;
 
+lazy_static::lazy_static! {
+    // Patterns to normalize log messages
+    static ref TIMESTAMP_RE: Regex =
+        Regex::new(r"^\d{4}[-/]\d{2}[-/]\d{2}[T ]\d{2}:\d{2}:\d{2}[.,]?\d*\s*").unwrap();
+    static ref UUID_RE: Regex =
+        Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
+            .unwrap();
+    static ref HEX_RE: Regex = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
+    static ref NUM_RE: Regex = Regex::new(r"\b\d{4,}\b").unwrap();
+    static ref PATH_RE: Regex = Regex::new(r"/[\w./\-]+").unwrap();
+}
+
 /// Filter and deduplicate log output
 pub fn run_file(file: &Path, verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
@@ -58,41 +70,32 @@ fn analyze_logs(content: &str) -> String {
     let mut unique_errors: Vec<String> = Vec::new();
     let mut unique_warnings: Vec<String> = Vec::new();
 
-    // Patterns to normalize log messages
-    let timestamp_re =
-        Regex::new(r"^\d{4}[-/]\d{2}[-/]\d{2}[T ]\d{2}:\d{2}:\d{2}[.,]?\d*\s*").unwrap();
-    let uuid_re =
-        Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{4}-[0-9a-fA-F]{12}")
-            .unwrap();
-    let hex_re = Regex::new(r"0x[0-9a-fA-F]+").unwrap();
-    let num_re = Regex::new(r"\b\d{4,}\b").unwrap();
-    let path_re = Regex::new(r"/[\w./\-]+").unwrap();
-
     for line in content.lines() {
         let line_lower = line.to_lowercase();
 
         // Normalize for deduplication
-        let normalized =
-            normalize_log_line(line, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re);
+        let normalized = normalize_log_line(line);
 
         // Categorize
-        if line_lower.contains("error")
-            || line_lower.contains("fatal")
-            || line_lower.contains("panic")
-        {
-            let count = error_counts.entry(normalized.clone()).or_insert(0);
-            if *count == 0 {
-                unique_errors.push(line.to_string());
+        match level(&line_lower) {
+            Some(Level::Error) => {
+                let count = error_counts.entry(normalized.clone()).or_insert(0);
+                if *count == 0 {
+                    unique_errors.push(line.to_string());
+                }
+                *count += 1;
             }
-            *count += 1;
-        } else if line_lower.contains("warn") {
-            let count = warn_counts.entry(normalized.clone()).or_insert(0);
-            if *count == 0 {
-                unique_warnings.push(line.to_string());
+            Some(Level::Warn) => {
+                let count = warn_counts.entry(normalized.clone()).or_insert(0);
+                if *count == 0 {
+                    unique_warnings.push(line.to_string());
+                }
+                *count += 1;
             }
-            *count += 1;
-        } else if line_lower.contains("info") {
-            *info_counts.entry(normalized).or_insert(0) += 1;
+            Some(Level::Info) => {
+                *info_counts.entry(normalized).or_insert(0) += 1;
+            }
+            None => {}
         }
     }
 
@@ -127,10 +130,7 @@ fn analyze_logs(content: &str) -> String {
             // Find original message
             let original = unique_errors
                 .iter()
-                .find(|e| {
-                    &normalize_log_line(e, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re)
-                        == *normalized
-                })
+                .find(|e| &normalize_log_line(e) == *normalized)
                 .map(|s| s.as_str())
                 .unwrap_or(normalized);
 
@@ -167,10 +167,7 @@ fn analyze_logs(content: &str) -> String {
         for (normalized, count) in warn_list.iter().take(5) {
             let original = unique_warnings
                 .iter()
-                .find(|w| {
-                    &normalize_log_line(w, &timestamp_re, &uuid_re, &hex_re, &num_re, &path_re)
-                        == *normalized
-                })
+                .find(|w| &normalize_log_line(w) == *normalized)
                 .map(|s| s.as_str())
                 .unwrap_or(normalized);
 
@@ -199,19 +196,77 @@ fn analyze_logs(content: &str) -> String {
     result.join("\n")
 }
 
-fn normalize_log_line(
-    line: &str,
-    timestamp_re: &Regex,
-    uuid_re: &Regex,
-    hex_re: &Regex,
-    num_re: &Regex,
-    path_re: &Regex,
-) -> String {
-    let mut normalized = timestamp_re.replace_all(line, "").to_string();
-    normalized = uuid_re.replace_all(&normalized, "<UUID>").to_string();
-    normalized = hex_re.replace_all(&normalized, "<HEX>").to_string();
-    normalized = num_re.replace_all(&normalized, "<NUM>").to_string();
-    normalized = path_re.replace_all(&normalized, "<PATH>").to_string();
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Level {
+    Error,
+    Warn,
+    Info,
+}
+
+fn level(line_lower: &str) -> Option<Level> {
+    if line_lower.contains("error") || line_lower.contains("fatal") || line_lower.contains("panic")
+    {
+        Some(Level::Error)
+    } else if line_lower.contains("warn") {
+        Some(Level::Warn)
+    } else if line_lower.contains("info") {
+        Some(Level::Info)
+    } else {
+        None
+    }
+}
+
+/// Streaming log filter for follow mode (`rtk docker logs -f`).
+///
+/// Prints each new unique error or warning as it arrives, reports a repeated
+/// message again when its count reaches 10, 100, 1000, ..., and flushes the
+/// full [`analyze_logs`] summary when the stream ends.
+#[derive(Default)]
+pub struct LogStream {
+    counts: HashMap<String, usize>,
+}
+
+impl crate::stream::StreamFilter for LogStream {
+    fn feed_line(&mut self, line: &str) -> Vec<String> {
+        let level = match level(&line.to_lowercase()) {
+            Some(l @ (Level::Error | Level::Warn)) => l,
+            _ => return Vec::new(),
+        };
+        let normalized = normalize_log_line(line);
+        let count = self.counts.entry(normalized).or_insert(0);
+        *count += 1;
+
+        let icon = if level == Level::Error {
+            "❌"
+        } else {
+            "⚠️ "
+        };
+        let message = crate::utils::truncate(line, 100);
+        if *count == 1 {
+            vec![format!("{} {}", icon, message)]
+        } else if is_power_of_ten(*count) {
+            vec![format!("{} [×{}] {}", icon, count, message)]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn finish(&mut self, raw: &str) -> String {
+        analyze_logs(raw)
+    }
+}
+
+fn is_power_of_ten(n: usize) -> bool {
+    let digits = n.to_string();
+    digits.starts_with('1') && digits[1..].bytes().all(|b| b == b'0')
+}
+
+fn normalize_log_line(line: &str) -> String {
+    let mut normalized = TIMESTAMP_RE.replace_all(line, "").to_string();
+    normalized = UUID_RE.replace_all(&normalized, "<UUID>").to_string();
+    normalized = HEX_RE.replace_all(&normalized, "<HEX>").to_string();
+    normalized = NUM_RE.replace_all(&normalized, "<NUM>").to_string();
+    normalized = PATH_RE.replace_all(&normalized, "<PATH>").to_string();
     normalized.trim().to_string()
 }
 
@@ -245,4 +300,35 @@ mod tests {
         // Should not panic even with very long multi-byte messages
         assert!(result.contains("ERRORS"));
     }
+
+    #[test]
+    fn test_log_stream_dedups_live() {
+        use crate::stream::StreamFilter;
+
+        let mut stream = LogStream::default();
+        let mut live = Vec::new();
+        for i in 0..12 {
+            live.extend(stream.feed_line(&format!(
+                "2024-01-01 10:00:{:02} ERROR: Connection failed to /api/server",
+                i
+            )));
+        }
+        live.extend(stream.feed_line("2024-01-01 10:00:13 INFO: Connected"));
+        live.extend(stream.feed_line("2024-01-01 10:00:14 WARN: Slow response"));
+
+        assert_eq!(live.len(), 3);
+        assert!(live[0].starts_with("❌ 2024-01-01 10:00:00 ERROR"));
+        assert!(live[1].contains("[×10]"));
+        assert!(live[2].contains("WARN: Slow response"));
+        assert!(stream.finish("").contains("Log Summary"));
+    }
+
+    #[test]
+    fn test_is_power_of_ten() {
+        assert!(is_power_of_ten(1));
+        assert!(is_power_of_ten(10));
+        assert!(is_power_of_ten(1000));
+        assert!(!is_power_of_ten(20));
+        assert!(!is_power_of_ten(11));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::output_cache;
 use crate::tracking;
 use anyhow::{Context, Result};
 use std::process::Command;
@@ -89,7 +90,12 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     }
 
     let raw = String::from_utf8_lossy(&output.stdout).to_string();
-    let filtered = compact_ls(&raw, show_all);
+    let filtered = output_cache::dedupe(
+        &format!("ls {}", args.join(" ")),
+        output_cache::dirs_fingerprint(&paths),
+        compact_ls(&raw, show_all),
+        verbose,
+    );
 
     if verbose > 0 {
         eprintln!(
@@ -121,7 +127,7 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
 }
 
 /// Format bytes into human-readable size
-fn human_size(bytes: u64) -> String {
+pub(crate) fn human_size(bytes: u64) -> String {
     if bytes >= 1_048_576 {
         format!("{:.1}M", bytes as f64 / 1_048_576.0)
     } else if bytes >= 1024 {
@@ -134,7 +140,7 @@ fn human_size(bytes: u64) -> String {
 /// Parse ls -la output into compact format:
 ///   name/  (dirs)
 ///   name  size  (files)
-fn compact_ls(raw: &str, show_all: bool) -> String {
+pub(crate) fn compact_ls(raw: &str, show_all: bool) -> String {
     use std::collections::HashMap;
 
     let mut dirs: Vec<String> = Vec::new();
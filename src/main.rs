@@ -21,6 +21,7 @@ mod grep_cmd;
 mod hook_audit_cmd;
 mod init;
 mod json_cmd;
+mod junit;
 mod learn;
 mod lint_cmd;
 mod local_llm;
@@ -28,6 +29,7 @@ mod log_cmd;
 mod ls;
 mod next_cmd;
 mod npm_cmd;
+mod output_cache;
 mod parser;
 mod pip_cmd;
 mod playwright_cmd;
@@ -36,13 +38,21 @@ mod prettier_cmd;
 mod prisma_cmd;
 mod pytest_cmd;
 mod read;
+mod replay;
+mod rewrite_cmd;
 mod ruff_cmd;
+mod run_cmd;
 mod runner;
+mod stream;
 mod summary;
 mod tee;
+mod tee_cmd;
+mod test_diff;
+mod tokens;
 mod tracking;
 mod tree;
 mod tsc_cmd;
+mod user_filter;
 mod utils;
 mod vitest_cmd;
 mod wc_cmd;
@@ -75,6 +85,22 @@ struct Cli {
     /// Set SKIP_ENV_VALIDATION=1 for child processes (Next.js, tsc, lint, prisma)
     #[arg(long = "skip-env", global = true)]
     skip_env: bool,
+
+    /// Kill wrapped commands after this many seconds (overrides limits.timeout_secs; goes before the subcommand)
+    #[arg(long, value_name = "SECS")]
+    timeout: Option<u64>,
+
+    /// Stop wrapped commands after this many bytes of output (overrides limits.max_output_bytes; goes before the subcommand)
+    #[arg(long = "max-output", value_name = "BYTES")]
+    max_output: Option<usize>,
+
+    /// Show every failing test instead of what changed since the last run (cargo test, pytest, vitest)
+    #[arg(long = "all-failures", global = true)]
+    all_failures: bool,
+
+    /// Output format for commands with a structured parser: text, json (goes before the subcommand)
+    #[arg(long, default_value = "text", value_parser = ["text", "json"])]
+    format: String,
 }
 
 #[derive(Subcommand)]
@@ -150,6 +176,9 @@ enum Commands {
 
     /// Run tests and show only failures
     Test {
+        /// Read results from JUnit XML reports written by the command (file, directory or glob)
+        #[arg(long, value_name = "PATH|GLOB")]
+        junit: Option<String>,
         /// Test command (e.g. cargo test)
         #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
         command: Vec<String>,
@@ -332,9 +361,21 @@ enum Commands {
         /// Show all time breakdowns (daily + weekly + monthly)
         #[arg(short, long)]
         all: bool,
+        /// Show savings per project (git toplevel), with a per-command breakdown
+        #[arg(long)]
+        by_project: bool,
         /// Output format: text, json, csv
         #[arg(short, long, default_value = "text")]
         format: String,
+        /// Show tracking database path, schema version and row count
+        #[arg(long)]
+        db_info: bool,
+        /// Only count commands run inside the current project (git toplevel)
+        #[arg(long)]
+        project: bool,
+        /// Only count commands that exited non-zero (commands that record no exit code, such as git, ls and gh, are left out)
+        #[arg(long)]
+        failed_only: bool,
     },
 
     /// Claude Code economics: spending (ccusage) vs savings (rtk) analysis
@@ -356,7 +397,7 @@ enum Commands {
         format: String,
     },
 
-    /// Show or create configuration file
+    /// Show effective configuration (global + project .rtk.toml) or create the global file
     Config {
         /// Create default config file
         #[arg(long)]
@@ -444,7 +485,7 @@ enum Commands {
         args: Vec<String>,
     },
 
-    /// Discover missed RTK savings from Claude Code history
+    /// Discover missed RTK savings from agent session history (Claude Code, Codex, Aider)
     Discover {
         /// Filter by project path (substring match)
         #[arg(short, long)]
@@ -461,9 +502,15 @@ enum Commands {
         /// Output format: text, json
         #[arg(short, long, default_value = "text")]
         format: String,
+        /// Agent whose session history to scan
+        #[arg(long, default_value = "claude", value_parser = discover::provider::PROVIDER_NAMES)]
+        provider: String,
+        /// Append filter stubs for the most frequent unhandled commands to config.toml
+        #[arg(long)]
+        apply: bool,
     },
 
-    /// Learn CLI corrections from Claude Code error history
+    /// Learn CLI corrections from agent session error history
     Learn {
         /// Filter by project path (substring match)
         #[arg(short, long)]
@@ -480,6 +527,9 @@ enum Commands {
         /// Generate .claude/rules/cli-corrections.md file
         #[arg(short, long)]
         write_rules: bool,
+        /// Agent whose session history to scan
+        #[arg(long, default_value = "claude", value_parser = discover::provider::PROVIDER_NAMES)]
+        provider: String,
         /// Minimum confidence threshold (0.0-1.0)
         #[arg(long, default_value = "0.6")]
         min_confidence: f64,
@@ -488,6 +538,14 @@ enum Commands {
         min_occurrences: usize,
     },
 
+    /// Run captured output through an rtk filter without running the tool
+    Replay {
+        /// Filter name: cargo-test, git-diff, pytest, ... (an unknown name lists them all)
+        tool: String,
+        /// File with the captured output (omit or - for stdin)
+        file: Option<PathBuf>,
+    },
+
     /// Execute command without filtering but track usage
     Proxy {
         /// Command and arguments to execute
@@ -495,6 +553,13 @@ enum Commands {
         args: Vec<OsString>,
     },
 
+    /// Run any command through a user-defined filter from config.toml ([[filters.custom]])
+    Run {
+        /// Command and arguments to execute
+        #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+        args: Vec<String>,
+    },
+
     /// Ruff linter/formatter with compact output
     Ruff {
         /// Ruff arguments (e.g., check, format --check)
@@ -537,6 +602,15 @@ enum Commands {
         #[arg(short, long, default_value = "7")]
         since: u64,
     },
+
+    /// Claude Code PreToolUse hook: read hook JSON on stdin, print rewritten command JSON
+    Rewrite,
+
+    /// Browse raw outputs saved on failure (list, show, grep)
+    Tee {
+        #[command(subcommand)]
+        command: TeeCommands,
+    },
 }
 
 #[derive(Subcommand)]
@@ -669,7 +743,12 @@ enum DockerCommands {
     /// List images
     Images,
     /// Show container logs (deduplicated)
-    Logs { container: String },
+    Logs {
+        container: String,
+        /// Stream new errors and warnings as they arrive
+        #[arg(short, long)]
+        follow: bool,
+    },
     /// Docker Compose commands with compact output
     Compose {
         #[command(subcommand)]
@@ -722,6 +801,9 @@ enum KubectlCommands {
         pod: String,
         #[arg(short, long)]
         container: Option<String>,
+        /// Stream new errors and warnings as they arrive
+        #[arg(short, long)]
+        follow: bool,
     },
     /// Passthrough: runs any unsupported kubectl subcommand directly
     #[command(external_subcommand)]
@@ -852,8 +934,76 @@ enum GoCommands {
     Other(Vec<OsString>),
 }
 
+#[derive(Subcommand)]
+enum TeeCommands {
+    /// List saved raw outputs, newest first
+    List,
+    /// Print a saved raw output
+    Show {
+        /// Tee id (from `rtk tee list`) or list position (1 = newest)
+        id: String,
+        /// Only print one section referenced by a filter (e.g. tsc:src/foo.ts, cargo-test:mod::name)
+        #[arg(long)]
+        section: Option<String>,
+        /// Only print these lines (1-based, inclusive): A:B, A:, :B or N
+        #[arg(long)]
+        lines: Option<String>,
+    },
+    /// Search saved raw outputs (regex), printing id:line: text
+    Grep {
+        pattern: String,
+        /// Only search this tee id
+        id: Option<String>,
+    },
+}
+
+impl Commands {
+    /// Whether the wrapped command runs through [`stream`], so `--timeout`,
+    /// `--max-output` and `[limits]` apply to it (keep in sync with the README).
+    fn honors_limits(&self) -> bool {
+        match self {
+            Commands::Cargo { command } => !matches!(command, CargoCommands::Other(_)),
+            Commands::Go { command } => matches!(command, GoCommands::Test { .. }),
+            Commands::Docker {
+                command: DockerCommands::Logs { follow, .. },
+            }
+            | Commands::Kubectl {
+                command: KubectlCommands::Logs { follow, .. },
+            } => *follow,
+            Commands::Npx { args } => !matches!(
+                args.first().map(String::as_str),
+                None | Some("prisma" | "prettier")
+            ),
+            Commands::Pytest { .. }
+            | Commands::Vitest { .. }
+            | Commands::Playwright { .. }
+            | Commands::Tsc { .. }
+            | Commands::Lint { .. }
+            | Commands::Next { .. }
+            | Commands::Npm { .. }
+            | Commands::Test { .. }
+            | Commands::Err { .. }
+            | Commands::Run { .. }
+            | Commands::Proxy { .. } => true,
+            _ => false,
+        }
+    }
+}
+
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let limits = stream::Limits::resolve(cli.timeout, cli.max_output);
+    // `[limits]` in config covers every command, so only say so when asked for
+    // explicitly (or with -v)
+    if limits.is_set()
+        && !cli.command.honors_limits()
+        && (cli.timeout.is_some() || cli.max_output.is_some() || cli.verbose > 0)
+    {
+        eprintln!("⚠️  rtk: timeout/output limits are not applied to this command");
+    }
+    stream::set_limits(limits);
+    parser::set_output_format(parser::OutputFormat::from_name(&cli.format));
+    test_diff::set_show_all(cli.all_failures);
 
     match cli.command {
         Commands::Ls { args } => {
@@ -971,9 +1121,9 @@ fn main() -> Result<()> {
             runner::run_err(&cmd, cli.verbose)?;
         }
 
-        Commands::Test { command } => {
+        Commands::Test { junit, command } => {
             let cmd = command.join(" ");
-            runner::run_test(&cmd, cli.verbose)?;
+            runner::run_test(&cmd, junit.as_deref(), cli.verbose)?;
         }
 
         Commands::Json { file, depth } => {
@@ -1024,8 +1174,15 @@ fn main() -> Result<()> {
             DockerCommands::Images => {
                 container::run(container::ContainerCmd::DockerImages, &[], cli.verbose)?;
             }
-            DockerCommands::Logs { container: c } => {
-                container::run(container::ContainerCmd::DockerLogs, &[c], cli.verbose)?;
+            DockerCommands::Logs {
+                container: c,
+                follow,
+            } => {
+                let mut args = vec![c];
+                if follow {
+                    args.push("--follow".to_string());
+                }
+                container::run(container::ContainerCmd::DockerLogs, &args, cli.verbose)?;
             }
             DockerCommands::Compose { command: compose } => match compose {
                 ComposeCommands::Ps => {
@@ -1067,12 +1224,19 @@ fn main() -> Result<()> {
                 }
                 container::run(container::ContainerCmd::KubectlServices, &args, cli.verbose)?;
             }
-            KubectlCommands::Logs { pod, container: c } => {
+            KubectlCommands::Logs {
+                pod,
+                container: c,
+                follow,
+            } => {
                 let mut args = vec![pod];
                 if let Some(cont) = c {
                     args.push("-c".to_string());
                     args.push(cont);
                 }
+                if follow {
+                    args.push("--follow".to_string());
+                }
                 container::run(container::ContainerCmd::KubectlLogs, &args, cli.verbose)?;
             }
             KubectlCommands::Other(args) => {
@@ -1153,20 +1317,33 @@ fn main() -> Result<()> {
             weekly,
             monthly,
             all,
+            by_project,
             format,
+            db_info,
+            project,
+            failed_only,
         } => {
-            gain::run(
-                graph,
-                history,
-                quota,
-                &tier,
-                daily,
-                weekly,
-                monthly,
-                all,
-                &format,
-                cli.verbose,
-            )?;
+            if db_info {
+                gain::show_db_info(&format)?;
+            } else {
+                gain::run(
+                    &gain::GainOptions {
+                        graph,
+                        history,
+                        quota,
+                        tier: &tier,
+                        daily,
+                        weekly,
+                        monthly,
+                        all,
+                        by_project,
+                        format: &format,
+                        project,
+                        failed_only,
+                    },
+                    cli.verbose,
+                )?;
+            }
         }
 
         Commands::CcEconomics {
@@ -1294,8 +1471,16 @@ fn main() -> Result<()> {
             all,
             since,
             format,
+            provider,
+            apply,
         } => {
-            discover::run(project.as_deref(), all, since, limit, &format, cli.verbose)?;
+            let scope = discover::provider::SessionScope {
+                provider: &provider,
+                project: project.as_deref(),
+                all,
+                since_days: since,
+            };
+            discover::run(&scope, limit, &format, apply, cli.verbose)?;
         }
 
         Commands::Learn {
@@ -1306,16 +1491,19 @@ fn main() -> Result<()> {
             write_rules,
             min_confidence,
             min_occurrences,
+            provider,
         } => {
-            learn::run(
-                project,
+            let scope = discover::provider::SessionScope {
+                provider: &provider,
+                project: project.as_deref(),
                 all,
-                since,
-                format,
-                write_rules,
-                min_confidence,
-                min_occurrences,
-            )?;
+                since_days: since,
+            };
+            learn::run(&scope, format, write_rules, min_confidence, min_occurrences)?;
+        }
+
+        Commands::Replay { tool, file } => {
+            replay::run(&tool, file.as_deref(), cli.verbose)?;
         }
 
         Commands::Npx { args } => {
@@ -1431,6 +1619,10 @@ fn main() -> Result<()> {
             hook_audit_cmd::run(since, cli.verbose)?;
         }
 
+        Commands::Rewrite => {
+            rewrite_cmd::run()?;
+        }
+
         Commands::Proxy { args } => {
             use std::process::Command;
 
@@ -1452,32 +1644,42 @@ fn main() -> Result<()> {
                 eprintln!("Proxy mode: {} {}", cmd_name, cmd_args.join(" "));
             }
 
-            let output = Command::new(cmd_name.as_ref())
-                .args(&cmd_args)
-                .output()
+            let output = stream::capture(Command::new(cmd_name.as_ref()).args(&cmd_args))
                 .context(format!("Failed to execute command: {}", cmd_name))?;
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            let full_output = format!("{}{}", stdout, stderr);
+            let full_output = format!("{}{}", output.stdout, output.stderr);
 
-            // Print output
-            print!("{}", stdout);
-            eprint!("{}", stderr);
+            // Print output unchanged
+            output.passthrough();
 
             // Track usage (input = output since no filtering)
-            timer.track(
+            timer.track_with(
                 &format!("{} {}", cmd_name, cmd_args.join(" ")),
                 &format!("rtk proxy {} {}", cmd_name, cmd_args.join(" ")),
                 &full_output,
                 &full_output,
+                tracking::RunContext::exited(output.exit_code),
             );
 
             // Exit with same code as child process
-            if !output.status.success() {
-                std::process::exit(output.status.code().unwrap_or(1));
+            if output.exit_code != 0 {
+                std::process::exit(output.exit_code);
             }
         }
+
+        Commands::Run { args } => {
+            run_cmd::run(&args, cli.verbose)?;
+        }
+
+        Commands::Tee { command } => match command {
+            TeeCommands::List => tee_cmd::list(cli.verbose)?,
+            TeeCommands::Show { id, section, lines } => {
+                tee_cmd::show(&id, section.as_deref(), lines.as_deref(), cli.verbose)?;
+            }
+            TeeCommands::Grep { pattern, id } => {
+                tee_cmd::grep(&pattern, id.as_deref(), cli.verbose)?;
+            }
+        },
     }
 
     Ok(())
@@ -1546,4 +1748,44 @@ mod tests {
             _ => panic!("Expected Git Commit command"),
         }
     }
+
+    #[test]
+    fn test_limit_flags_leave_wrapped_tool_flags_alone() {
+        let cli = Cli::try_parse_from([
+            "rtk",
+            "--timeout",
+            "300",
+            "pytest",
+            "--timeout=30",
+            "--max-output",
+            "tests/",
+        ])
+        .unwrap();
+        assert_eq!(cli.timeout, Some(300));
+        assert_eq!(cli.max_output, None);
+        match cli.command {
+            Commands::Pytest { args } => {
+                assert_eq!(args, vec!["--timeout=30", "--max-output", "tests/"]);
+            }
+            _ => panic!("Expected Pytest command"),
+        }
+    }
+
+    #[test]
+    fn test_honors_limits() {
+        let honors = |args: &[&str]| {
+            Cli::try_parse_from([&["rtk"], args].concat())
+                .unwrap()
+                .command
+                .honors_limits()
+        };
+        assert!(honors(&["tsc"]));
+        assert!(honors(&["cargo", "test"]));
+        assert!(honors(&["npx", "eslint", "."]));
+        assert!(honors(&["docker", "logs", "web", "-f"]));
+        assert!(!honors(&["docker", "logs", "web"]));
+        assert!(!honors(&["npx", "prisma", "generate"]));
+        assert!(!honors(&["go", "build"]));
+        assert!(!honors(&["git", "status"]));
+    }
 }
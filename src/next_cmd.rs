@@ -33,29 +33,32 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: {} build", tool);
     }
 
-    let output = cmd
-        .output()
+    let output = crate::stream::capture(&mut cmd)
         .context("Failed to run next build (try: npm install -g next)")?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let raw = &output.raw;
 
-    let filtered = filter_next_build(&raw);
+    let filtered = output.annotate(&filter_next_build(raw));
 
     println!("{}", filtered);
 
-    timer.track("next build", "rtk next build", &raw, &filtered);
+    timer.track_with(
+        "next build",
+        "rtk next build",
+        raw,
+        &filtered,
+        tracking::RunContext::exited(output.exit_code),
+    );
 
     // Preserve exit code for CI/CD
-    if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+    if output.exit_code != 0 {
+        std::process::exit(output.exit_code);
     }
 
     Ok(())
 }
 
 /// Filter Next.js build output - extract routes, bundles, warnings
-fn filter_next_build(output: &str) -> String {
+pub(crate) fn filter_next_build(output: &str) -> String {
     lazy_static::lazy_static! {
         // Route line pattern: ○ /dashboard    1.2 kB  132 kB
         static ref ROUTE_PATTERN: Regex = Regex::new(
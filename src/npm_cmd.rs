@@ -20,30 +20,29 @@ pub fn run(args: &[String], verbose: u8, skip_env: bool) -> Result<()> {
         eprintln!("Running: npm run {}", args.join(" "));
     }
 
-    let output = cmd.output().context("Failed to run npm run")?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
+    let output = crate::stream::capture(&mut cmd).context("Failed to run npm run")?;
+    let raw = &output.raw;
 
-    let filtered = filter_npm_output(&raw);
+    let filtered = output.annotate(&filter_npm_output(raw));
     println!("{}", filtered);
 
-    timer.track(
+    timer.track_with(
         &format!("npm run {}", args.join(" ")),
         &format!("rtk npm run {}", args.join(" ")),
-        &raw,
+        raw,
         &filtered,
+        tracking::RunContext::exited(output.exit_code),
     );
 
-    if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+    if output.exit_code != 0 {
+        std::process::exit(output.exit_code);
     }
 
     Ok(())
 }
 
 /// Filter npm run output - strip boilerplate, progress bars, npm WARN
-fn filter_npm_output(output: &str) -> String {
+pub(crate) fn filter_npm_output(output: &str) -> String {
     let mut result = Vec::new();
 
     for line in output.lines() {
@@ -0,0 +1,198 @@
+//! Repeated-output suppression across invocations.
+//!
+//! Agents often run `rtk git status` or `rtk ls src` twice in a row with
+//! nothing changed in between. With `[cache] enabled = true`, rtk remembers a
+//! hash of the last output per command and directory, together with a cheap
+//! state fingerprint (git index/HEAD mtimes, directory mtimes). When the
+//! command runs again in the same state and prints the same thing, the output
+//! is replaced by a one-line notice. The command itself always runs; only the
+//! printing is skipped. `-v` always prints the full output.
+
+use crate::config::Config;
+use crate::tracking::{self, CachedOutput, Tracker};
+use chrono::{DateTime, Local, Utc};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// `output`, or a notice when the previous run of `command` in this directory
+/// printed the same output with the same `fingerprint` less than
+/// `max_age_secs` ago. `fingerprint` is `None` when the state can't be read.
+pub fn dedupe(command: &str, fingerprint: Option<String>, output: String, verbose: u8) -> String {
+    let config = Config::load().map(|c| c.cache).unwrap_or_default();
+    if !config.enabled || verbose > 0 {
+        return output;
+    }
+    let fingerprint = match fingerprint {
+        Some(f) => f,
+        None => return output,
+    };
+    let cwd = match std::env::current_dir() {
+        Ok(dir) => dir.to_string_lossy().to_string(),
+        Err(_) => return output,
+    };
+    let tracker = match Tracker::new() {
+        Ok(t) => t,
+        Err(_) => return output,
+    };
+
+    let current = CachedOutput {
+        fingerprint,
+        output_hash: hash_output(&output),
+        timestamp: Utc::now().to_rfc3339(),
+    };
+    if let Ok(Some(previous)) = tracker.last_output(command, &cwd) {
+        if is_repeat(&previous, &current, config.max_age_secs, Utc::now()) {
+            return notice(&previous.timestamp, output.ends_with('\n'));
+        }
+    }
+    let _ = tracker.record_output(command, &cwd, &current);
+    output
+}
+
+/// Same state, same output, and the full output was printed recently enough.
+fn is_repeat(
+    previous: &CachedOutput,
+    current: &CachedOutput,
+    max_age_secs: u64,
+    now: DateTime<Utc>,
+) -> bool {
+    if previous.fingerprint != current.fingerprint || previous.output_hash != current.output_hash {
+        return false;
+    }
+    match DateTime::parse_from_rfc3339(&previous.timestamp) {
+        Ok(printed) => {
+            let age = now.signed_duration_since(printed).num_seconds();
+            age >= 0 && (age as u64) < max_age_secs
+        }
+        Err(_) => false,
+    }
+}
+
+fn notice(timestamp: &str, newline: bool) -> String {
+    let since = DateTime::parse_from_rfc3339(timestamp)
+        .map(|t| t.with_timezone(&Local).format("%H:%M").to_string())
+        .unwrap_or_else(|_| timestamp.to_string());
+    let mut text = format!("unchanged since {} (same as previous output)", since);
+    if newline {
+        text.push('\n');
+    }
+    text
+}
+
+fn hash_output(output: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    output.hash(&mut hasher);
+    format!("{:016x}", hasher.finish())
+}
+
+/// Modification times of the current repository's index and HEAD.
+pub fn git_fingerprint() -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    let root = tracking::find_project_root(&cwd)?;
+    let git_dir = git_dir(&root)?;
+    Some(format!(
+        "index:{} head:{}",
+        mtime(&git_dir.join("index")).unwrap_or_else(|| "-".to_string()),
+        mtime(&git_dir.join("HEAD")).unwrap_or_else(|| "-".to_string())
+    ))
+}
+
+/// Modification times of the listed directories (`.` when empty).
+pub fn dirs_fingerprint(paths: &[&str]) -> Option<String> {
+    let paths = if paths.is_empty() { &["."][..] } else { paths };
+    let parts: Option<Vec<String>> = paths
+        .iter()
+        .map(|p| mtime(Path::new(p)).map(|m| format!("{}:{}", p, m)))
+        .collect();
+    parts.map(|p| p.join(" "))
+}
+
+/// `.git` directory of a work tree, following the `gitdir:` file of worktrees.
+fn git_dir(root: &Path) -> Option<PathBuf> {
+    let dot_git = root.join(".git");
+    if dot_git.is_dir() {
+        return Some(dot_git);
+    }
+    let content = std::fs::read_to_string(&dot_git).ok()?;
+    let target = content.trim().strip_prefix("gitdir:")?.trim();
+    Some(root.join(target))
+}
+
+fn mtime(path: &Path) -> Option<String> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    let since_epoch = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some(format!(
+        "{}.{:09}",
+        since_epoch.as_secs(),
+        since_epoch.subsec_nanos()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn cached(fingerprint: &str, output: &str, timestamp: DateTime<Utc>) -> CachedOutput {
+        CachedOutput {
+            fingerprint: fingerprint.to_string(),
+            output_hash: hash_output(output),
+            timestamp: timestamp.to_rfc3339(),
+        }
+    }
+
+    #[test]
+    fn test_is_repeat() {
+        let now = Utc::now();
+        let previous = cached("index:1", "clean", now - Duration::seconds(30));
+        let repeat = |current: CachedOutput, max_age| is_repeat(&previous, &current, max_age, now);
+        assert!(repeat(cached("index:1", "clean", now), 600));
+        // Changed state, changed output, or too old: print again
+        assert!(!repeat(cached("index:2", "clean", now), 600));
+        assert!(!repeat(cached("index:1", "M a.rs", now), 600));
+        assert!(!repeat(cached("index:1", "clean", now), 10));
+    }
+
+    #[test]
+    fn test_notice_keeps_trailing_newline() {
+        let printed = Local::now();
+        let text = notice(&printed.to_rfc3339(), true);
+        assert_eq!(
+            text,
+            format!(
+                "unchanged since {} (same as previous output)\n",
+                printed.format("%H:%M")
+            )
+        );
+        assert!(!notice(&printed.to_rfc3339(), false).ends_with('\n'));
+    }
+
+    #[test]
+    fn test_dirs_fingerprint_changes_with_contents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let before = dirs_fingerprint(&[&path]).unwrap();
+        assert!(before.starts_with(&path));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        std::fs::write(dir.path().join("new.txt"), "x").unwrap();
+        assert_ne!(dirs_fingerprint(&[&path]).unwrap(), before);
+        assert_eq!(dirs_fingerprint(&["/nonexistent/rtk"]), None);
+    }
+
+    #[test]
+    fn test_git_dir_follows_worktree_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join(".git"),
+            "gitdir: ../main/.git/worktrees/wt\n",
+        )
+        .unwrap();
+        assert_eq!(
+            git_dir(dir.path()).unwrap(),
+            dir.path().join("../main/.git/worktrees/wt")
+        );
+    }
+}
@@ -14,6 +14,40 @@ pub mod types;
 pub use formatter::{FormatMode, TokenFormatter};
 pub use types::*;
 
+use serde::Serialize;
+use std::sync::OnceLock;
+
+/// Output format selected with the top-level `--format` flag
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Token-optimized text (default)
+    #[default]
+    Text,
+    /// Canonical parser types as JSON, for editor plugins and scripts
+    Json,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Self {
+        match name {
+            "json" => OutputFormat::Json,
+            _ => OutputFormat::Text,
+        }
+    }
+}
+
+static OUTPUT_FORMAT: OnceLock<OutputFormat> = OnceLock::new();
+
+/// Set the process-wide output format (once, from main).
+pub fn set_output_format(format: OutputFormat) {
+    let _ = OUTPUT_FORMAT.set(format);
+}
+
+/// True when `--format json` was requested.
+pub fn json_output() -> bool {
+    OUTPUT_FORMAT.get().copied().unwrap_or_default() == OutputFormat::Json
+}
+
 /// Parse result with degradation tier
 #[derive(Debug)]
 pub enum ParseResult<T> {
@@ -46,6 +80,14 @@ impl<T> ParseResult<T> {
         }
     }
 
+    /// Parsed data, if any (Full or Degraded)
+    pub fn data(&self) -> Option<&T> {
+        match self {
+            ParseResult::Full(data) | ParseResult::Degraded(data, _) => Some(data),
+            ParseResult::Passthrough(_) => None,
+        }
+    }
+
     /// Check if parsing succeeded (Full or Degraded)
     pub fn is_ok(&self) -> bool {
         !matches!(self, ParseResult::Passthrough(_))
@@ -72,6 +114,85 @@ impl<T> ParseResult<T> {
     }
 }
 
+impl<T: Serialize> ParseResult<T> {
+    /// `--format json` document: the canonical struct with its parse tier and
+    /// warnings. Passthrough results carry `data: null` and the truncated `raw` text.
+    pub fn to_json(&self, tool: &str) -> String {
+        let (data, raw) = match self {
+            ParseResult::Full(data) | ParseResult::Degraded(data, _) => {
+                (serde_json::to_value(data).ok(), None)
+            }
+            ParseResult::Passthrough(raw) => (None, Some(raw.as_str())),
+        };
+        let doc = serde_json::json!({
+            "tool": tool,
+            "tier": self.tier(),
+            "warnings": self.warnings(),
+            "data": data,
+            "raw": raw,
+        });
+        serde_json::to_string_pretty(&doc).unwrap_or_default()
+    }
+}
+
+/// Render a parse result for printing: JSON under `--format json`, otherwise
+/// text in the verbosity's [`FormatMode`]. Emits the tier markers either way.
+pub fn render<T: TokenFormatter + Serialize>(
+    tool: &str,
+    result: ParseResult<T>,
+    verbose: u8,
+) -> String {
+    if json_output() {
+        match &result {
+            ParseResult::Degraded(_, warnings) if verbose > 0 => {
+                emit_degradation_warning(tool, &warnings.join(", "))
+            }
+            ParseResult::Passthrough(_) => {
+                emit_passthrough_warning(tool, "All parsing tiers failed")
+            }
+            _ => {}
+        }
+        return result.to_json(tool);
+    }
+
+    let mode = FormatMode::from_verbosity(verbose);
+    match result {
+        ParseResult::Full(data) => {
+            if verbose > 0 {
+                eprintln!("{} (Tier 1: Full JSON parse)", tool);
+            }
+            data.format(mode)
+        }
+        ParseResult::Degraded(data, warnings) => {
+            if verbose > 0 {
+                emit_degradation_warning(tool, &warnings.join(", "));
+            }
+            data.format(mode)
+        }
+        ParseResult::Passthrough(raw) => {
+            emit_passthrough_warning(tool, "All parsing tiers failed");
+            raw
+        }
+    }
+}
+
+/// Like [`render`], but Compact text (and the Passthrough fallback) comes from
+/// `compact`: the tool's own filter, which knows its layout better than the
+/// generic formatter. JSON and Verbose/Ultra use the canonical type.
+pub fn render_with<T, F>(tool: &str, result: ParseResult<T>, verbose: u8, compact: F) -> String
+where
+    T: TokenFormatter + Serialize,
+    F: FnOnce() -> String,
+{
+    if json_output() || (verbose > 0 && result.is_ok()) {
+        return render(tool, result, verbose);
+    }
+    if !result.is_ok() {
+        emit_passthrough_warning(tool, "All parsing tiers failed");
+    }
+    compact()
+}
+
 /// Unified parser trait for tool outputs
 pub trait OutputParser: Sized {
     type Output;
@@ -221,6 +342,89 @@ mod tests {
         assert_eq!(mapped.unwrap(), 84);
     }
 
+    #[test]
+    fn test_parse_result_to_json() {
+        let result: ParseResult<TestResult> = ParseResult::Degraded(
+            TestResult {
+                total: 3,
+                passed: 2,
+                failed: 1,
+                skipped: 0,
+                duration_ms: None,
+                failures: vec![],
+            },
+            vec!["JSON parse failed".to_string()],
+        );
+        let json: serde_json::Value = serde_json::from_str(&result.to_json("vitest")).unwrap();
+        assert_eq!(json["tool"], "vitest");
+        assert_eq!(json["tier"], 2);
+        assert_eq!(json["warnings"][0], "JSON parse failed");
+        assert_eq!(json["data"]["failed"], 1);
+        assert!(json["raw"].is_null());
+
+        let passthrough: ParseResult<TestResult> = ParseResult::Passthrough("oops".to_string());
+        let json: serde_json::Value = serde_json::from_str(&passthrough.to_json("vitest")).unwrap();
+        assert_eq!(json["tier"], 3);
+        assert!(json["data"].is_null());
+        assert_eq!(json["raw"], "oops");
+    }
+
+    #[test]
+    fn test_render_text() {
+        let result: ParseResult<TestResult> = ParseResult::Full(TestResult {
+            total: 1,
+            passed: 1,
+            failed: 0,
+            skipped: 0,
+            duration_ms: None,
+            failures: vec![],
+        });
+        assert!(render("vitest", result, 0).starts_with("PASS (1) FAIL (0)"));
+        let passthrough: ParseResult<TestResult> = ParseResult::Passthrough("raw".to_string());
+        assert_eq!(render("vitest", passthrough, 0), "raw");
+    }
+
+    #[test]
+    fn test_render_with_uses_tool_filter_for_compact() {
+        let result: ParseResult<TestResult> = ParseResult::Full(TestResult {
+            total: 1,
+            passed: 1,
+            failed: 0,
+            skipped: 0,
+            duration_ms: None,
+            failures: vec![],
+        });
+        assert_eq!(
+            render_with("cargo test", result, 0, || "✓ 1 passed".to_string()),
+            "✓ 1 passed"
+        );
+
+        let result: ParseResult<TestResult> = ParseResult::Full(TestResult {
+            total: 1,
+            passed: 1,
+            failed: 0,
+            skipped: 0,
+            duration_ms: Some(5),
+            failures: vec![],
+        });
+        assert_eq!(
+            render_with("cargo test", result, 2, || unreachable!()),
+            "✓1 ✗0 ⊘0 (5ms)"
+        );
+
+        let passthrough: ParseResult<TestResult> = ParseResult::Passthrough("raw".to_string());
+        assert_eq!(
+            render_with("cargo test", passthrough, 1, || "filtered".to_string()),
+            "filtered"
+        );
+    }
+
+    #[test]
+    fn test_output_format_from_name() {
+        assert_eq!(OutputFormat::from_name("json"), OutputFormat::Json);
+        assert_eq!(OutputFormat::from_name("text"), OutputFormat::Text);
+    }
+
     #[test]
     fn test_truncate_output() {
         let short = "hello";
@@ -32,6 +32,34 @@ pub struct LintResult {
     pub issues: Vec<LintIssue>,
 }
 
+impl LintResult {
+    /// Totals derived from the issue list (files = distinct non-empty paths).
+    pub fn from_issues(issues: Vec<LintIssue>) -> Self {
+        let files: std::collections::HashSet<&str> = issues
+            .iter()
+            .map(|i| i.file_path.as_str())
+            .filter(|f| !f.is_empty())
+            .collect();
+        let files = files.len();
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == LintSeverity::Error)
+            .count();
+        let warnings = issues
+            .iter()
+            .filter(|i| i.severity == LintSeverity::Warning)
+            .count();
+        Self {
+            total_files: files,
+            files_with_issues: files,
+            total_issues: issues.len(),
+            errors,
+            warnings,
+            issues,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LintIssue {
     pub file_path: String,
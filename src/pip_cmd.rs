@@ -166,7 +166,7 @@ fn which_command(cmd: &str) -> Option<String> {
 }
 
 /// Filter pip list JSON output
-fn filter_pip_list(output: &str) -> String {
+pub(crate) fn filter_pip_list(output: &str) -> String {
     let packages: Vec<Package> = match serde_json::from_str(output) {
         Ok(p) => p,
         Err(e) => {
@@ -211,7 +211,7 @@ fn filter_pip_list(output: &str) -> String {
 }
 
 /// Filter pip outdated JSON output
-fn filter_pip_outdated(output: &str) -> String {
+pub(crate) fn filter_pip_outdated(output: &str) -> String {
     let packages: Vec<Package> = match serde_json::from_str(output) {
         Ok(p) => p,
         Err(e) => {
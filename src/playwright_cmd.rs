@@ -4,10 +4,7 @@ use anyhow::{Context, Result};
 use regex::Regex;
 use serde::Deserialize;
 
-use crate::parser::{
-    emit_degradation_warning, emit_passthrough_warning, truncate_output, FormatMode, OutputParser,
-    ParseResult, TestFailure, TestResult, TokenFormatter,
-};
+use crate::parser::{render, truncate_output, OutputParser, ParseResult, TestFailure, TestResult};
 
 /// Matches real Playwright JSON reporter output (suites → specs → tests → results)
 #[derive(Debug, Deserialize)]
@@ -273,49 +270,27 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: playwright {}", args.join(" "));
     }
 
-    let output = cmd
-        .output()
+    let output = crate::stream::capture(&mut cmd)
         .context("Failed to run playwright (try: npm install -g playwright)")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-
     // Parse output using PlaywrightParser
-    let parse_result = PlaywrightParser::parse(&stdout);
-    let mode = FormatMode::from_verbosity(verbose);
-
-    let filtered = match parse_result {
-        ParseResult::Full(data) => {
-            if verbose > 0 {
-                eprintln!("playwright test (Tier 1: Full JSON parse)");
-            }
-            data.format(mode)
-        }
-        ParseResult::Degraded(data, warnings) => {
-            if verbose > 0 {
-                emit_degradation_warning("playwright", &warnings.join(", "));
-            }
-            data.format(mode)
-        }
-        ParseResult::Passthrough(raw) => {
-            emit_passthrough_warning("playwright", "All parsing tiers failed");
-            raw
-        }
-    };
+    let parse_result = PlaywrightParser::parse(&output.stdout);
+    let tier = parse_result.tier();
+    let filtered = output.annotate(&render("playwright test", parse_result, verbose));
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.track_with(
         &format!("playwright {}", args.join(" ")),
         &format!("rtk playwright {}", args.join(" ")),
-        &raw,
+        &output.raw,
         &filtered,
+        tracking::RunContext::exited(output.exit_code).with_tier(tier),
     );
 
     // Preserve exit code for CI/CD
-    if !output.status.success() {
-        std::process::exit(output.status.code().unwrap_or(1));
+    if output.exit_code != 0 {
+        std::process::exit(output.exit_code);
     }
 
     Ok(())
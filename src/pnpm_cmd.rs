@@ -6,8 +6,7 @@ use std::ffi::OsString;
 use std::process::Command;
 
 use crate::parser::{
-    emit_degradation_warning, emit_passthrough_warning, truncate_output, Dependency,
-    DependencyState, FormatMode, OutputParser, ParseResult, TokenFormatter,
+    render, truncate_output, Dependency, DependencyState, OutputParser, ParseResult,
 };
 
 /// pnpm list JSON output structure
@@ -314,34 +313,17 @@ fn run_list(depth: usize, args: &[String], verbose: u8) -> Result<()> {
 
     // Parse output using PnpmListParser
     let parse_result = PnpmListParser::parse(&stdout);
-    let mode = FormatMode::from_verbosity(verbose);
-
-    let filtered = match parse_result {
-        ParseResult::Full(data) => {
-            if verbose > 0 {
-                eprintln!("pnpm list (Tier 1: Full JSON parse)");
-            }
-            data.format(mode)
-        }
-        ParseResult::Degraded(data, warnings) => {
-            if verbose > 0 {
-                emit_degradation_warning("pnpm list", &warnings.join(", "));
-            }
-            data.format(mode)
-        }
-        ParseResult::Passthrough(raw) => {
-            emit_passthrough_warning("pnpm list", "All parsing tiers failed");
-            raw
-        }
-    };
+    let tier = parse_result.tier();
+    let filtered = render("pnpm list", parse_result, verbose);
 
     println!("{}", filtered);
 
-    timer.track(
+    timer.track_with(
         &format!("pnpm list --depth={}", depth),
         &format!("rtk pnpm list --depth={}", depth),
         &stdout,
         &filtered,
+        tracking::RunContext::current().with_tier(tier),
     );
 
     Ok(())
@@ -366,26 +348,8 @@ fn run_outdated(args: &[String], verbose: u8) -> Result<()> {
 
     // Parse output using PnpmOutdatedParser
     let parse_result = PnpmOutdatedParser::parse(&stdout);
-    let mode = FormatMode::from_verbosity(verbose);
-
-    let filtered = match parse_result {
-        ParseResult::Full(data) => {
-            if verbose > 0 {
-                eprintln!("pnpm outdated (Tier 1: Full JSON parse)");
-            }
-            data.format(mode)
-        }
-        ParseResult::Degraded(data, warnings) => {
-            if verbose > 0 {
-                emit_degradation_warning("pnpm outdated", &warnings.join(", "));
-            }
-            data.format(mode)
-        }
-        ParseResult::Passthrough(raw) => {
-            emit_passthrough_warning("pnpm outdated", "All parsing tiers failed");
-            raw
-        }
-    };
+    let tier = parse_result.tier();
+    let filtered = render("pnpm outdated", parse_result, verbose);
 
     if filtered.trim().is_empty() {
         println!("All packages up-to-date ✓");
@@ -393,7 +357,13 @@ fn run_outdated(args: &[String], verbose: u8) -> Result<()> {
         println!("{}", filtered);
     }
 
-    timer.track("pnpm outdated", "rtk pnpm outdated", &combined, &filtered);
+    timer.track_with(
+        "pnpm outdated",
+        "rtk pnpm outdated",
+        &combined,
+        &filtered,
+        tracking::RunContext::current().with_tier(tier),
+    );
 
     Ok(())
 }
@@ -450,7 +420,7 @@ fn run_install(packages: &[String], args: &[String], verbose: u8) -> Result<()>
 }
 
 /// Filter pnpm install output - remove progress bars, keep summary
-fn filter_pnpm_install(output: &str) -> String {
+pub(crate) fn filter_pnpm_install(output: &str) -> String {
     let mut result = Vec::new();
     let mut saw_progress = false;
 
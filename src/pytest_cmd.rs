@@ -1,6 +1,11 @@
+use crate::parser::{
+    render_with, truncate_output, OutputParser, ParseResult, TestFailure, TestResult,
+};
+use crate::test_diff;
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
+use regex::Regex;
 use std::process::Command;
 
 #[derive(Debug, PartialEq)]
@@ -43,21 +48,27 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: pytest --tb=short -q {}", args.join(" "));
     }
 
-    let output = cmd
-        .output()
+    let output = crate::stream::capture(&mut cmd)
         .context("Failed to run pytest. Is it installed? Try: pip install pytest")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-
-    let filtered = filter_pytest_output(&stdout);
-
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "pytest", exit_code) {
+    let stderr = &output.stderr;
+    let raw = &output.raw;
+
+    let parse_result = PytestParser::parse(&output.stdout);
+    let tier = parse_result.tier();
+    let current = parse_result.data().cloned();
+    let diff = test_diff::record(&format!("pytest {}", args.join(" ")), current.as_ref());
+    let filtered = output.annotate(&render_with("pytest", parse_result, verbose, || {
+        test_diff::compact(
+            "pytest",
+            current.as_ref(),
+            diff,
+            filter_pytest_output(&output.stdout),
+        )
+    }));
+
+    let exit_code = output.exit_code;
+    if let Some(hint) = crate::tee::tee_and_hint(raw, "pytest", exit_code) {
         println!("{}\n{}", filtered, hint);
     } else {
         println!("{}", filtered);
@@ -68,21 +79,92 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("{}", stderr.trim());
     }
 
-    timer.track(
+    timer.track_with(
         &format!("pytest {}", args.join(" ")),
         &format!("rtk pytest {}", args.join(" ")),
-        &raw,
+        raw,
         &filtered,
+        tracking::RunContext::exited(exit_code).with_tier(tier),
     );
 
     // Preserve exit code for CI/CD
-    if !output.status.success() {
+    if exit_code != 0 {
         std::process::exit(exit_code);
     }
 
     Ok(())
 }
 
+/// Parser for pytest's terminal output (`-q --tb=short`)
+pub struct PytestParser;
+
+impl OutputParser for PytestParser {
+    type Output = TestResult;
+
+    fn parse(input: &str) -> ParseResult<TestResult> {
+        lazy_static::lazy_static! {
+            // "4 passed, 1 failed in 0.50s" (decorated with === outside -q mode)
+            static ref SUMMARY: Regex =
+                Regex::new(r"^=*\s*(\d+ \w+.*|no tests ran) in ([\d.]+)s").unwrap();
+            static ref COUNT: Regex = Regex::new(r"(\d+) (\w+)").unwrap();
+        }
+
+        let mut result = TestResult {
+            total: 0,
+            passed: 0,
+            failed: 0,
+            skipped: 0,
+            duration_ms: None,
+            failures: Vec::new(),
+        };
+
+        let mut has_summary = false;
+        for line in input.lines() {
+            let trimmed = line.trim();
+            if let Some(caps) = SUMMARY.captures(trimmed) {
+                has_summary = true;
+                for count in COUNT.captures_iter(&caps[1]) {
+                    let n: usize = count[1].parse().unwrap_or(0);
+                    match &count[2] {
+                        "passed" | "xpassed" => result.passed += n,
+                        "failed" | "error" | "errors" => result.failed += n,
+                        "skipped" | "xfailed" => result.skipped += n,
+                        _ => {}
+                    }
+                }
+                let secs: f64 = caps[2].parse().unwrap_or(0.0);
+                result.duration_ms = Some((secs * 1000.0).round() as u64);
+            } else if let Some(rest) = trimmed
+                .strip_prefix("FAILED ")
+                .or_else(|| trimmed.strip_prefix("ERROR "))
+            {
+                // "FAILED tests/test_foo.py::test_bar - AssertionError: ..."
+                let (node, message) = rest.split_once(" - ").unwrap_or((rest, ""));
+                result.failures.push(TestFailure {
+                    test_name: node.to_string(),
+                    file_path: node.split("::").next().unwrap_or(node).to_string(),
+                    error_message: message.to_string(),
+                    stack_trace: None,
+                });
+            }
+        }
+        result.total = result.passed + result.failed + result.skipped;
+
+        if has_summary {
+            ParseResult::Full(result)
+        } else if !result.failures.is_empty() {
+            result.failed = result.failures.len();
+            result.total = result.failed;
+            ParseResult::Degraded(
+                result,
+                vec!["no summary line, counted FAILED/ERROR lines".to_string()],
+            )
+        } else {
+            ParseResult::Passthrough(truncate_output(input, 500))
+        }
+    }
+}
+
 /// Check if a command exists in PATH
 fn which_command(cmd: &str) -> Option<String> {
     Command::new("which")
@@ -96,7 +178,7 @@ fn which_command(cmd: &str) -> Option<String> {
 }
 
 /// Parse pytest output using state machine
-fn filter_pytest_output(output: &str) -> String {
+pub(crate) fn filter_pytest_output(output: &str) -> String {
     let mut state = ParseState::Header;
     let mut test_files: Vec<String> = Vec::new();
     let mut failures: Vec<String> = Vec::new();
@@ -369,6 +451,45 @@ collected 0 items
         assert!(result.contains("No tests collected"));
     }
 
+    #[test]
+    fn test_pytest_parser() {
+        let output = r#"=== test session starts ===
+collected 6 items
+
+tests/test_foo.py ..F.s.                                           [100%]
+
+=== short test summary info ===
+FAILED tests/test_foo.py::test_one - AssertionError: expected 5
+=== 4 passed, 1 failed, 1 skipped in 0.25s ==="#;
+        let result = PytestParser::parse(output);
+        assert_eq!(result.tier(), 1);
+        let tests = result.unwrap();
+        assert_eq!((tests.passed, tests.failed, tests.skipped), (4, 1, 1));
+        assert_eq!(tests.total, 6);
+        assert_eq!(tests.duration_ms, Some(250));
+        assert_eq!(tests.failures[0].test_name, "tests/test_foo.py::test_one");
+        assert_eq!(tests.failures[0].file_path, "tests/test_foo.py");
+        assert_eq!(
+            tests.failures[0].error_message,
+            "AssertionError: expected 5"
+        );
+
+        // -q output: undecorated summary
+        let quiet = PytestParser::parse("..\n2 passed in 0.01s\n");
+        assert_eq!(quiet.tier(), 1);
+        assert_eq!(quiet.unwrap().passed, 2);
+
+        let no_tests = PytestParser::parse("=== no tests ran in 0.00s ===");
+        assert_eq!(no_tests.tier(), 1);
+        assert_eq!(no_tests.unwrap().total, 0);
+
+        let cut = PytestParser::parse("FAILED tests/test_a.py::test_x - boom\n");
+        assert_eq!(cut.tier(), 2);
+        assert_eq!(cut.unwrap().failed, 1);
+
+        assert_eq!(PytestParser::parse("ImportError: no module").tier(), 3);
+    }
+
     #[test]
     fn test_parse_summary_line() {
         assert_eq!(parse_summary_line("=== 5 passed in 0.50s ==="), (5, 0, 0));
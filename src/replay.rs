@@ -0,0 +1,565 @@
+//! Offline replay of recorded command output through rtk's filters.
+//!
+//! Each entry names the filter an `rtk` command applies to the tool's output.
+//! `rtk replay <tool> [file]` runs captured output through it without running
+//! the tool, for filter changes and bug reports with attached raw logs.
+//!
+//! Entries with a `command` pattern read the plain output of that command,
+//! so `rtk discover` can replay the outputs in the session history to measure
+//! savings instead of estimating them. The others read the format rtk asks
+//! the tool for (`git status --porcelain -b`, `go test -json`, pip's JSON):
+//! a recorded `git log` or `pip list` can't be replayed.
+
+use crate::tracking::estimate_tokens;
+use crate::{
+    cargo_cmd, curl_cmd, git, go_cmd, golangci_cmd, lint_cmd, ls, next_cmd, npm_cmd, pip_cmd,
+    pnpm_cmd, prettier_cmd, pytest_cmd, ruff_cmd, tree, tsc_cmd,
+};
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::io::Read;
+use std::path::Path;
+
+/// A filter and the commands whose output it reads.
+struct Replay {
+    /// Name for `rtk replay`
+    name: &'static str,
+    /// Regex on the command line (environment prefix removed) whose plain
+    /// output this filter reads; `None` when rtk asks for another format
+    command: Option<&'static str>,
+    filter: fn(&str) -> String,
+}
+
+const REPLAYS: &[Replay] = &[
+    Replay {
+        name: "cargo-test",
+        command: Some(r"^cargo\s+test\b"),
+        filter: cargo_cmd::replay_test,
+    },
+    Replay {
+        name: "cargo-build",
+        command: Some(r"^cargo\s+(build|check)\b"),
+        filter: cargo_cmd::filter_cargo_build,
+    },
+    Replay {
+        name: "cargo-clippy",
+        command: Some(r"^cargo\s+clippy\b"),
+        filter: cargo_cmd::filter_cargo_clippy,
+    },
+    Replay {
+        name: "cargo-install",
+        command: Some(r"^cargo\s+install\b"),
+        filter: cargo_cmd::filter_cargo_install,
+    },
+    Replay {
+        name: "cargo-nextest",
+        command: Some(r"^cargo\s+nextest\b"),
+        filter: cargo_cmd::filter_cargo_nextest,
+    },
+    // `git status --porcelain -b`
+    Replay {
+        name: "git-status",
+        command: None,
+        filter: git::format_status_output,
+    },
+    Replay {
+        name: "git-diff",
+        command: Some(r"^git\s+(-C\s+\S+\s+)?diff\b"),
+        filter: |output| git::compact_diff(output, 100),
+    },
+    // `git log --pretty=format:"%h %s (%ar) <%an>"`, default limit
+    Replay {
+        name: "git-log",
+        command: None,
+        filter: |output| git::filter_log_output(output, 10),
+    },
+    // Human `git status`, as filtered for `rtk git status <args>`
+    Replay {
+        name: "git-status-args",
+        command: Some(r"^git\s+(-C\s+\S+\s+)?status\b"),
+        filter: git::filter_status_with_args,
+    },
+    Replay {
+        name: "git-branch",
+        command: Some(r"^git\s+(-C\s+\S+\s+)?branch\s*$"),
+        filter: git::filter_branch_output,
+    },
+    Replay {
+        name: "git-stash-list",
+        command: Some(r"^git\s+(-C\s+\S+\s+)?stash\s+list\b"),
+        filter: git::filter_stash_list,
+    },
+    Replay {
+        name: "git-worktree-list",
+        command: Some(r"^git\s+(-C\s+\S+\s+)?worktree\s+list\b"),
+        filter: git::filter_worktree_list,
+    },
+    // rtk ls always lists with -la
+    Replay {
+        name: "ls",
+        command: Some(r"^ls\s+-[a-zA-Z]*l"),
+        filter: |output| ls::compact_ls(output, true),
+    },
+    Replay {
+        name: "tree",
+        command: Some(r"^tree\b"),
+        filter: tree::filter_tree_output,
+    },
+    Replay {
+        name: "pytest",
+        command: Some(r"^(python3?\s+-m\s+)?pytest\b"),
+        filter: pytest_cmd::filter_pytest_output,
+    },
+    Replay {
+        name: "eslint",
+        command: None,
+        filter: lint_cmd::filter_eslint_json,
+    },
+    Replay {
+        name: "pylint",
+        command: None,
+        filter: lint_cmd::filter_pylint_json,
+    },
+    Replay {
+        name: "mypy",
+        command: Some(r"^(python3?\s+-m\s+)?mypy\b"),
+        filter: lint_cmd::filter_mypy_output,
+    },
+    Replay {
+        name: "ruff-check",
+        command: None,
+        filter: ruff_cmd::filter_ruff_check_json,
+    },
+    Replay {
+        name: "ruff-format",
+        command: Some(r"^ruff\s+format\b"),
+        filter: ruff_cmd::filter_ruff_format,
+    },
+    Replay {
+        name: "go-test",
+        command: None,
+        filter: go_cmd::filter_go_test_json,
+    },
+    Replay {
+        name: "go-build",
+        command: Some(r"^go\s+build\b"),
+        filter: go_cmd::filter_go_build,
+    },
+    Replay {
+        name: "go-vet",
+        command: Some(r"^go\s+vet\b"),
+        filter: go_cmd::filter_go_vet,
+    },
+    Replay {
+        name: "golangci-lint",
+        command: None,
+        filter: golangci_cmd::filter_golangci_json,
+    },
+    Replay {
+        name: "tsc",
+        command: Some(r"^(npx\s+)?tsc\b"),
+        filter: tsc_cmd::filter_tsc_output,
+    },
+    Replay {
+        name: "npm-run",
+        command: Some(r"^npm\s+run\b"),
+        filter: npm_cmd::filter_npm_output,
+    },
+    Replay {
+        name: "next-build",
+        command: Some(r"^(npx\s+)?next\s+build\b"),
+        filter: next_cmd::filter_next_build,
+    },
+    Replay {
+        name: "prettier",
+        command: Some(r"^(npx\s+)?prettier\b"),
+        filter: prettier_cmd::filter_prettier_output,
+    },
+    Replay {
+        name: "pnpm-install",
+        command: Some(r"^pnpm\s+(install|i)\b"),
+        filter: pnpm_cmd::filter_pnpm_install,
+    },
+    Replay {
+        name: "pip-list",
+        command: None,
+        filter: pip_cmd::filter_pip_list,
+    },
+    Replay {
+        name: "pip-outdated",
+        command: None,
+        filter: pip_cmd::filter_pip_outdated,
+    },
+    Replay {
+        name: "curl",
+        command: Some(r"^curl\b"),
+        filter: curl_cmd::filter_curl_output,
+    },
+];
+
+lazy_static::lazy_static! {
+    static ref COMMANDS: Vec<Option<Regex>> = REPLAYS
+        .iter()
+        .map(|r| r.command.map(|c| Regex::new(c).unwrap()))
+        .collect();
+}
+
+/// Tokens of one recorded output before and after its rtk filter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+}
+
+impl Measurement {
+    pub fn saved_tokens(&self) -> usize {
+        self.input_tokens.saturating_sub(self.output_tokens)
+    }
+
+    pub fn savings_pct(&self) -> f64 {
+        if self.input_tokens == 0 {
+            return 0.0;
+        }
+        self.saved_tokens() as f64 * 100.0 / self.input_tokens as f64
+    }
+}
+
+/// The filter rtk applies to the output of `command`, if a recorded output
+/// of `command` can be replayed through it.
+fn filter_for(command: &str) -> Option<fn(&str) -> String> {
+    let command = command.trim();
+    COMMANDS
+        .iter()
+        .position(|re| re.as_ref().is_some_and(|re| re.is_match(command)))
+        .map(|i| REPLAYS[i].filter)
+}
+
+/// Names accepted by `rtk replay`.
+pub fn names() -> Vec<&'static str> {
+    REPLAYS.iter().map(|r| r.name).collect()
+}
+
+fn by_name(name: &str) -> Option<fn(&str) -> String> {
+    REPLAYS.iter().find(|r| r.name == name).map(|r| r.filter)
+}
+
+/// Filter captured output from `file` (stdin when `None` or "-") with the
+/// `tool` filter: filtered output on stdout, token stats on stderr.
+pub fn run(tool: &str, file: Option<&Path>, verbose: u8) -> Result<()> {
+    let filter = match by_name(tool) {
+        Some(f) => f,
+        None => anyhow::bail!(
+            "unknown filter '{}' (available: {})",
+            tool,
+            names().join(", ")
+        ),
+    };
+
+    let raw = match file {
+        Some(path) if path != Path::new("-") => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        _ => {
+            let mut input = String::new();
+            std::io::stdin()
+                .read_to_string(&mut input)
+                .context("Failed to read from stdin")?;
+            input
+        }
+    };
+    if verbose > 0 {
+        eprintln!("Replaying {} lines through {}", raw.lines().count(), tool);
+    }
+
+    let filtered = filter(&raw);
+    println!("{}", filtered);
+
+    let measurement = Measurement {
+        input_tokens: estimate_tokens(&raw),
+        output_tokens: estimate_tokens(&filtered),
+    };
+    eprintln!("{}", stats_line(tool, &measurement));
+    Ok(())
+}
+
+fn stats_line(tool: &str, m: &Measurement) -> String {
+    format!(
+        "rtk replay {}: {} -> {} tokens (-{:.1}%)",
+        tool,
+        m.input_tokens,
+        m.output_tokens,
+        m.savings_pct()
+    )
+}
+
+/// Replay `output` of `command` through its rtk filter. `None` when rtk has
+/// no filter for the command's plain output, or the output is empty.
+pub fn measure(command: &str, output: &str) -> Option<Measurement> {
+    let filter = filter_for(command)?;
+    if output.trim().is_empty() {
+        return None;
+    }
+    Some(Measurement {
+        input_tokens: estimate_tokens(output),
+        output_tokens: estimate_tokens(&filter(output)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use std::path::PathBuf;
+
+    /// Golden-file corpus: see manifest.toml there
+    fn snapshots_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("src/fixtures/snapshots")
+    }
+
+    #[derive(Deserialize)]
+    struct Manifest {
+        /// Filters known to have no fixture yet
+        #[serde(default)]
+        uncovered: Vec<String>,
+        fixture: Vec<Fixture>,
+    }
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        /// `<filter>/<case>.raw`, relative to the snapshots directory
+        path: String,
+        min_reduction_pct: f64,
+    }
+
+    /// Signed: filters may grow already compact output
+    fn reduction_pct(raw: &str, filtered: &str) -> f64 {
+        let before = estimate_tokens(raw) as f64;
+        let after = estimate_tokens(filtered) as f64;
+        if before == 0.0 {
+            return 0.0;
+        }
+        (before - after) * 100.0 / before
+    }
+
+    /// Where `actual` first departs from `expected`, for the failure message.
+    fn first_difference(expected: &str, actual: &str) -> String {
+        let mut expected_lines = expected.lines();
+        let mut actual_lines = actual.lines();
+        for line_no in 1.. {
+            match (expected_lines.next(), actual_lines.next()) {
+                (Some(e), Some(a)) if e == a => continue,
+                (None, None) => break,
+                (e, a) => {
+                    return format!(
+                        "line {}: expected {:?}, got {:?}",
+                        line_no,
+                        e.unwrap_or("<end>"),
+                        a.unwrap_or("<end>")
+                    )
+                }
+            }
+        }
+        "trailing newline differs".to_string()
+    }
+
+    /// Checks every fixture against its golden file and reduction floor.
+    /// `RTK_UPDATE_SNAPSHOTS=1` rewrites the golden files instead.
+    #[test]
+    fn test_snapshots() {
+        let dir = snapshots_dir();
+        let update = std::env::var_os("RTK_UPDATE_SNAPSHOTS").is_some();
+        let manifest: Manifest =
+            toml::from_str(&std::fs::read_to_string(dir.join("manifest.toml")).unwrap()).unwrap();
+        let mut problems = Vec::new();
+
+        for fixture in &manifest.fixture {
+            let raw_path = dir.join(&fixture.path);
+            let tool = raw_path
+                .parent()
+                .and_then(Path::file_name)
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let filter = match by_name(&tool) {
+                Some(f) => f,
+                None => {
+                    problems.push(format!("{}: no filter named '{}'", fixture.path, tool));
+                    continue;
+                }
+            };
+            let raw = match std::fs::read_to_string(&raw_path) {
+                Ok(raw) => raw,
+                Err(e) => {
+                    problems.push(format!("{}: {}", fixture.path, e));
+                    continue;
+                }
+            };
+            let filtered = filter(&raw);
+
+            let golden_path = raw_path.with_extension("golden");
+            if update {
+                std::fs::write(&golden_path, &filtered).unwrap();
+            } else {
+                match std::fs::read_to_string(&golden_path) {
+                    Ok(expected) if expected == filtered => {}
+                    Ok(expected) => problems.push(format!(
+                        "{}: output changed, {}",
+                        fixture.path,
+                        first_difference(&expected, &filtered)
+                    )),
+                    Err(_) => problems.push(format!("{}: no golden file", fixture.path)),
+                }
+            }
+
+            let pct = reduction_pct(&raw, &filtered);
+            if pct < fixture.min_reduction_pct {
+                problems.push(format!(
+                    "{}: {:.1}% token reduction, floor is {}%",
+                    fixture.path, pct, fixture.min_reduction_pct
+                ));
+            }
+        }
+
+        // Captured outputs nobody checks are a mistake
+        for entry in walkdir::WalkDir::new(&dir) {
+            let path = entry.unwrap().into_path();
+            if path.extension().is_some_and(|e| e == "raw") {
+                let relative = path
+                    .strip_prefix(&dir)
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string();
+                if !manifest.fixture.iter().any(|f| f.path == relative) {
+                    problems.push(format!("{}: missing from manifest.toml", relative));
+                }
+            }
+        }
+
+        // Every filter has a fixture, or is listed as not having one yet
+        for name in names() {
+            let covered = manifest
+                .fixture
+                .iter()
+                .any(|f| f.path.starts_with(&format!("{}/", name)));
+            let listed = manifest.uncovered.iter().any(|u| u == name);
+            if !covered && !listed {
+                problems.push(format!(
+                    "{}: no fixture (capture one, or list it under `uncovered`)",
+                    name
+                ));
+            } else if covered && listed {
+                problems.push(format!(
+                    "{}: has a fixture, remove it from `uncovered`",
+                    name
+                ));
+            }
+        }
+        for name in &manifest.uncovered {
+            if by_name(name).is_none() {
+                problems.push(format!("uncovered: no filter named '{}'", name));
+            }
+        }
+
+        assert!(
+            problems.is_empty(),
+            "snapshot failures (after an intended change: RTK_UPDATE_SNAPSHOTS=1 cargo test snapshots):\n{}",
+            problems.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_first_difference() {
+        assert_eq!(
+            first_difference("a\nb\nc", "a\nx\nc"),
+            r#"line 2: expected "b", got "x""#
+        );
+        assert_eq!(
+            first_difference("a", "a\nb"),
+            r#"line 2: expected "<end>", got "b""#
+        );
+        assert_eq!(first_difference("a\n", "a"), "trailing newline differs");
+        assert_eq!(reduction_pct("aaaaaaaa", "aaaaaaaaaaaa"), -50.0);
+    }
+
+    #[test]
+    fn test_every_command_pattern_compiles() {
+        assert_eq!(COMMANDS.len(), REPLAYS.len());
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        let mut names = names();
+        let count = names.len();
+        names.sort();
+        names.dedup();
+        assert_eq!(names.len(), count);
+        assert!(by_name("cargo-test").is_some());
+        assert!(by_name("git-status").is_some());
+        assert!(by_name("cargo").is_none());
+    }
+
+    #[test]
+    fn test_replay_only_filters_are_not_measured() {
+        // git log output recorded in a session isn't in rtk's --pretty format
+        assert!(filter_for("git log").is_none());
+        assert!(by_name("git-log").is_some());
+        let porcelain = "## main...origin/main\n M src/main.rs\n";
+        assert!(by_name("git-status").unwrap()(porcelain).contains("main.rs"));
+    }
+
+    #[test]
+    fn test_stats_line() {
+        let m = Measurement {
+            input_tokens: 1200,
+            output_tokens: 300,
+        };
+        assert_eq!(
+            stats_line("cargo-test", &m),
+            "rtk replay cargo-test: 1200 -> 300 tokens (-75.0%)"
+        );
+    }
+
+    #[test]
+    fn test_filter_for_matches_plain_commands_only() {
+        assert!(filter_for("cargo test --all").is_some());
+        assert!(filter_for("git -C repo diff HEAD~1").is_some());
+        assert!(filter_for("ls -la src").is_some());
+        assert!(filter_for("python -m pytest tests/").is_some());
+        // rtk asks for a different format than the plain command prints
+        assert!(filter_for("git log -5").is_none());
+        assert!(filter_for("ls src").is_none());
+        assert!(filter_for("git branch -d old").is_none());
+        assert!(filter_for("make build").is_none());
+    }
+
+    #[test]
+    fn test_measure_cargo_build_output() {
+        let output = "   Compiling libc v0.2.150\n   Compiling serde v1.0.193\n   Compiling regex v1.10.2\n   Compiling rtk v0.1.0 (/src/rtk)\n    Finished dev [unoptimized + debuginfo] target(s) in 12.34s\n";
+        let m = measure("cargo build", output).unwrap();
+        assert_eq!(m.input_tokens, estimate_tokens(output));
+        assert!(m.output_tokens < m.input_tokens);
+        assert!(m.savings_pct() > 0.0);
+        assert!(measure("cargo build", "  \n").is_none());
+        assert!(measure("make", output).is_none());
+    }
+
+    #[test]
+    fn test_savings_pct() {
+        let m = Measurement {
+            input_tokens: 200,
+            output_tokens: 50,
+        };
+        assert_eq!(m.saved_tokens(), 150);
+        assert_eq!(m.savings_pct(), 75.0);
+        // Filters may add a header to tiny outputs
+        let grown = Measurement {
+            input_tokens: 5,
+            output_tokens: 8,
+        };
+        assert_eq!(grown.saved_tokens(), 0);
+        assert_eq!(grown.savings_pct(), 0.0);
+        let empty = Measurement {
+            input_tokens: 0,
+            output_tokens: 0,
+        };
+        assert_eq!(empty.savings_pct(), 0.0);
+    }
+}
@@ -0,0 +1,173 @@
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+
+use crate::discover::registry::rewrite_command;
+
+/// Outcome of a hook invocation, logged when RTK_HOOK_AUDIT=1.
+#[derive(Debug, PartialEq)]
+enum HookAction {
+    Rewrite(String),
+    AlreadyRtk,
+    Heredoc,
+    NoMatch,
+}
+
+impl HookAction {
+    fn label(&self) -> &'static str {
+        match self {
+            HookAction::Rewrite(_) => "rewrite",
+            HookAction::AlreadyRtk => "skip:already_rtk",
+            HookAction::Heredoc => "skip:heredoc",
+            HookAction::NoMatch => "skip:no_match",
+        }
+    }
+}
+
+/// Decide what the hook should do with a raw Bash command.
+fn decide(cmd: &str) -> HookAction {
+    if cmd.contains("<<") {
+        return HookAction::Heredoc;
+    }
+    if let Some(rewritten) = rewrite_command(cmd) {
+        return HookAction::Rewrite(rewritten);
+    }
+    let trimmed = cmd.trim_start();
+    if trimmed.starts_with("rtk ") || trimmed.contains("/rtk ") {
+        HookAction::AlreadyRtk
+    } else {
+        HookAction::NoMatch
+    }
+}
+
+/// Build the PreToolUse response, preserving every tool_input field but `command`.
+fn build_response(tool_input: &serde_json::Value, rewritten: &str) -> serde_json::Value {
+    let mut updated = tool_input.clone();
+    updated["command"] = serde_json::Value::String(rewritten.to_string());
+
+    serde_json::json!({
+        "hookSpecificOutput": {
+            "hookEventName": "PreToolUse",
+            "permissionDecision": "allow",
+            "permissionDecisionReason": "RTK auto-rewrite",
+            "updatedInput": updated
+        }
+    })
+}
+
+/// Append "timestamp | action | original_cmd | rewritten_cmd" to the audit log.
+fn append_audit_log(cmd: &str, action: &HookAction) {
+    let path = crate::hook_audit_cmd::default_log_path();
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+
+    let rewritten = match action {
+        HookAction::Rewrite(r) => r.as_str(),
+        _ => "-",
+    };
+    // One entry per line: flatten multi-line commands (heredocs)
+    let line = format!(
+        "{} | {} | {} | {}\n",
+        chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        action.label(),
+        cmd.replace('\n', " "),
+        rewritten.replace('\n', " ")
+    );
+
+    if let Ok(mut file) = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+    {
+        let _ = file.write_all(line.as_bytes());
+    }
+}
+
+/// Claude Code PreToolUse hook entry point: hook JSON on stdin, updatedInput JSON on stdout.
+///
+/// Never fails the tool call: malformed input or unknown commands produce no output,
+/// which lets the original command run unchanged.
+pub fn run() -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin()
+        .read_to_string(&mut input)
+        .context("Failed to read hook input from stdin")?;
+
+    let payload: serde_json::Value = match serde_json::from_str(&input) {
+        Ok(v) => v,
+        Err(_) => return Ok(()),
+    };
+
+    let tool_input = &payload["tool_input"];
+    let cmd = match tool_input.get("command").and_then(|c| c.as_str()) {
+        Some(c) if !c.trim().is_empty() => c,
+        _ => return Ok(()),
+    };
+
+    let action = decide(cmd);
+
+    if std::env::var("RTK_HOOK_AUDIT").ok().as_deref() == Some("1") {
+        append_audit_log(cmd, &action);
+    }
+
+    if let HookAction::Rewrite(rewritten) = &action {
+        println!("{}", build_response(tool_input, rewritten));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decide_rewrite() {
+        assert_eq!(
+            decide("git status"),
+            HookAction::Rewrite("rtk git status".to_string())
+        );
+    }
+
+    #[test]
+    fn test_decide_skip_reasons() {
+        assert_eq!(decide("rtk git status"), HookAction::AlreadyRtk);
+        assert_eq!(decide("/usr/local/bin/rtk ls"), HookAction::AlreadyRtk);
+        assert_eq!(decide("cat <<'EOF'\nhello\nEOF"), HookAction::Heredoc);
+        assert_eq!(decide("echo hello world"), HookAction::NoMatch);
+    }
+
+    #[test]
+    fn test_decide_partially_rtk_chain() {
+        // An rtk prefix on the first command must not hide later segments
+        assert_eq!(
+            decide("rtk cargo fmt && cargo test"),
+            HookAction::Rewrite("rtk cargo fmt && rtk cargo test".to_string())
+        );
+    }
+
+    #[test]
+    fn test_action_labels_match_hook_audit() {
+        assert_eq!(HookAction::Rewrite(String::new()).label(), "rewrite");
+        assert_eq!(HookAction::AlreadyRtk.label(), "skip:already_rtk");
+        assert_eq!(HookAction::Heredoc.label(), "skip:heredoc");
+        assert_eq!(HookAction::NoMatch.label(), "skip:no_match");
+    }
+
+    #[test]
+    fn test_build_response_preserves_other_fields() {
+        let tool_input = serde_json::json!({
+            "command": "git status",
+            "description": "Show status",
+            "timeout": 60000
+        });
+        let response = build_response(&tool_input, "rtk git status");
+        let output = &response["hookSpecificOutput"];
+
+        assert_eq!(output["hookEventName"], "PreToolUse");
+        assert_eq!(output["permissionDecision"], "allow");
+        assert_eq!(output["updatedInput"]["command"], "rtk git status");
+        assert_eq!(output["updatedInput"]["description"], "Show status");
+        assert_eq!(output["updatedInput"]["timeout"], 60000);
+    }
+}
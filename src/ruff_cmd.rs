@@ -1,3 +1,6 @@
+use crate::parser::{
+    truncate_output, LintIssue, LintResult, LintSeverity, OutputParser, ParseResult,
+};
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
@@ -111,6 +114,35 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
     Ok(())
 }
 
+/// Parser for `ruff check --output-format=json`
+pub struct RuffCheckParser;
+
+impl OutputParser for RuffCheckParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        if input.trim().is_empty() {
+            return ParseResult::Full(LintResult::from_issues(Vec::new()));
+        }
+        match serde_json::from_str::<Vec<RuffDiagnostic>>(input) {
+            Ok(diagnostics) => ParseResult::Full(LintResult::from_issues(
+                diagnostics
+                    .into_iter()
+                    .map(|d| LintIssue {
+                        file_path: d.filename,
+                        line: d.location.row,
+                        column: d.location.column,
+                        severity: LintSeverity::Error,
+                        rule_id: d.code,
+                        message: d.message,
+                    })
+                    .collect(),
+            )),
+            Err(_) => ParseResult::Passthrough(truncate_output(input, 500)),
+        }
+    }
+}
+
 /// Filter ruff check JSON output - group by rule and file
 pub fn filter_ruff_check_json(output: &str) -> String {
     let diagnostics: Result<Vec<RuffDiagnostic>, _> = serde_json::from_str(output);
@@ -320,6 +352,22 @@ fn compact_path(path: &str) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_ruff_check_parser() {
+        let json = r#"[{"code": "F401", "message": "`os` imported but unused",
+            "location": {"row": 1, "column": 8}, "end_location": {"row": 1, "column": 10},
+            "filename": "src/app.py", "fix": null}]"#;
+        let result = RuffCheckParser::parse(json);
+        assert_eq!(result.tier(), 1);
+        let lint = result.unwrap();
+        assert_eq!(lint.total_issues, 1);
+        assert_eq!(lint.issues[0].rule_id, "F401");
+        assert_eq!(lint.issues[0].line, 1);
+
+        assert_eq!(RuffCheckParser::parse("").unwrap().total_issues, 0);
+        assert_eq!(RuffCheckParser::parse("error: unknown rule").tier(), 3);
+    }
+
     #[test]
     fn test_filter_ruff_check_no_issues() {
         let output = "[]";
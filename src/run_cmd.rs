@@ -0,0 +1,65 @@
+use crate::config::Config;
+use crate::stream;
+use crate::tracking;
+use crate::user_filter;
+use anyhow::{Context, Result};
+use std::process::Command;
+
+/// Run an arbitrary command through the first matching `[[filters.custom]]` entry.
+/// Commands without a matching filter pass through unchanged (but are still tracked).
+pub fn run(args: &[String], verbose: u8) -> Result<()> {
+    if args.is_empty() {
+        anyhow::bail!("run requires a command to execute\nUsage: rtk run <command> [args...]");
+    }
+
+    let timer = tracking::TimedExecution::start();
+    let config = Config::load()?;
+    let filters = user_filter::load_filters(&config.filters.custom)?;
+
+    let command_line = args.join(" ");
+    let filter = user_filter::find_filter(&filters, &command_line);
+
+    if verbose > 0 {
+        match filter {
+            Some(f) => eprintln!("Running: {} (filter: {})", command_line, f.name),
+            None => eprintln!("Running: {} (no matching filter)", command_line),
+        }
+    }
+
+    let output = stream::capture(Command::new(&args[0]).args(&args[1..]))
+        .with_context(|| format!("Failed to execute command: {}", args[0]))?;
+
+    let raw = &output.raw;
+    let exit_code = output.exit_code;
+
+    let rtk_output = match filter {
+        Some(filter) => {
+            let filtered = output.annotate(&filter.apply(raw.trim_end()));
+            if let Some(hint) = crate::tee::tee_and_hint(raw, &filter.name, exit_code) {
+                println!("{}\n{}", filtered, hint);
+            } else {
+                println!("{}", filtered);
+            }
+            filtered
+        }
+        None => {
+            // No filter configured: behave like `rtk proxy`
+            output.passthrough();
+            raw.clone()
+        }
+    };
+
+    timer.track_with(
+        &command_line,
+        &format!("rtk run {}", command_line),
+        raw,
+        &rtk_output,
+        tracking::RunContext::exited(exit_code),
+    );
+
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+
+    Ok(())
+}
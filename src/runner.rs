@@ -1,7 +1,27 @@
+use crate::junit::{self, JunitParser};
+use crate::parser::{self, OutputParser};
+use crate::stream;
+use crate::test_diff;
 use crate::tracking;
 use anyhow::{Context, Result};
 use regex::Regex;
-use std::process::{Command, Stdio};
+use std::process::Command;
+use std::time::SystemTime;
+
+/// `sh -c <command>` (`cmd /C` on Windows)
+fn shell_command(command: &str) -> Command {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    cmd.arg(command);
+    cmd
+}
 
 /// Run a command and filter output to show only errors/warnings
 pub fn run_err(command: &str, verbose: u8) -> Result<()> {
@@ -11,35 +31,19 @@ pub fn run_err(command: &str, verbose: u8) -> Result<()> {
         eprintln!("Running: {}", command);
     }
 
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    } else {
-        Command::new("sh")
-            .args(["-c", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    }
-    .context("Failed to execute command")?;
+    let output =
+        stream::capture(&mut shell_command(command)).context("Failed to execute command")?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-    let filtered = filter_errors(&raw);
+    let raw = &output.raw;
+    let exit_code = output.exit_code;
+    let filtered = filter_errors(raw);
     let mut rtk = String::new();
 
     if filtered.is_empty() {
-        if output.status.success() {
+        if exit_code == 0 {
             rtk.push_str("✅ Command completed successfully (no errors)");
         } else {
-            rtk.push_str(&format!(
-                "❌ Command failed (exit code: {:?})\n",
-                output.status.code()
-            ));
+            rtk.push_str(&format!("❌ Command failed (exit code: {})\n", exit_code));
             let lines: Vec<&str> = raw.lines().collect();
             for line in lines.iter().rev().take(10).rev() {
                 rtk.push_str(&format!("  {}\n", line));
@@ -48,61 +52,127 @@ pub fn run_err(command: &str, verbose: u8) -> Result<()> {
     } else {
         rtk.push_str(&filtered);
     }
+    let rtk = output.annotate(&rtk);
 
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "err", exit_code) {
+    if let Some(hint) = crate::tee::tee_and_hint(raw, "err", exit_code) {
         println!("{}\n{}", rtk, hint);
     } else {
         println!("{}", rtk);
     }
-    timer.track(command, "rtk run-err", &raw, &rtk);
+    timer.track_with(
+        command,
+        "rtk run-err",
+        raw,
+        &rtk,
+        tracking::RunContext::exited(exit_code),
+    );
     Ok(())
 }
 
-/// Run tests and show only failures
-pub fn run_test(command: &str, verbose: u8) -> Result<()> {
+/// Run tests and show only failures. With `junit`, results come from the
+/// JUnit XML reports the command wrote (see [`crate::junit`]).
+pub fn run_test(command: &str, junit: Option<&str>, verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
+    let started = SystemTime::now();
 
     if verbose > 0 {
         eprintln!("Running tests: {}", command);
     }
 
-    let output = if cfg!(target_os = "windows") {
-        Command::new("cmd")
-            .args(["/C", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    } else {
-        Command::new("sh")
-            .args(["-c", command])
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-    }
-    .context("Failed to execute test command")?;
-
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-
-    let exit_code = output
-        .status
-        .code()
-        .unwrap_or(if output.status.success() { 0 } else { 1 });
-    let summary = extract_test_summary(&raw, command);
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "test", exit_code) {
+    let output =
+        stream::capture(&mut shell_command(command)).context("Failed to execute test command")?;
+
+    let raw = &output.raw;
+    let exit_code = output.exit_code;
+    let mut tier = None;
+    let summary = match junit {
+        Some(pattern) => {
+            let (text, report_tier) = junit_summary(command, pattern, started, raw, verbose)?;
+            tier = report_tier;
+            text
+        }
+        None => extract_test_summary(raw, command),
+    };
+    let summary = output.annotate(&summary);
+    if let Some(hint) = crate::tee::tee_and_hint(raw, "test", exit_code) {
         println!("{}\n{}", summary, hint);
     } else {
         println!("{}", summary);
     }
-    timer.track(command, "rtk run-test", &raw, &summary);
+    let context = tracking::RunContext::exited(exit_code);
+    timer.track_with(
+        command,
+        "rtk run-test",
+        raw,
+        &summary,
+        match tier {
+            Some(tier) => context.with_tier(tier),
+            None => context,
+        },
+    );
     Ok(())
 }
 
+/// Test results from the JUnit reports matching `pattern` written since
+/// `started`, or the console summary when the run wrote none.
+fn junit_summary(
+    command: &str,
+    pattern: &str,
+    started: SystemTime,
+    raw: &str,
+    verbose: u8,
+) -> Result<(String, Option<u8>)> {
+    let reports = junit::find_reports(pattern, started)?;
+    if verbose > 0 {
+        for report in &reports {
+            eprintln!("JUnit report: {}", report.display());
+        }
+    }
+
+    // An unreadable report is skipped with a warning; the rest still count
+    let mut xml = String::new();
+    let mut warnings = Vec::new();
+    for report in &reports {
+        match junit::read_report(report) {
+            Ok(text) => {
+                xml.push_str(&text);
+                xml.push('\n');
+            }
+            Err(e) => warnings.push(format!("⚠️  rtk: skipped JUnit report: {:#}", e)),
+        }
+    }
+    if xml.is_empty() {
+        if reports.is_empty() {
+            warnings.push(format!(
+                "⚠️  rtk: no JUnit report matching '{}' was written by this run",
+                pattern
+            ));
+        }
+        return Ok((
+            format!(
+                "{}\n{}",
+                extract_test_summary(raw, command),
+                warnings.join("\n")
+            ),
+            None,
+        ));
+    }
+
+    let parse_result = JunitParser::parse(&xml);
+    let tier = parse_result.tier();
+    let current = parse_result.data().cloned();
+    let diff = test_diff::record(&format!("test {}", command), current.as_ref());
+    let mut text = parser::render("test", parse_result, verbose);
+    if verbose == 0 && !parser::json_output() {
+        text = test_diff::compact("test", current.as_ref(), diff, text);
+    }
+    for warning in warnings {
+        text.push('\n');
+        text.push_str(&warning);
+    }
+    Ok((text, Some(tier)))
+}
+
 fn filter_errors(output: &str) -> String {
     lazy_static::lazy_static! {
         static ref ERROR_PATTERNS: Vec<Regex> = vec![
@@ -0,0 +1,581 @@
+//! Line-streaming execution for long-running commands.
+//!
+//! Most commands run the child with `.output()` and filter once it exits, so
+//! `rtk cargo test` stays silent until the end and follow modes
+//! (`rtk docker logs -f`) never print at all. [`run`] instead reads stdout and
+//! stderr as they arrive and hands each complete line to a [`StreamFilter`],
+//! which may print compact output immediately. When the child exits, the
+//! filter's [`StreamFilter::finish`] gets the complete raw output, so existing
+//! batch filters become the flush step.
+//!
+//! Every run also enforces the process-wide [`Limits`] (`[limits]` in config,
+//! `--timeout` / `--max-output` on the command line): a run that exceeds them is
+//! stopped (SIGTERM to its process group, SIGKILL after [`KILL_GRACE`]) and its
+//! output is annotated as cut off.
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long a timed-out command gets between SIGTERM and SIGKILL.
+pub const KILL_GRACE: Duration = Duration::from_secs(2);
+
+/// Exit code reported for timed-out commands (same as coreutils `timeout`).
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Wall-clock and output-size limits for wrapped commands.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    pub timeout: Option<Duration>,
+    pub max_output_bytes: Option<usize>,
+}
+
+impl Limits {
+    /// Command-line values win over `[limits]` in config.
+    pub fn resolve(timeout_secs: Option<u64>, max_output_bytes: Option<usize>) -> Self {
+        let config = crate::config::Config::load()
+            .map(|c| c.limits)
+            .unwrap_or_default();
+        Self {
+            timeout: timeout_secs
+                .or(config.timeout_secs)
+                .map(Duration::from_secs),
+            max_output_bytes: max_output_bytes.or(config.max_output_bytes),
+        }
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.timeout.is_some() || self.max_output_bytes.is_some()
+    }
+}
+
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+/// Install the limits for this process (called once from `main`).
+pub fn set_limits(limits: Limits) {
+    let _ = LIMITS.set(limits);
+}
+
+fn limits() -> Limits {
+    LIMITS.get().copied().unwrap_or_default()
+}
+
+/// Why a run was stopped early.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CutOff {
+    Timeout(Duration),
+    OutputCap(usize),
+}
+
+impl CutOff {
+    pub fn notice(&self) -> String {
+        match self {
+            CutOff::Timeout(t) => format!(
+                "⏱️  rtk: command killed after {}s timeout — output above is partial",
+                t.as_secs()
+            ),
+            CutOff::OutputCap(bytes) => format!(
+                "✂️  rtk: command stopped at {} bytes of output — output above is partial",
+                bytes
+            ),
+        }
+    }
+}
+
+/// Incremental filter fed one line at a time.
+pub trait StreamFilter {
+    /// Called for each output line (no trailing newline), in arrival order.
+    /// Returned lines are printed right away.
+    fn feed_line(&mut self, line: &str) -> Vec<String>;
+
+    /// Called once after the child exits, with the same raw text batch filters
+    /// get (`stdout`, newline, `stderr`). The result is the final output.
+    fn finish(&mut self, raw: &str) -> String;
+}
+
+/// Adapter for a plain batch filter: prints nothing until the child exits.
+pub struct Batch<F>(pub F);
+
+impl<F: Fn(&str) -> String> StreamFilter for Batch<F> {
+    fn feed_line(&mut self, _line: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn finish(&mut self, raw: &str) -> String {
+        (self.0)(raw)
+    }
+}
+
+/// What a streamed run produced.
+#[derive(Debug)]
+pub struct StreamOutput {
+    pub stdout: String,
+    pub stderr: String,
+    /// Exact bytes the child wrote, for passthrough (`stdout`/`stderr` are lossy UTF-8)
+    pub stdout_bytes: Vec<u8>,
+    pub stderr_bytes: Vec<u8>,
+    /// `stdout` + "\n" + `stderr`, as the batch filters expect
+    pub raw: String,
+    /// Lines already printed while the child was running (each ends with "\n")
+    pub live: String,
+    /// Result of [`StreamFilter::finish`], with the cut-off notice appended;
+    /// not printed, so callers can append tee hints
+    pub flushed: String,
+    pub exit_code: i32,
+    pub cut_off: Option<CutOff>,
+    /// The child died from a signal rtk did not send (OOM killer, abort)
+    pub signaled: bool,
+}
+
+impl StreamOutput {
+    /// Everything the user saw: live lines followed by the flushed output.
+    pub fn filtered(&self) -> String {
+        format!("{}{}", self.live, self.flushed)
+    }
+
+    /// Write the child's output to our stdout/stderr unchanged, followed by the
+    /// cut-off notice (if any) on stderr. For commands with no filter.
+    pub fn passthrough(&self) {
+        let mut stdout = std::io::stdout().lock();
+        let _ = stdout.write_all(&self.stdout_bytes);
+        let _ = stdout.flush();
+        let mut stderr = std::io::stderr().lock();
+        let _ = stderr.write_all(&self.stderr_bytes);
+        if let Some(cut) = self.cut_off {
+            let _ = writeln!(stderr, "{}", cut.notice());
+        }
+    }
+
+    /// Append the cut-off notice (if any) to output filtered by the caller
+    /// (printed to stderr instead under `--format json`).
+    pub fn annotate(&self, filtered: &str) -> String {
+        match self.cut_off {
+            Some(cut) if crate::parser::json_output() => {
+                eprintln!("{}", cut.notice());
+                filtered.to_string()
+            }
+            Some(cut) => format!("{}\n{}", filtered.trim_end(), cut.notice()),
+            None => filtered.to_string(),
+        }
+    }
+}
+
+/// Filter that prints nothing, for callers that filter the output themselves.
+struct Capture;
+
+impl StreamFilter for Capture {
+    fn feed_line(&mut self, _line: &str) -> Vec<String> {
+        Vec::new()
+    }
+
+    fn finish(&mut self, _raw: &str) -> String {
+        String::new()
+    }
+}
+
+/// Drop-in for `Command::output()` that enforces the configured [`Limits`].
+/// Use [`StreamOutput::annotate`] on the filtered result.
+pub fn capture(cmd: &mut Command) -> Result<StreamOutput> {
+    run(cmd, &mut Capture)
+}
+
+/// Upper bound for a single read from a child pipe, so the output cap holds
+/// even for output without newlines (minified JSON, `\r` progress bars).
+const READ_CHUNK: usize = 8 * 1024;
+
+enum Chunk {
+    Stdout(Vec<u8>),
+    Stderr(Vec<u8>),
+}
+
+/// Spawn `cmd`, streaming its output through `filter`, within the configured [`Limits`].
+pub fn run(cmd: &mut Command, filter: &mut dyn StreamFilter) -> Result<StreamOutput> {
+    run_with_limits(cmd, filter, limits())
+}
+
+fn run_with_limits(
+    cmd: &mut Command,
+    filter: &mut dyn StreamFilter,
+    limits: Limits,
+) -> Result<StreamOutput> {
+    // A separate process group lets us stop the whole tree (test runners fork
+    // workers). Only done when limits are set: it also detaches the child from
+    // terminal Ctrl-C.
+    #[cfg(unix)]
+    if limits.is_set() {
+        use std::os::unix::process::CommandExt;
+        cmd.process_group(0);
+    }
+
+    let mut child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .with_context(|| format!("Failed to execute command: {:?}", cmd.get_program()))?;
+
+    let (tx, rx) = mpsc::channel();
+    let stdout = child.stdout.take().context("Failed to capture stdout")?;
+    let stderr = child.stderr.take().context("Failed to capture stderr")?;
+    let stdout_reader = spawn_reader(stdout, tx.clone(), Chunk::Stdout);
+    let stderr_reader = spawn_reader(stderr, tx, Chunk::Stderr);
+
+    let mut out = Vec::new();
+    let mut err = Vec::new();
+    let mut out_line = Vec::new();
+    let mut err_line = Vec::new();
+    let mut live = String::new();
+    let mut captured = 0usize;
+    let mut cut_off = None;
+    let mut deadline = limits.timeout.map(|t| Instant::now() + t);
+
+    // Ends once both readers hit EOF and drop their senders
+    loop {
+        let chunk = match deadline {
+            Some(at) => match rx.recv_timeout(at.saturating_duration_since(Instant::now())) {
+                Ok(chunk) => chunk,
+                Err(RecvTimeoutError::Timeout) => {
+                    cut_off = limits.timeout.map(CutOff::Timeout);
+                    deadline = None;
+                    terminate(&mut child);
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            },
+            None => match rx.recv() {
+                Ok(chunk) => chunk,
+                Err(_) => break,
+            },
+        };
+        if cut_off.is_some() {
+            // Drain what the dying process still writes, without keeping it
+            continue;
+        }
+
+        let (buf, pending, mut bytes) = match chunk {
+            Chunk::Stdout(bytes) => (&mut out, &mut out_line, bytes),
+            Chunk::Stderr(bytes) => (&mut err, &mut err_line, bytes),
+        };
+        if let Some(max) = limits.max_output_bytes {
+            let room = max.saturating_sub(captured);
+            if bytes.len() > room {
+                bytes.truncate(room);
+                cut_off = Some(CutOff::OutputCap(max));
+            }
+        }
+        captured += bytes.len();
+        buf.extend_from_slice(&bytes);
+        pending.extend_from_slice(&bytes);
+        emit(filter, take_lines(pending), &mut live);
+
+        if cut_off.is_some() {
+            deadline = None;
+            terminate(&mut child);
+        }
+    }
+
+    // Trailing text without a final newline is still a line for the filter
+    for pending in [&mut out_line, &mut err_line] {
+        if !pending.is_empty() {
+            pending.push(b'\n');
+            emit(filter, take_lines(pending), &mut live);
+        }
+    }
+
+    let _ = stdout_reader.join();
+    let _ = stderr_reader.join();
+    let status = child.wait().context("Failed to wait for command")?;
+    let mut exit_code = status
+        .code()
+        .unwrap_or(if status.success() { 0 } else { 1 });
+    let signaled = status.code().is_none() && cut_off.is_none();
+    match cut_off {
+        Some(CutOff::Timeout(_)) => exit_code = TIMEOUT_EXIT_CODE,
+        Some(CutOff::OutputCap(_)) if exit_code == 0 => exit_code = 1,
+        _ => {}
+    }
+
+    let stdout_bytes = out;
+    let stderr_bytes = err;
+    let out = String::from_utf8_lossy(&stdout_bytes).into_owned();
+    let err = String::from_utf8_lossy(&stderr_bytes).into_owned();
+    let raw = format!("{}\n{}", out, err);
+    let mut flushed = filter.finish(&raw);
+    if let Some(cut) = cut_off {
+        flushed = format!("{}\n{}", flushed.trim_end(), cut.notice())
+            .trim_start()
+            .to_string();
+    }
+
+    Ok(StreamOutput {
+        stdout: out,
+        stderr: err,
+        stdout_bytes,
+        stderr_bytes,
+        raw,
+        live,
+        flushed,
+        exit_code,
+        cut_off,
+        signaled,
+    })
+}
+
+/// SIGTERM the child's process group, then SIGKILL it after [`KILL_GRACE`].
+#[cfg(unix)]
+fn terminate(child: &mut Child) {
+    let pgid = child.id() as libc::pid_t;
+    // SAFETY: plain kill(2) on the process group we created at spawn
+    unsafe {
+        libc::kill(-pgid, libc::SIGTERM);
+    }
+    let give_up = Instant::now() + KILL_GRACE;
+    while Instant::now() < give_up {
+        if let Ok(Some(_)) = child.try_wait() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+    // Also reaps group members that outlived the leader and still hold our pipes
+    unsafe {
+        libc::kill(-pgid, libc::SIGKILL);
+    }
+}
+
+#[cfg(not(unix))]
+fn terminate(child: &mut Child) {
+    let _ = child.kill();
+}
+
+/// Split the complete lines off the front of `pending` (lossy UTF-8, line
+/// endings stripped), leaving any unterminated tail in place.
+fn take_lines(pending: &mut Vec<u8>) -> Vec<String> {
+    let Some(end) = pending.iter().rposition(|&b| b == b'\n') else {
+        return Vec::new();
+    };
+    let tail = pending.split_off(end + 1);
+    let done = std::mem::replace(pending, tail);
+    String::from_utf8_lossy(&done[..end])
+        .split('\n')
+        .map(|line| line.trim_end_matches('\r').to_string())
+        .collect()
+}
+
+/// Feed `lines` to the filter and print whatever it returns right away.
+fn emit(filter: &mut dyn StreamFilter, lines: Vec<String>, live: &mut String) {
+    let mut emitted = Vec::new();
+    for line in lines {
+        emitted.extend(filter.feed_line(&line));
+    }
+    if emitted.is_empty() {
+        return;
+    }
+    let mut console = std::io::stdout().lock();
+    for l in emitted {
+        let _ = writeln!(console, "{}", l);
+        live.push_str(&l);
+        live.push('\n');
+    }
+    let _ = console.flush();
+}
+
+/// Forward raw output from `source` into the channel, at most [`READ_CHUNK`]
+/// bytes at a time.
+fn spawn_reader<R: Read + Send + 'static>(
+    mut source: R,
+    tx: mpsc::Sender<Chunk>,
+    wrap: fn(Vec<u8>) -> Chunk,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let mut buf = [0u8; READ_CHUNK];
+        loop {
+            match source.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    if tx.send(wrap(buf[..n].to_vec())).is_err() {
+                        break;
+                    }
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            }
+        }
+    })
+}
+
+/// Run `filter` over already captured output, as [`run`] would have while
+/// the command printed it: live lines followed by the flushed result.
+pub fn replay(filter: &mut dyn StreamFilter, raw: &str) -> String {
+    let mut live = String::new();
+    for line in raw.lines() {
+        for l in filter.feed_line(line) {
+            live.push_str(&l);
+            live.push('\n');
+        }
+    }
+    format!("{}{}", live, filter.finish(raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Echoes lines containing "!" live, flushes a line count.
+    struct Shout {
+        seen: usize,
+    }
+
+    impl StreamFilter for Shout {
+        fn feed_line(&mut self, line: &str) -> Vec<String> {
+            self.seen += 1;
+            if line.contains('!') {
+                vec![format!("live: {}", line)]
+            } else {
+                Vec::new()
+            }
+        }
+
+        fn finish(&mut self, _raw: &str) -> String {
+            format!("{} lines", self.seen)
+        }
+    }
+
+    #[test]
+    fn test_run_streams_and_flushes() {
+        let mut filter = Shout { seen: 0 };
+        let out = run(
+            Command::new("sh").args(["-c", "echo a; echo 'b!'; echo 'err!' >&2; exit 3"]),
+            &mut filter,
+        )
+        .unwrap();
+
+        assert_eq!(out.exit_code, 3);
+        assert_eq!(out.raw, "a\nb!\n\nerr!\n");
+        assert!(out.live.contains("live: b!\n"));
+        assert!(out.live.contains("live: err!\n"));
+        assert_eq!(out.flushed, "3 lines");
+        assert!(out.filtered().ends_with("3 lines"));
+    }
+
+    #[test]
+    fn test_batch_adapter_only_flushes() {
+        let mut filter = Batch(|raw: &str| raw.lines().count().to_string());
+        let out = run(
+            Command::new("sh").args(["-c", "echo a; echo b"]),
+            &mut filter,
+        )
+        .unwrap();
+
+        assert_eq!(out.exit_code, 0);
+        assert!(out.live.is_empty());
+        assert_eq!(out.flushed, "3");
+        assert_eq!(out.filtered(), "3");
+    }
+
+    #[test]
+    fn test_replay_matches_live_run() {
+        let mut filter = Shout { seen: 0 };
+        assert_eq!(replay(&mut filter, "a\nb!\nc"), "live: b!\n3 lines");
+    }
+
+    #[test]
+    fn test_timeout_kills_process_group() {
+        let limits = Limits {
+            timeout: Some(Duration::from_millis(300)),
+            max_output_bytes: None,
+        };
+        let started = Instant::now();
+        // The background sleep keeps stdout open: only a group kill ends the run
+        let out = run_with_limits(
+            Command::new("sh").args(["-c", "echo before; sleep 30 & sleep 30"]),
+            &mut Batch(|raw: &str| raw.trim().to_string()),
+            limits,
+        )
+        .unwrap();
+
+        assert!(started.elapsed() < Duration::from_secs(10));
+        assert_eq!(out.exit_code, TIMEOUT_EXIT_CODE);
+        assert!(matches!(out.cut_off, Some(CutOff::Timeout(_))));
+        assert!(out.flushed.starts_with("before\n"));
+        assert!(out.flushed.contains("timeout"));
+        assert!(out.raw.contains("before"));
+    }
+
+    #[test]
+    fn test_output_cap_stops_command() {
+        let limits = Limits {
+            timeout: None,
+            max_output_bytes: Some(100),
+        };
+        let out = run_with_limits(
+            Command::new("sh").args(["-c", "while true; do echo 0123456789; done"]),
+            &mut Capture,
+            limits,
+        )
+        .unwrap();
+
+        assert_eq!(out.cut_off, Some(CutOff::OutputCap(100)));
+        assert!(out.stdout.len() <= 100);
+        assert_ne!(out.exit_code, 0);
+        assert_eq!(
+            out.annotate("filtered"),
+            format!("filtered\n{}", CutOff::OutputCap(100).notice())
+        );
+    }
+
+    #[test]
+    fn test_output_cap_without_newlines() {
+        let limits = Limits {
+            timeout: None,
+            max_output_bytes: Some(100),
+        };
+        let out = run_with_limits(
+            Command::new("sh").args(["-c", "while true; do printf '0123456789\\r'; done"]),
+            &mut Capture,
+            limits,
+        )
+        .unwrap();
+
+        assert_eq!(out.cut_off, Some(CutOff::OutputCap(100)));
+        assert_eq!(out.stdout.len(), 100);
+    }
+
+    #[test]
+    fn test_unterminated_last_line_reaches_filter() {
+        let mut filter = Shout { seen: 0 };
+        let out = run(
+            Command::new("sh").args(["-c", "echo a; printf 'tail!'"]),
+            &mut filter,
+        )
+        .unwrap();
+
+        assert_eq!(out.stdout, "a\ntail!");
+        assert_eq!(out.live, "live: tail!\n");
+        assert_eq!(out.flushed, "2 lines");
+    }
+
+    #[test]
+    fn test_capture_keeps_exact_bytes() {
+        let out = capture(Command::new("sh").args(["-c", "printf 'a\\r\\nb\\377'"])).unwrap();
+        assert_eq!(out.stdout_bytes, b"a\r\nb\xff");
+        assert_eq!(out.stdout, "a\r\nb\u{fffd}");
+    }
+
+    #[test]
+    fn test_no_limits_no_annotation() {
+        let out = capture(Command::new("sh").args(["-c", "echo hi"])).unwrap();
+        assert_eq!(out.stdout, "hi\n");
+        assert_eq!(out.cut_off, None);
+        assert_eq!(out.annotate("hi"), "hi");
+    }
+
+    #[test]
+    fn test_run_missing_binary() {
+        let mut filter = Batch(|raw: &str| raw.to_string());
+        assert!(run(&mut Command::new("rtk-no-such-binary"), &mut filter).is_err());
+    }
+}
@@ -1,5 +1,11 @@
 use crate::config::Config;
-use std::path::PathBuf;
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
 
 /// Minimum output size to tee (smaller outputs don't need recovery)
 const MIN_TEE_SIZE: usize = 500;
@@ -10,6 +16,10 @@ const DEFAULT_MAX_FILES: usize = 20;
 /// Default max file size (1MB)
 const DEFAULT_MAX_FILE_SIZE: usize = 1_048_576;
 
+/// Suffixes of plain and gzip-compressed tee files
+const LOG_SUFFIX: &str = ".log";
+const GZ_SUFFIX: &str = ".log.gz";
+
 /// Sanitize a command slug for use in filenames.
 /// Replaces non-alphanumeric chars (except underscore/hyphen) with underscore,
 /// truncates at 40 chars.
@@ -47,26 +57,84 @@ fn get_tee_dir(config: &Config) -> Option<PathBuf> {
     dirs::data_local_dir().map(|d| d.join("rtk").join("tee"))
 }
 
-/// Rotate old tee files: keep only the last `max_files`, delete oldest.
-fn cleanup_old_files(dir: &std::path::Path, max_files: usize) {
-    let mut entries: Vec<_> = std::fs::read_dir(dir)
+/// Tee id (`<epoch>_<slug>`) of a `.log` or `.log.gz` file, None for anything else.
+fn tee_id(path: &Path) -> Option<String> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_suffix(GZ_SUFFIX)
+        .or_else(|| name.strip_suffix(LOG_SUFFIX))
+        .map(str::to_string)
+}
+
+/// Index record path for a tee file: `<id>.json` next to it.
+fn index_path(log: &Path) -> Option<PathBuf> {
+    Some(log.with_file_name(format!("{}.json", tee_id(log)?)))
+}
+
+/// Epoch seconds a tee file was written, from its name (mtime for odd names).
+fn written_at(path: &Path) -> Option<u64> {
+    let from_name = tee_id(path).and_then(|id| id.split('_').next()?.parse().ok());
+    from_name.or_else(|| {
+        std::fs::metadata(path)
+            .ok()?
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs())
+    })
+}
+
+fn remove_tee_file(path: &Path) {
+    let _ = std::fs::remove_file(path);
+    if let Some(index) = index_path(path) {
+        let _ = std::fs::remove_file(index);
+    }
+}
+
+/// Apply retention to the tee directory, oldest files first:
+/// drop files older than `max_age_days`, keep at most `max_files`, then shrink
+/// the directory under `max_total_size`. The newest file is always kept.
+fn cleanup_old_files(dir: &Path, config: &TeeConfig) {
+    let mut files: Vec<(PathBuf, u64)> = std::fs::read_dir(dir)
         .ok()
         .into_iter()
         .flatten()
         .filter_map(|e| e.ok())
-        .filter(|e| e.path().extension().is_some_and(|ext| ext == "log"))
+        .map(|e| e.path())
+        .filter(|p| tee_id(p).is_some())
+        .map(|p| {
+            let size = std::fs::metadata(&p).map(|m| m.len()).unwrap_or(0);
+            (p, size)
+        })
         .collect();
 
-    if entries.len() <= max_files {
-        return;
-    }
-
     // Sort by filename (which starts with epoch timestamp = chronological)
-    entries.sort_by_key(|e| e.file_name());
+    files.sort_by(|a, b| a.0.file_name().cmp(&b.0.file_name()));
 
-    let to_remove = entries.len() - max_files;
-    for entry in entries.iter().take(to_remove) {
-        let _ = std::fs::remove_file(entry.path());
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let max_age = config.max_age_days.map(|days| days * 86_400);
+    let mut total: u64 = files.iter().map(|(_, size)| size).sum();
+    let mut remaining = files.len();
+
+    for (path, size) in &files {
+        if remaining <= 1 {
+            break;
+        }
+        let expired = match (max_age, written_at(path)) {
+            (Some(max_age), Some(at)) => now.saturating_sub(at) > max_age,
+            _ => false,
+        };
+        let over_count = remaining > config.max_files;
+        let over_budget = config.max_total_size.is_some_and(|max| total > max);
+        if !(expired || over_count || over_budget) {
+            break;
+        }
+        remove_tee_file(path);
+        total -= size;
+        remaining -= 1;
     }
 }
 
@@ -99,15 +167,54 @@ fn should_tee(
     tee_dir
 }
 
-/// Write raw output to a tee file in the given directory.
-/// Returns file path on success.
+/// Cut `raw` to at most `max` bytes (on a char boundary) and mark the cut.
+fn truncate_raw(raw: &str, max: usize) -> String {
+    let mut end = max.min(raw.len());
+    while !raw.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}\n\n--- truncated at {} bytes ---", &raw[..end], end)
+}
+
+fn gzip(data: &[u8]) -> Option<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data).ok()?;
+    encoder.finish().ok()
+}
+
+/// Gzip `raw` into at most `max` bytes. Only when the whole output does not
+/// fit is a prefix kept, sized from the observed compression ratio.
+/// Returns the compressed bytes and whether the output was truncated.
+fn compress_within(raw: &str, max: usize) -> Option<(Vec<u8>, bool)> {
+    let full = gzip(raw.as_bytes())?;
+    if full.len() <= max {
+        return Some((full, false));
+    }
+
+    let mut keep = (raw.len() as u128 * max as u128 / full.len() as u128) as usize;
+    loop {
+        let data = gzip(truncate_raw(raw, keep).as_bytes())?;
+        if data.len() <= max || keep == 0 {
+            return Some((data, true));
+        }
+        keep = keep * 9 / 10;
+    }
+}
+
+/// A tee file just written by `write_tee_file`.
+struct TeeFile {
+    path: PathBuf,
+    truncated: bool,
+}
+
+/// Write raw output to a tee file in the given directory, then apply retention.
+/// With `compress`, the file is gzipped and `max_file_size` caps the compressed size.
 fn write_tee_file(
     raw: &str,
     command_slug: &str,
-    tee_dir: &std::path::Path,
-    max_file_size: usize,
-    max_files: usize,
-) -> Option<PathBuf> {
+    tee_dir: &Path,
+    config: &TeeConfig,
+) -> Option<TeeFile> {
     std::fs::create_dir_all(tee_dir).ok()?;
 
     let slug = sanitize_slug(command_slug);
@@ -115,26 +222,31 @@ fn write_tee_file(
         .duration_since(std::time::UNIX_EPOCH)
         .ok()?
         .as_secs();
-    let filename = format!("{}_{}.log", epoch, slug);
-    let filepath = tee_dir.join(filename);
 
-    // Truncate at max_file_size
-    let content = if raw.len() > max_file_size {
-        format!(
-            "{}\n\n--- truncated at {} bytes ---",
-            &raw[..max_file_size],
-            max_file_size
+    let (filename, content, truncated) = if config.compress {
+        let (data, truncated) = compress_within(raw, config.max_file_size)?;
+        (format!("{}_{}{}", epoch, slug, GZ_SUFFIX), data, truncated)
+    } else if raw.len() > config.max_file_size {
+        let content = truncate_raw(raw, config.max_file_size);
+        (
+            format!("{}_{}{}", epoch, slug, LOG_SUFFIX),
+            content.into_bytes(),
+            true,
         )
     } else {
-        raw.to_string()
+        let content = raw.as_bytes().to_vec();
+        (format!("{}_{}{}", epoch, slug, LOG_SUFFIX), content, false)
     };
+    let filepath = tee_dir.join(filename);
 
     std::fs::write(&filepath, content).ok()?;
 
-    // Rotate old files
-    cleanup_old_files(tee_dir, max_files);
+    cleanup_old_files(tee_dir, config);
 
-    Some(filepath)
+    Some(TeeFile {
+        path: filepath,
+        truncated,
+    })
 }
 
 /// Write raw output to tee file if conditions are met.
@@ -150,17 +262,167 @@ pub fn tee_raw(raw: &str, command_slug: &str, exit_code: i32) -> Option<PathBuf>
 
     let tee_dir = should_tee(&config.tee, raw.len(), exit_code, Some(tee_dir))?;
 
-    write_tee_file(
-        raw,
-        command_slug,
-        &tee_dir,
-        config.tee.max_file_size,
-        config.tee.max_files,
-    )
+    let file = write_tee_file(raw, command_slug, &tee_dir, &config.tee)?;
+    write_index_entry(&file, command_slug, raw.len(), exit_code);
+    Some(file.path)
+}
+
+/// Read a tee file back, decompressing `.log.gz` files transparently.
+pub fn read_tee_file(path: &Path) -> Result<String> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !path.to_string_lossy().ends_with(GZ_SUFFIX) {
+        return Ok(String::from_utf8_lossy(&bytes).into_owned());
+    }
+
+    let mut raw = Vec::new();
+    GzDecoder::new(bytes.as_slice())
+        .read_to_end(&mut raw)
+        .with_context(|| format!("Failed to decompress {}", path.display()))?;
+    Ok(String::from_utf8_lossy(&raw).into_owned())
+}
+
+/// Index record stored next to each tee file as `<id>.json`, read by `rtk tee`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TeeEntry {
+    /// File name without `.log`/`.log.gz`: `<epoch>_<slug>`
+    pub id: String,
+    pub slug: String,
+    /// rtk invocation that produced the output (unknown for files from older rtk)
+    pub command: Option<String>,
+    pub exit_code: Option<i32>,
+    pub cwd: Option<String>,
+    /// Raw output size in bytes, before compression and `max_file_size` truncation
+    pub size: usize,
+    pub truncated: bool,
+    /// RFC 3339 timestamp
+    pub timestamp: String,
+    #[serde(skip)]
+    pub path: PathBuf,
+}
+
+impl TeeEntry {
+    /// Best-effort entry for a log without an index record: everything but the
+    /// command can be recovered from the file name and size.
+    fn from_log_file(path: &Path) -> Option<Self> {
+        let id = tee_id(path)?;
+        let (epoch, slug) = id.split_once('_')?;
+        let timestamp = chrono::DateTime::from_timestamp(epoch.parse().ok()?, 0)?.to_rfc3339();
+        let size = std::fs::metadata(path).ok()?.len() as usize;
+        Some(Self {
+            slug: slug.to_string(),
+            id,
+            command: None,
+            exit_code: None,
+            cwd: None,
+            size,
+            truncated: false,
+            timestamp,
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+/// The current rtk invocation, as it would be re-run ("rtk cargo test --lib").
+fn current_command() -> String {
+    std::iter::once("rtk".to_string())
+        .chain(std::env::args().skip(1).map(|arg| shell_quote(&arg)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Single-quote `arg` for a POSIX shell unless it is made only of safe characters.
+fn shell_quote(arg: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c);
+    if !arg.is_empty() && arg.chars().all(safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+fn write_index_entry(file: &TeeFile, slug: &str, raw_len: usize, exit_code: i32) {
+    let (id, index) = match (tee_id(&file.path), index_path(&file.path)) {
+        (Some(id), Some(index)) => (id, index),
+        _ => return,
+    };
+    let entry = TeeEntry {
+        id,
+        slug: sanitize_slug(slug),
+        command: Some(current_command()),
+        exit_code: Some(exit_code),
+        cwd: std::env::current_dir()
+            .ok()
+            .map(|p| p.to_string_lossy().into_owned()),
+        size: raw_len,
+        truncated: file.truncated,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        path: file.path.clone(),
+    };
+    if let Ok(json) = serde_json::to_string_pretty(&entry) {
+        let _ = std::fs::write(index, json);
+    }
+}
+
+/// All tee files in `dir`, newest first.
+pub fn entries_in(dir: &Path) -> Vec<TeeEntry> {
+    let mut entries: Vec<TeeEntry> = std::fs::read_dir(dir)
+        .ok()
+        .into_iter()
+        .flatten()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| tee_id(p).is_some())
+        .filter_map(|log| {
+            let indexed = index_path(&log)
+                .and_then(|index| std::fs::read_to_string(index).ok())
+                .and_then(|json| serde_json::from_str::<TeeEntry>(&json).ok());
+            match indexed {
+                Some(mut entry) => {
+                    entry.path = log;
+                    Some(entry)
+                }
+                None => TeeEntry::from_log_file(&log),
+            }
+        })
+        .collect();
+    entries.sort_by(|a, b| b.id.cmp(&a.id));
+    entries
+}
+
+/// Tee files in the configured directory, newest first.
+pub fn list_entries() -> Result<Vec<TeeEntry>> {
+    let config = Config::load()?;
+    let dir = get_tee_dir(&config).context("Cannot determine tee directory")?;
+    Ok(entries_in(&dir))
+}
+
+/// Look up an entry by id, file name, or position in `rtk tee list` (1 = newest).
+pub fn find_entry<'a>(entries: &'a [TeeEntry], id: &str) -> Result<&'a TeeEntry> {
+    let id = id
+        .strip_suffix(GZ_SUFFIX)
+        .or_else(|| id.strip_suffix(LOG_SUFFIX))
+        .unwrap_or(id);
+    if let Some(entry) = entries.iter().find(|e| e.id == id) {
+        return Ok(entry);
+    }
+    if let Ok(n) = id.parse::<usize>() {
+        if n >= 1 && n <= entries.len() {
+            return Ok(&entries[n - 1]);
+        }
+    }
+    anyhow::bail!("No tee file '{}' (see `rtk tee list`)", id)
 }
 
 /// Format the hint line with ~ shorthand for home directory.
+/// Compressed files can't be read directly, so their hint points at `rtk tee show`.
 fn format_hint(path: &std::path::Path) -> String {
+    if path.to_string_lossy().ends_with(GZ_SUFFIX) {
+        if let Some(id) = tee_id(path) {
+            return format!("[full output: rtk tee show {}]", id);
+        }
+    }
+
     let display = if let Some(home) = dirs::home_dir() {
         if let Ok(relative) = path.strip_prefix(&home) {
             format!("~/{}", relative.display())
@@ -176,11 +438,68 @@ fn format_hint(path: &std::path::Path) -> String {
 
 /// Convenience: tee + format hint in one call.
 /// Returns hint string if file was written, None if skipped.
+/// Under `--format json` the hint goes to stderr so stdout stays valid JSON.
 pub fn tee_and_hint(raw: &str, command_slug: &str, exit_code: i32) -> Option<String> {
     let path = tee_raw(raw, command_slug, exit_code)?;
+    if crate::parser::json_output() {
+        eprintln!("{}", format_hint(&path));
+        return None;
+    }
     Some(format_hint(&path))
 }
 
+/// Stands in for the tee id in section references until the file is written.
+const SECTION_ID_PLACEHOLDER: &str = "{tee}";
+
+/// Drill-down reference for a section a filter collapsed, appended to its line:
+/// ` → rtk tee show <id> --section <key>`. Keys are `<kind>:<name>`, resolved by
+/// `rtk tee show --section` (see `tee_cmd::extract_section`).
+pub fn section_ref(key: &str) -> String {
+    format!(
+        " → rtk tee show {} --section {}",
+        SECTION_ID_PLACEHOLDER, key
+    )
+}
+
+/// Fill in section references with the tee id, or drop them when nothing was teed.
+fn link_sections(filtered: &str, id: Option<&str>) -> String {
+    if !filtered.contains(SECTION_ID_PLACEHOLDER) {
+        return filtered.to_string();
+    }
+    match id {
+        Some(id) => filtered.replace(SECTION_ID_PLACEHOLDER, id),
+        None => {
+            let marker = section_ref("");
+            let marker = marker.trim_end_matches(" --section ");
+            filtered
+                .lines()
+                .map(|line| match line.find(marker) {
+                    Some(pos) => &line[..pos],
+                    None => line,
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    }
+}
+
+/// Tee + resolve the filter's section references + hint, for filters that emit
+/// [`section_ref`]s. Returns the text to print (hint on stderr under `--format json`).
+pub fn tee_and_link(raw: &str, command_slug: &str, exit_code: i32, filtered: &str) -> String {
+    match tee_raw(raw, command_slug, exit_code) {
+        Some(path) if crate::parser::json_output() => {
+            eprintln!("{}", format_hint(&path));
+            filtered.to_string()
+        }
+        Some(path) => format!(
+            "{}\n{}",
+            link_sections(filtered, tee_id(&path).as_deref()),
+            format_hint(&path)
+        ),
+        None => link_sections(filtered, None),
+    }
+}
+
 /// TeeMode controls when tee writes files.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -202,7 +521,17 @@ pub struct TeeConfig {
     pub enabled: bool,
     pub mode: TeeMode,
     pub max_files: usize,
+    /// Per-file cap in bytes (of the compressed file when `compress` is set)
     pub max_file_size: usize,
+    /// Store files gzip-compressed (`.log.gz`)
+    #[serde(default)]
+    pub compress: bool,
+    /// Delete files older than this many days
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<u64>,
+    /// Total size budget for the tee directory, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_total_size: Option<u64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub directory: Option<PathBuf>,
 }
@@ -214,6 +543,9 @@ impl Default for TeeConfig {
             mode: TeeMode::default(),
             max_files: DEFAULT_MAX_FILES,
             max_file_size: DEFAULT_MAX_FILE_SIZE,
+            compress: false,
+            max_age_days: None,
+            max_total_size: None,
             directory: None,
         }
     }
@@ -291,16 +623,10 @@ mod tests {
     fn test_write_tee_file_creates_file() {
         let tmpdir = tempfile::tempdir().unwrap();
         let content = "error: test failed\n".repeat(50);
-        let result = write_tee_file(
-            &content,
-            "cargo_test",
-            tmpdir.path(),
-            DEFAULT_MAX_FILE_SIZE,
-            20,
-        );
+        let result = write_tee_file(&content, "cargo_test", tmpdir.path(), &TeeConfig::default());
         assert!(result.is_some());
 
-        let path = result.unwrap();
+        let path = result.unwrap().path;
         assert!(path.exists());
         let written = fs::read_to_string(&path).unwrap();
         assert!(written.contains("error: test failed"));
@@ -311,15 +637,142 @@ mod tests {
         let tmpdir = tempfile::tempdir().unwrap();
         let big_output = "x".repeat(2000);
         // Set max_file_size to 1000 bytes
-        let result = write_tee_file(&big_output, "test", tmpdir.path(), 1000, 20);
+        let config = TeeConfig {
+            max_file_size: 1000,
+            ..TeeConfig::default()
+        };
+        let result = write_tee_file(&big_output, "test", tmpdir.path(), &config);
         assert!(result.is_some());
 
-        let path = result.unwrap();
+        let path = result.unwrap().path;
         let content = fs::read_to_string(&path).unwrap();
         assert!(content.contains("--- truncated at 1000 bytes ---"));
         assert!(content.len() < 2000);
     }
 
+    #[test]
+    fn test_write_tee_file_truncation_char_boundary() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        // 'é' is 2 bytes: byte 1001 falls inside a char
+        let big_output = format!("x{}", "é".repeat(1000));
+        let config = TeeConfig {
+            max_file_size: 1000,
+            ..TeeConfig::default()
+        };
+        let file = write_tee_file(&big_output, "test", tmpdir.path(), &config).unwrap();
+        assert!(file.truncated);
+        let content = fs::read_to_string(&file.path).unwrap();
+        assert!(content.contains("--- truncated at 999 bytes ---"));
+    }
+
+    #[test]
+    fn test_write_tee_file_compressed_round_trip() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let content = "error[E0308]: mismatched types\n".repeat(10_000);
+        let config = TeeConfig {
+            compress: true,
+            max_file_size: 100_000,
+            ..TeeConfig::default()
+        };
+        let file = write_tee_file(&content, "cargo_build", tmpdir.path(), &config).unwrap();
+        assert!(file.path.to_string_lossy().ends_with(".log.gz"));
+        assert!(!file.truncated);
+        // Well beyond max_file_size raw, but stored whole
+        assert!(content.len() > config.max_file_size);
+        assert!(fs::metadata(&file.path).unwrap().len() < 100_000);
+        assert_eq!(read_tee_file(&file.path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_write_tee_file_compressed_cap() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        // Pseudo-random lines compress poorly
+        let content: String = (0..20_000u64)
+            .map(|i| format!("{:x}\n", i.wrapping_mul(0x9E37_79B9_7F4A_7C15)))
+            .collect();
+        let config = TeeConfig {
+            compress: true,
+            max_file_size: 10_000,
+            ..TeeConfig::default()
+        };
+        let file = write_tee_file(&content, "test", tmpdir.path(), &config).unwrap();
+        assert!(file.truncated);
+        assert!(fs::metadata(&file.path).unwrap().len() <= 10_000);
+        let read = read_tee_file(&file.path).unwrap();
+        assert!(read.contains("--- truncated at "));
+        assert!(content.starts_with(read.split("\n\n--- truncated").next().unwrap()));
+    }
+
+    #[test]
+    fn test_cleanup_by_age() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let old = format!("{}_test.log.gz", now - 8 * 86_400);
+        let recent = format!("{}_test.log", now - 86_400);
+        let newest = format!("{}_test.log", now);
+        for name in [&old, &recent, &newest] {
+            fs::write(dir.join(name), "content").unwrap();
+        }
+        fs::write(dir.join(format!("{}_test.json", now - 8 * 86_400)), "{}").unwrap();
+
+        let config = TeeConfig {
+            max_age_days: Some(7),
+            ..TeeConfig::default()
+        };
+        cleanup_old_files(dir, &config);
+
+        assert!(!dir.join(&old).exists());
+        assert!(!dir.join(format!("{}_test.json", now - 8 * 86_400)).exists());
+        assert!(dir.join(&recent).exists());
+        assert!(dir.join(&newest).exists());
+    }
+
+    #[test]
+    fn test_cleanup_by_total_size() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        for i in 0..5 {
+            fs::write(
+                dir.join(format!("{}_test.log", 1000000 + i)),
+                "x".repeat(100),
+            )
+            .unwrap();
+        }
+
+        let config = TeeConfig {
+            max_total_size: Some(250),
+            ..TeeConfig::default()
+        };
+        cleanup_old_files(dir, &config);
+
+        let remaining = entries_in(dir);
+        assert_eq!(remaining.len(), 2);
+        assert_eq!(remaining[0].id, "1000004_test");
+        assert_eq!(remaining[1].id, "1000003_test");
+    }
+
+    #[test]
+    fn test_cleanup_keeps_newest_file() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        fs::write(dir.join("1000000_test.log"), "x".repeat(100)).unwrap();
+        fs::write(dir.join("1000001_test.log"), "x".repeat(500)).unwrap();
+
+        let config = TeeConfig {
+            max_age_days: Some(1),
+            max_total_size: Some(10),
+            ..TeeConfig::default()
+        };
+        cleanup_old_files(dir, &config);
+
+        assert!(!dir.join("1000000_test.log").exists());
+        assert!(dir.join("1000001_test.log").exists());
+    }
+
     #[test]
     fn test_cleanup_old_files() {
         let tmpdir = tempfile::tempdir().unwrap();
@@ -331,7 +784,7 @@ mod tests {
             fs::write(dir.join(&filename), "content").unwrap();
         }
 
-        cleanup_old_files(dir, 20);
+        cleanup_old_files(dir, &TeeConfig::default());
 
         let remaining: Vec<_> = fs::read_dir(dir).unwrap().filter_map(|e| e.ok()).collect();
         assert_eq!(remaining.len(), 20);
@@ -348,6 +801,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_cleanup_removes_index_records() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        for i in 0..3 {
+            fs::write(dir.join(format!("{}_test.log", 1000000 + i)), "content").unwrap();
+            fs::write(dir.join(format!("{}_test.json", 1000000 + i)), "{}").unwrap();
+        }
+
+        let config = TeeConfig {
+            max_files: 2,
+            ..TeeConfig::default()
+        };
+        cleanup_old_files(dir, &config);
+
+        assert!(!dir.join("1000000_test.json").exists());
+        assert!(dir.join("1000001_test.json").exists());
+    }
+
+    #[test]
+    fn test_index_entries_and_lookup() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        let content = "error: test failed\n".repeat(50);
+        let file = write_tee_file(&content, "cargo_test", dir, &TeeConfig::default()).unwrap();
+        write_index_entry(&file, "cargo_test", content.len(), 101);
+        let path = file.path;
+        // A log from before the index existed
+        fs::write(dir.join("1000000_go_test.log"), "FAIL").unwrap();
+
+        let entries = entries_in(dir);
+        assert_eq!(entries.len(), 2);
+
+        let newest = &entries[0];
+        assert_eq!(newest.slug, "cargo_test");
+        assert_eq!(newest.exit_code, Some(101));
+        assert_eq!(newest.size, content.len());
+        assert!(newest.command.as_deref().unwrap().starts_with("rtk"));
+        assert_eq!(newest.path, path);
+
+        let legacy = &entries[1];
+        assert_eq!(legacy.id, "1000000_go_test");
+        assert_eq!(legacy.slug, "go_test");
+        assert_eq!(legacy.command, None);
+        assert_eq!(legacy.size, 4);
+
+        assert_eq!(find_entry(&entries, "1").unwrap().id, newest.id);
+        assert_eq!(
+            find_entry(&entries, "1000000_go_test.log").unwrap().id,
+            "1000000_go_test"
+        );
+        assert!(find_entry(&entries, "1000000_go_test.log.gz").is_ok());
+        assert!(find_entry(&entries, "3").is_err());
+        assert!(find_entry(&entries, "nope").is_err());
+    }
+
     #[test]
     fn test_format_hint() {
         let path = PathBuf::from("/tmp/rtk/tee/123_cargo_test.log");
@@ -357,6 +866,63 @@ mod tests {
         assert!(hint.contains("123_cargo_test.log"));
     }
 
+    #[test]
+    fn test_index_entries_compressed() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let dir = tmpdir.path();
+        let content = "FAIL: TestParse\n".repeat(100);
+        let config = TeeConfig {
+            compress: true,
+            ..TeeConfig::default()
+        };
+        let file = write_tee_file(&content, "go_test", dir, &config).unwrap();
+        write_index_entry(&file, "go_test", content.len(), 1);
+
+        let id = tee_id(&file.path).unwrap();
+        assert!(dir.join(format!("{}.json", id)).exists());
+        let entries = entries_in(dir);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].id, id);
+        assert_eq!(entries[0].size, content.len());
+        assert_eq!(read_tee_file(&entries[0].path).unwrap(), content);
+    }
+
+    #[test]
+    fn test_format_hint_compressed() {
+        let path = PathBuf::from("/tmp/rtk/tee/123_cargo_test.log.gz");
+        assert_eq!(
+            format_hint(&path),
+            "[full output: rtk tee show 123_cargo_test]"
+        );
+    }
+
+    #[test]
+    fn test_link_sections() {
+        let filtered = format!(
+            "src/a.ts (12 errors)\n  ... +2 more errors{}\nsummary",
+            section_ref("tsc:src/a.ts")
+        );
+        assert_eq!(
+            link_sections(&filtered, Some("1707753600_tsc")),
+            "src/a.ts (12 errors)\n  ... +2 more errors → rtk tee show 1707753600_tsc --section tsc:src/a.ts\nsummary"
+        );
+        assert_eq!(
+            link_sections(&filtered, None),
+            "src/a.ts (12 errors)\n  ... +2 more errors\nsummary"
+        );
+        assert_eq!(link_sections("plain\n", None), "plain\n");
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("--lib"), "--lib");
+        assert_eq!(shell_quote("src/a.rs:10"), "src/a.rs:10");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
     #[test]
     fn test_tee_config_default() {
         let config = TeeConfig::default();
@@ -365,6 +931,9 @@ mod tests {
         assert_eq!(config.max_files, 20);
         assert_eq!(config.max_file_size, 1_048_576);
         assert!(config.directory.is_none());
+        assert!(!config.compress);
+        assert!(config.max_age_days.is_none());
+        assert!(config.max_total_size.is_none());
     }
 
     #[test]
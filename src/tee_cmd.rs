@@ -0,0 +1,182 @@
+use crate::tee::{self, TeeEntry};
+use crate::utils::truncate;
+use anyhow::{Context, Result};
+use regex::Regex;
+
+/// Max matches printed by `rtk tee grep`
+const MAX_GREP_MATCHES: usize = 100;
+
+/// `rtk tee list`: saved raw outputs, newest first.
+pub fn list(_verbose: u8) -> Result<()> {
+    let entries = tee::list_entries()?;
+    if entries.is_empty() {
+        println!("No tee files yet (raw output is saved when a filtered command fails).");
+        return Ok(());
+    }
+
+    println!("Tee files ({}):", entries.len());
+    for (idx, entry) in entries.iter().enumerate() {
+        println!(
+            "{:>3}. {:<32} {:>8} {:>7} {:>7}  {}",
+            idx + 1,
+            entry.id,
+            format_age(entry),
+            entry
+                .exit_code
+                .map(|c| format!("exit {}", c))
+                .unwrap_or_else(|| "-".to_string()),
+            crate::ls::human_size(entry.size as u64),
+            entry.command.as_deref().unwrap_or(&entry.slug)
+        );
+    }
+    Ok(())
+}
+
+/// `rtk tee show <id> [--section K] [--lines A:B]`: print a saved raw output,
+/// or one section / slice of it.
+pub fn show(id: &str, section: Option<&str>, lines: Option<&str>, _verbose: u8) -> Result<()> {
+    let entries = tee::list_entries()?;
+    let entry = tee::find_entry(&entries, id)?;
+    let mut content = tee::read_tee_file(&entry.path)?;
+    if let Some(key) = section {
+        content = extract_section(&content, key)
+            .with_context(|| format!("No section '{}' in tee file {}", key, entry.id))?;
+        content.push('\n');
+    }
+
+    match lines {
+        Some(range) => {
+            let (start, end) = parse_range(range)?;
+            for line in content
+                .lines()
+                .skip(start - 1)
+                .take(end.saturating_sub(start - 1))
+            {
+                println!("{}", line);
+            }
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+/// `rtk tee grep <pattern> [id]`: search saved raw outputs, printing `id:line: text`.
+pub fn grep(pattern: &str, id: Option<&str>, _verbose: u8) -> Result<()> {
+    let re = Regex::new(pattern).with_context(|| format!("Invalid regex: {}", pattern))?;
+    let entries = tee::list_entries()?;
+    let selected: Vec<&TeeEntry> = match id {
+        Some(id) => vec![tee::find_entry(&entries, id)?],
+        None => entries.iter().collect(),
+    };
+
+    let mut total = 0;
+    for entry in selected {
+        let content = match tee::read_tee_file(&entry.path) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        for (lineno, line) in content.lines().enumerate() {
+            if !re.is_match(line) {
+                continue;
+            }
+            total += 1;
+            if total <= MAX_GREP_MATCHES {
+                println!("{}:{}: {}", entry.id, lineno + 1, truncate(line, 200));
+            }
+        }
+    }
+
+    if total == 0 {
+        println!("No matches for '{}'", pattern);
+    } else if total > MAX_GREP_MATCHES {
+        println!(
+            "... +{} more matches (narrow the pattern or pass a tee id)",
+            total - MAX_GREP_MATCHES
+        );
+    }
+    Ok(())
+}
+
+/// Raw lines of one section referenced by a filter's drill-down hint
+/// (`tee::section_ref`). Keys are `<kind>:<name>`.
+fn extract_section(raw: &str, key: &str) -> Result<String> {
+    let (kind, name) = key
+        .split_once(':')
+        .with_context(|| format!("Invalid section '{}' (expected <kind>:<name>)", key))?;
+    let section = match kind {
+        "tsc" => crate::tsc_cmd::extract_section(raw, name),
+        "cargo-test" => crate::cargo_cmd::extract_test_section(raw, name),
+        _ => anyhow::bail!(
+            "Unknown section kind '{}' (expected tsc or cargo-test)",
+            kind
+        ),
+    };
+    section.context("section not found")
+}
+
+/// Parse a 1-based inclusive line range: `A:B`, `A:` (to end), `:B`, or `N`.
+fn parse_range(range: &str) -> Result<(usize, usize)> {
+    let parse = |s: &str, default: usize| -> Result<usize> {
+        if s.trim().is_empty() {
+            Ok(default)
+        } else {
+            s.trim()
+                .parse()
+                .with_context(|| format!("Invalid line number '{}' in --lines {}", s, range))
+        }
+    };
+
+    let (start, end) = match range.split_once(':') {
+        Some((a, b)) => (parse(a, 1)?, parse(b, usize::MAX)?),
+        None => {
+            let n = parse(range, 1)?;
+            (n, n)
+        }
+    };
+    if start == 0 || end < start {
+        anyhow::bail!("Invalid --lines range '{}' (expected A:B, 1-based)", range);
+    }
+    Ok((start, end))
+}
+
+fn format_age(entry: &TeeEntry) -> String {
+    let ts = match chrono::DateTime::parse_from_rfc3339(&entry.timestamp) {
+        Ok(ts) => ts,
+        Err(_) => return "?".to_string(),
+    };
+    let secs = (chrono::Utc::now() - ts.with_timezone(&chrono::Utc)).num_seconds();
+    match secs {
+        s if s < 60 => format!("{}s ago", s.max(0)),
+        s if s < 3600 => format!("{}m ago", s / 60),
+        s if s < 86400 => format!("{}h ago", s / 3600),
+        s => format!("{}d ago", s / 86400),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_section() {
+        let raw = "src/a.ts(1,1): error TS2322: A.\nsrc/b.ts(2,1): error TS2322: B.\n";
+        assert_eq!(
+            extract_section(raw, "tsc:src/b.ts").unwrap(),
+            "src/b.ts(2,1): error TS2322: B."
+        );
+        assert!(extract_section(raw, "tsc:src/c.ts").is_err());
+        assert!(extract_section(raw, "nope:src/a.ts").is_err());
+        assert!(extract_section(raw, "src/a.ts").is_err());
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("10:20").unwrap(), (10, 20));
+        assert_eq!(parse_range("5:").unwrap(), (5, usize::MAX));
+        assert_eq!(parse_range(":3").unwrap(), (1, 3));
+        assert_eq!(parse_range("7").unwrap(), (7, 7));
+        assert!(parse_range("0:5").is_err());
+        assert!(parse_range("9:2").is_err());
+        assert!(parse_range("a:b").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Cross-run test failure diffing.
+//!
+//! While fixing tests, agents rerun the same command over and over and get the
+//! same failure list each time. Each run of `rtk cargo test`, `rtk pytest` or
+//! `rtk vitest run` stores its failing test names in the tracking database,
+//! keyed by project root and command line. When the previous run of the same
+//! command left failures behind, compact output shows only what changed: new
+//! failures in full, still-failing and fixed tests by name. `--all-failures`
+//! restores the full list.
+
+use crate::parser::TestResult;
+use crate::tracking::{self, Tracker};
+use crate::utils::truncate;
+use std::collections::HashSet;
+use std::sync::OnceLock;
+
+/// Names listed per category (still failing, fixed) before "+N more"
+const MAX_NAMES: usize = 10;
+
+/// New failures shown with their message
+const MAX_NEW_DETAILS: usize = 10;
+
+static SHOW_ALL: OnceLock<bool> = OnceLock::new();
+
+/// Set from `--all-failures` (once, from main).
+pub fn set_show_all(show_all: bool) {
+    let _ = SHOW_ALL.set(show_all);
+}
+
+fn show_all() -> bool {
+    SHOW_ALL.get().copied().unwrap_or(false)
+}
+
+/// Failing tests of this run compared with the previous run of the same command.
+#[derive(Debug, Default, PartialEq)]
+pub struct FailureDiff {
+    /// Failing now, passing (or absent) last time
+    pub new: Vec<String>,
+    /// Failing in both runs
+    pub still_failing: Vec<String>,
+    /// Failing last time, not anymore
+    pub fixed: Vec<String>,
+}
+
+impl FailureDiff {
+    pub fn between(previous: &[String], current: &[String]) -> Self {
+        let before: HashSet<&str> = previous.iter().map(String::as_str).collect();
+        let now: HashSet<&str> = current.iter().map(String::as_str).collect();
+        let (still_failing, new) = current
+            .iter()
+            .cloned()
+            .partition(|name| before.contains(name.as_str()));
+        FailureDiff {
+            new,
+            still_failing,
+            fixed: previous
+                .iter()
+                .filter(|name| !now.contains(name.as_str()))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+/// Store this run's failing tests for `command` in the current project and
+/// compare them with the previous run. `None` on the first run, or when the
+/// output could not be parsed.
+pub fn record(command: &str, result: Option<&TestResult>) -> Option<FailureDiff> {
+    let result = result?;
+    let project = tracking::current_project_root().or_else(|| {
+        std::env::current_dir()
+            .ok()
+            .map(|d| d.to_string_lossy().to_string())
+    })?;
+    let current: Vec<String> = result
+        .failures
+        .iter()
+        .map(|f| f.test_name.clone())
+        .collect();
+
+    let tracker = Tracker::new().ok()?;
+    let previous = tracker.last_test_failures(&project, command).ok()?;
+    let _ = tracker.record_test_failures(&project, command, &current);
+    previous.map(|previous| FailureDiff::between(&previous, &current))
+}
+
+/// Compact text for a test run: `full` on a first run or with
+/// `--all-failures`, otherwise reduced to what changed since the last run.
+pub fn compact(
+    tool: &str,
+    result: Option<&TestResult>,
+    diff: Option<FailureDiff>,
+    full: String,
+) -> String {
+    match (result, diff) {
+        (Some(result), Some(diff)) if !show_all() => render(tool, result, &diff, full),
+        _ => full,
+    }
+}
+
+fn render(tool: &str, result: &TestResult, diff: &FailureDiff, full: String) -> String {
+    if result.failures.is_empty() {
+        // All green (or failures we could not name): keep the usual summary
+        if diff.fixed.is_empty() || result.failed > 0 {
+            return full;
+        }
+        return format!(
+            "{}\nfixed since last run: {}",
+            full,
+            list_names(&diff.fixed)
+        );
+    }
+
+    let mut lines = vec![
+        format!(
+            "{}: {} passed, {} failed (since last run: {} new, {} still failing, {} fixed)",
+            tool,
+            result.passed,
+            result.failed,
+            diff.new.len(),
+            diff.still_failing.len(),
+            diff.fixed.len()
+        ),
+        "═══════════════════════════════════════".to_string(),
+    ];
+
+    for name in diff.new.iter().take(MAX_NEW_DETAILS) {
+        let failure = match result.failures.iter().find(|f| &f.test_name == name) {
+            Some(f) => f,
+            None => continue,
+        };
+        if failure.file_path.is_empty() {
+            lines.push(format!("NEW {}", name));
+        } else {
+            lines.push(format!("NEW {} ({})", name, failure.file_path));
+        }
+        for line in failure.error_message.lines().take(3) {
+            lines.push(format!("   {}", truncate(line, 200)));
+        }
+    }
+    if diff.new.len() > MAX_NEW_DETAILS {
+        lines.push(format!(
+            "... +{} more new failures",
+            diff.new.len() - MAX_NEW_DETAILS
+        ));
+    }
+    if !diff.still_failing.is_empty() {
+        lines.push(format!(
+            "still failing: {}",
+            list_names(&diff.still_failing)
+        ));
+    }
+    if !diff.fixed.is_empty() {
+        lines.push(format!("fixed: {}", list_names(&diff.fixed)));
+    }
+    lines.push("(rtk --all-failures ... for the full failure list)".to_string());
+    lines.join("\n")
+}
+
+fn list_names(names: &[String]) -> String {
+    let mut listed = names[..names.len().min(MAX_NAMES)].join(", ");
+    if names.len() > MAX_NAMES {
+        listed.push_str(&format!(" (+{} more)", names.len() - MAX_NAMES));
+    }
+    listed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::TestFailure;
+
+    fn names(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    fn result_with(failures: &[&str]) -> TestResult {
+        TestResult {
+            total: 10,
+            passed: 10 - failures.len(),
+            failed: failures.len(),
+            skipped: 0,
+            duration_ms: None,
+            failures: failures
+                .iter()
+                .map(|name| TestFailure {
+                    test_name: name.to_string(),
+                    file_path: "src/lib.rs:3:5".to_string(),
+                    error_message: format!("thread '{}' panicked\nassertion failed", name),
+                    stack_trace: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_failure_diff_between() {
+        let diff = FailureDiff::between(&names(&["a", "b", "c"]), &names(&["b", "d"]));
+        assert_eq!(diff.new, names(&["d"]));
+        assert_eq!(diff.still_failing, names(&["b"]));
+        assert_eq!(diff.fixed, names(&["a", "c"]));
+        assert_eq!(FailureDiff::between(&[], &[]), FailureDiff::default());
+    }
+
+    #[test]
+    fn test_render_shows_only_changes() {
+        let result = result_with(&["b", "d"]);
+        let diff = FailureDiff::between(&names(&["a", "b"]), &names(&["b", "d"]));
+        let text = render("cargo test", &result, &diff, "FULL".to_string());
+        assert!(text.starts_with(
+            "cargo test: 8 passed, 2 failed (since last run: 1 new, 1 still failing, 1 fixed)"
+        ));
+        assert!(
+            text.contains("NEW d (src/lib.rs:3:5)\n   thread 'd' panicked\n   assertion failed")
+        );
+        assert!(!text.contains("thread 'b'"));
+        assert!(text.contains("still failing: b\nfixed: a"));
+        assert!(!text.contains("FULL"));
+    }
+
+    #[test]
+    fn test_render_all_green_lists_fixed() {
+        let diff = FailureDiff::between(&names(&["a"]), &[]);
+        let text = render(
+            "pytest",
+            &result_with(&[]),
+            &diff,
+            "✓ all passed".to_string(),
+        );
+        assert_eq!(text, "✓ all passed\nfixed since last run: a");
+
+        let unchanged = render(
+            "pytest",
+            &result_with(&[]),
+            &FailureDiff::default(),
+            "✓ all passed".to_string(),
+        );
+        assert_eq!(unchanged, "✓ all passed");
+    }
+
+    #[test]
+    fn test_compact_without_previous_run_is_full() {
+        let result = result_with(&["a"]);
+        assert_eq!(
+            compact("pytest", Some(&result), None, "FULL".to_string()),
+            "FULL"
+        );
+        assert_eq!(
+            compact(
+                "pytest",
+                None,
+                Some(FailureDiff::default()),
+                "FULL".to_string()
+            ),
+            "FULL"
+        );
+    }
+
+    #[test]
+    fn test_list_names_truncates() {
+        let many: Vec<String> = (0..12).map(|i| format!("t{}", i)).collect();
+        assert!(list_names(&many).ends_with("t9 (+2 more)"));
+    }
+}
@@ -0,0 +1,112 @@
+//! Token counting strategies used by tracking.
+//!
+//! Two counters are available, selected by `tracking.token_counter` in config.toml:
+//!
+//! - `heuristic` (default): ~4 chars per token, free to compute
+//! - `bpe`: exact count with the bundled cl100k_base BPE vocabulary (no network)
+//!
+//! Each tracking row stores the name of the counter that produced it, so
+//! `rtk gain` can tell estimated numbers from exact ones.
+
+use serde::{Deserialize, Serialize};
+
+/// Counts tokens in a piece of text.
+pub trait TokenCounter: Sync {
+    /// Identifier stored alongside each tracking row.
+    fn name(&self) -> &'static str;
+    fn count(&self, text: &str) -> usize;
+}
+
+/// Which counter tracking should use.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenCounterKind {
+    #[default]
+    Heuristic,
+    Bpe,
+}
+
+impl TokenCounterKind {
+    pub fn counter(self) -> &'static dyn TokenCounter {
+        match self {
+            TokenCounterKind::Heuristic => &HeuristicCounter,
+            TokenCounterKind::Bpe => &BpeCounter,
+        }
+    }
+}
+
+/// chars/4 approximation (see [`crate::tracking::estimate_tokens`]).
+pub struct HeuristicCounter;
+
+impl TokenCounter for HeuristicCounter {
+    fn name(&self) -> &'static str {
+        "heuristic"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        crate::tracking::estimate_tokens(text)
+    }
+}
+
+/// Exact BPE count using the cl100k_base vocabulary embedded in the binary.
+/// The vocabulary is decoded on first use only.
+pub struct BpeCounter;
+
+impl TokenCounter for BpeCounter {
+    fn name(&self) -> &'static str {
+        "cl100k_base"
+    }
+
+    fn count(&self, text: &str) -> usize {
+        tiktoken_rs::cl100k_base_singleton()
+            .encode_ordinary(text)
+            .len()
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref CONFIGURED: TokenCounterKind = crate::config::Config::load()
+        .map(|c| c.tracking.token_counter)
+        .unwrap_or_default();
+}
+
+/// Counter selected in config (falls back to the heuristic if config can't be read).
+pub fn configured() -> &'static dyn TokenCounter {
+    CONFIGURED.counter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heuristic_matches_estimate_tokens() {
+        let text = "hello world";
+        assert_eq!(HeuristicCounter.count(text), 3);
+        assert_eq!(HeuristicCounter.name(), "heuristic");
+    }
+
+    #[test]
+    fn test_bpe_counts_real_tokens() {
+        assert_eq!(BpeCounter.count(""), 0);
+        assert_eq!(BpeCounter.count("hello world"), 2);
+
+        // chars/4 overstates box-drawing art and understates dense code
+        let art = "─".repeat(40);
+        assert!(BpeCounter.count(&art) < HeuristicCounter.count(&art));
+        let code = "if (a[i] != b[j]) { x += 1; }";
+        assert!(BpeCounter.count(code) > HeuristicCounter.count(code));
+    }
+
+    #[test]
+    fn test_kind_from_config_value() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            token_counter: TokenCounterKind,
+        }
+        let w: Wrapper = toml::from_str("token_counter = \"bpe\"").unwrap();
+        assert_eq!(w.token_counter, TokenCounterKind::Bpe);
+        assert_eq!(w.token_counter.counter().name(), "cl100k_base");
+        assert_eq!(TokenCounterKind::default().counter().name(), "heuristic");
+    }
+}
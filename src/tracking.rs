@@ -8,7 +8,10 @@
 //!
 //! - Storage: SQLite database (~/.local/share/rtk/tracking.db)
 //! - Retention: 90-day automatic cleanup
+//! - Schema: versioned with `PRAGMA user_version`, upgraded by [`MIGRATIONS`] on open
 //! - Metrics: Input/output tokens, savings %, execution time
+//! - Token counts come from the configured [`crate::tokens::TokenCounter`];
+//!   each row records which counter produced it
 //!
 //! # Quick Start
 //!
@@ -29,17 +32,123 @@
 //!
 //! See [docs/tracking.md](../docs/tracking.md) for full documentation.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
 use serde::Serialize;
 use std::ffi::OsString;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 /// Number of days to retain tracking history before automatic cleanup.
 const HISTORY_DAYS: i64 = 90;
 
+/// A schema migration, applied inside a transaction.
+type Migration = fn(&Transaction) -> rusqlite::Result<()>;
+
+/// Ordered schema migrations. Migration `i` upgrades the database from
+/// `user_version = i` to `i + 1`; never reorder or edit a released entry,
+/// only append.
+///
+/// Databases created before versioning report `user_version = 0` but may
+/// already have some columns, so column additions check before altering.
+const MIGRATIONS: &[Migration] = &[
+    // 1: base table
+    |tx| {
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS commands (
+                id INTEGER PRIMARY KEY,
+                timestamp TEXT NOT NULL,
+                original_cmd TEXT NOT NULL,
+                rtk_cmd TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                saved_tokens INTEGER NOT NULL,
+                savings_pct REAL NOT NULL
+            );
+            CREATE INDEX IF NOT EXISTS idx_timestamp ON commands(timestamp);",
+        )
+    },
+    // 2: execution time
+    |tx| add_column_if_missing(tx, "exec_time_ms", "INTEGER DEFAULT 0"),
+    // 3: token counter (rows before it were all chars/4 estimates)
+    |tx| add_column_if_missing(tx, "token_counter", "TEXT DEFAULT 'heuristic'"),
+    // 4: run context (NULL = not recorded)
+    |tx| {
+        add_column_if_missing(tx, "exit_code", "INTEGER")?;
+        add_column_if_missing(tx, "cwd", "TEXT")?;
+        add_column_if_missing(tx, "project_root", "TEXT")?;
+        add_column_if_missing(tx, "filter_tier", "INTEGER")?;
+        tx.execute_batch("CREATE INDEX IF NOT EXISTS idx_project_root ON commands(project_root)")
+    },
+    // 5: failing tests of the last run, per project and test command
+    |tx| {
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS test_runs (
+                project_root TEXT NOT NULL,
+                command TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                failures TEXT NOT NULL,
+                PRIMARY KEY (project_root, command)
+            );",
+        )
+    },
+    // 6: last output per command and directory, for repeated-output suppression
+    |tx| {
+        tx.execute_batch(
+            "CREATE TABLE IF NOT EXISTS output_cache (
+                command TEXT NOT NULL,
+                cwd TEXT NOT NULL,
+                fingerprint TEXT NOT NULL,
+                output_hash TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                PRIMARY KEY (command, cwd)
+            );",
+        )
+    },
+];
+
+/// Row filter shared by every query; bound to `?1` (project root) and `?2` (failed only).
+const SCOPE_FILTER: &str = "(?1 IS NULL OR project_root = ?1) AND (?2 = 0 OR exit_code != 0)";
+
+/// Schema version this build writes.
+pub const SCHEMA_VERSION: u32 = MIGRATIONS.len() as u32;
+
+fn add_column_if_missing(tx: &Transaction, column: &str, decl: &str) -> rusqlite::Result<()> {
+    let exists = tx
+        .prepare("SELECT 1 FROM pragma_table_info('commands') WHERE name = ?1")?
+        .exists(params![column])?;
+    if !exists {
+        tx.execute_batch(&format!(
+            "ALTER TABLE commands ADD COLUMN {} {}",
+            column, decl
+        ))?;
+    }
+    Ok(())
+}
+
+/// Bring the database up to [`SCHEMA_VERSION`], one transaction per migration.
+fn migrate(conn: &mut Connection) -> Result<()> {
+    let current: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    if current > SCHEMA_VERSION {
+        anyhow::bail!(
+            "Tracking database schema v{} is newer than this rtk supports (v{}); upgrade rtk",
+            current,
+            SCHEMA_VERSION
+        );
+    }
+
+    for (idx, migration) in MIGRATIONS.iter().enumerate().skip(current as usize) {
+        let version = idx as u32 + 1;
+        let tx = conn.transaction()?;
+        migration(&tx).with_context(|| format!("Tracking migration to v{} failed", version))?;
+        tx.pragma_update(None, "user_version", version)?;
+        tx.commit()?;
+    }
+
+    Ok(())
+}
+
 /// Main tracking interface for recording and querying command history.
 ///
 /// Manages SQLite database connection and provides methods for:
@@ -56,10 +165,10 @@ const HISTORY_DAYS: i64 = 90;
 /// # Examples
 ///
 /// ```no_run
-/// use rtk::tracking::Tracker;
+/// use rtk::tracking::{RunContext, Tracker};
 ///
 /// let tracker = Tracker::new()?;
-/// tracker.record("ls -la", "rtk ls", 1000, 200, 50)?;
+/// tracker.record("ls -la", "rtk ls", 1000, 200, "heuristic", &RunContext::current())?;
 ///
 /// let summary = tracker.get_summary()?;
 /// println!("Total saved: {} tokens", summary.total_saved);
@@ -67,6 +176,76 @@ const HISTORY_DAYS: i64 = 90;
 /// ```
 pub struct Tracker {
     conn: Connection,
+    path: PathBuf,
+    scope: Scope,
+}
+
+/// Restricts which rows the query methods see.
+///
+/// Used by `rtk gain --project` and `rtk gain --failed-only`.
+#[derive(Debug, Default, Clone)]
+pub struct Scope {
+    /// Only rows recorded inside this project root
+    pub project_root: Option<String>,
+    /// Only rows whose command exited non-zero; rows without an exit code are excluded
+    pub failed_only: bool,
+}
+
+/// Where and how a tracked command ran, stored alongside its token counts.
+#[derive(Debug, Default, Clone)]
+pub struct RunContext {
+    /// Exit status of the underlying command, if known
+    pub exit_code: Option<i32>,
+    /// Working directory rtk ran in
+    pub cwd: Option<String>,
+    /// Enclosing git toplevel of `cwd`
+    pub project_root: Option<String>,
+    /// `ParseResult::tier()` for parser-based filters (1 = full, 2 = degraded, 3 = passthrough)
+    pub filter_tier: Option<u8>,
+    /// Execution time in milliseconds (filled in by [`TimedExecution`])
+    pub exec_time_ms: u64,
+}
+
+impl RunContext {
+    /// Context of the current process: cwd and project root, exit code unknown.
+    pub fn current() -> Self {
+        let cwd = std::env::current_dir().ok();
+        Self {
+            exit_code: None,
+            project_root: cwd
+                .as_deref()
+                .and_then(find_project_root)
+                .map(|p| p.to_string_lossy().into_owned()),
+            cwd: cwd.map(|p| p.to_string_lossy().into_owned()),
+            filter_tier: None,
+            exec_time_ms: 0,
+        }
+    }
+
+    /// Current context for a command that exited with `exit_code`.
+    pub fn exited(exit_code: i32) -> Self {
+        Self {
+            exit_code: Some(exit_code),
+            ..Self::current()
+        }
+    }
+
+    pub fn with_tier(mut self, tier: u8) -> Self {
+        self.filter_tier = Some(tier);
+        self
+    }
+}
+
+/// Nearest ancestor containing `.git` (a directory, or a file for worktrees).
+pub fn find_project_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Project root of the current working directory, as stored in tracking rows.
+pub fn current_project_root() -> Option<String> {
+    RunContext::current().project_root
 }
 
 /// Individual command record from tracking history.
@@ -108,6 +287,25 @@ pub struct GainSummary {
     pub by_command: Vec<(String, usize, usize, f64, u64)>,
     /// Last 30 days of activity: (date, saved_tokens)
     pub by_day: Vec<(String, usize)>,
+    /// Rows per token counter: (counter name, commands)
+    pub by_counter: Vec<(String, usize)>,
+}
+
+/// Tracking database metadata shown by `rtk gain --db-info`.
+#[derive(Debug, Serialize)]
+pub struct DbInfo {
+    /// Database file location
+    pub path: PathBuf,
+    /// `PRAGMA user_version` after migrations
+    pub schema_version: u32,
+    /// Number of rows in `commands`
+    pub rows: usize,
+    /// Timestamp of the oldest row (RFC3339)
+    pub oldest: Option<String>,
+    /// Timestamp of the newest row (RFC3339)
+    pub newest: Option<String>,
+    /// Database file size in bytes
+    pub size_bytes: u64,
 }
 
 /// Daily statistics for token savings and execution metrics.
@@ -197,12 +395,79 @@ pub struct MonthStats {
     pub avg_time_ms: u64,
 }
 
+/// Per-project statistics, for `rtk gain --by-project`.
+///
+/// Commands recorded outside any git repository (or before project roots
+/// were tracked) are grouped under `project: null`.
+#[derive(Debug, Serialize)]
+pub struct ProjectStats {
+    /// Project root (git toplevel), `None` for commands outside a repository
+    pub project: Option<String>,
+    /// Number of commands executed in this project
+    pub commands: usize,
+    /// Total input tokens for this project
+    pub input_tokens: usize,
+    /// Total output tokens for this project
+    pub output_tokens: usize,
+    /// Total tokens saved in this project
+    pub saved_tokens: usize,
+    /// Savings percentage for this project
+    pub savings_pct: f64,
+    /// Total execution time for this project (milliseconds)
+    pub total_time_ms: u64,
+    /// Average execution time per command (milliseconds)
+    pub avg_time_ms: u64,
+    /// Breakdown by rtk command, most tokens saved first
+    pub by_command: Vec<ProjectCommandStats>,
+}
+
+/// Output printed by the previous run of a command, as stored in `output_cache`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedOutput {
+    /// Cheap state fingerprint taken with the output (index/directory mtimes)
+    pub fingerprint: String,
+    /// Hash of the printed output
+    pub output_hash: String,
+    /// RFC3339 time the output was printed in full
+    pub timestamp: String,
+}
+
+/// Statistics for one rtk command within a project.
+#[derive(Debug, Serialize)]
+pub struct ProjectCommandStats {
+    /// rtk command as recorded (e.g. "rtk cargo test")
+    pub command: String,
+    pub commands: usize,
+    pub input_tokens: usize,
+    pub output_tokens: usize,
+    pub saved_tokens: usize,
+    pub savings_pct: f64,
+    pub total_time_ms: u64,
+    pub avg_time_ms: u64,
+}
+
+fn savings_pct(input: usize, saved: usize) -> f64 {
+    if input > 0 {
+        (saved as f64 / input as f64) * 100.0
+    } else {
+        0.0
+    }
+}
+
+fn avg_time(total_time_ms: u64, commands: usize) -> u64 {
+    if commands > 0 {
+        total_time_ms / commands as u64
+    } else {
+        0
+    }
+}
+
 impl Tracker {
     /// Create a new tracker instance.
     ///
     /// Opens or creates the SQLite database at the platform-specific location.
-    /// Automatically creates the `commands` table if it doesn't exist and runs
-    /// any necessary schema migrations.
+    /// Runs any pending schema [`MIGRATIONS`], which also creates the
+    /// `commands` table on first use.
     ///
     /// # Errors
     ///
@@ -210,7 +475,7 @@ impl Tracker {
     /// - Cannot determine database path
     /// - Cannot create parent directories
     /// - Cannot open/create SQLite database
-    /// - Schema creation/migration fails
+    /// - Schema migration fails, or the database is from a newer rtk
     ///
     /// # Examples
     ///
@@ -221,38 +486,57 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn new() -> Result<Self> {
-        let db_path = get_db_path()?;
+        Self::open(&get_db_path()?)
+    }
+
+    /// Open (and migrate) the database at an explicit path.
+    pub fn open(db_path: &Path) -> Result<Self> {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)?;
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS commands (
-                id INTEGER PRIMARY KEY,
-                timestamp TEXT NOT NULL,
-                original_cmd TEXT NOT NULL,
-                rtk_cmd TEXT NOT NULL,
-                input_tokens INTEGER NOT NULL,
-                output_tokens INTEGER NOT NULL,
-                saved_tokens INTEGER NOT NULL,
-                savings_pct REAL NOT NULL
-            )",
-            [],
-        )?;
+        let mut conn = Connection::open(db_path)?;
+        migrate(&mut conn)?;
 
-        conn.execute(
-            "CREATE INDEX IF NOT EXISTS idx_timestamp ON commands(timestamp)",
-            [],
-        )?;
+        Ok(Self {
+            conn,
+            path: db_path.to_path_buf(),
+            scope: Scope::default(),
+        })
+    }
 
-        // Migration: add exec_time_ms column if it doesn't exist
-        let _ = conn.execute(
-            "ALTER TABLE commands ADD COLUMN exec_time_ms INTEGER DEFAULT 0",
-            [],
-        );
+    /// Restrict all subsequent queries to `scope`.
+    pub fn with_scope(mut self, scope: Scope) -> Self {
+        self.scope = scope;
+        self
+    }
 
-        Ok(Self { conn })
+    /// Database location, schema version and size, for `rtk gain --db-info`.
+    pub fn db_info(&self) -> Result<DbInfo> {
+        let schema_version: u32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let (rows, oldest, newest) = self.conn.query_row(
+            "SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM commands",
+            [],
+            |row| {
+                Ok((
+                    row.get::<_, i64>(0)? as usize,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, Option<String>>(2)?,
+                ))
+            },
+        )?;
+        let size_bytes = std::fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+
+        Ok(DbInfo {
+            path: self.path.clone(),
+            schema_version,
+            rows,
+            oldest,
+            newest,
+            size_bytes,
+        })
     }
 
     /// Record a command execution with token counts and timing.
@@ -266,15 +550,16 @@ impl Tracker {
     /// - `rtk_cmd`: The RTK command used (e.g., "rtk ls")
     /// - `input_tokens`: Estimated tokens from standard command output
     /// - `output_tokens`: Actual tokens from RTK output
-    /// - `exec_time_ms`: Execution time in milliseconds
+    /// - `token_counter`: Name of the [`crate::tokens::TokenCounter`] that produced the counts
+    /// - `context`: Execution time, exit code, cwd, project root and filter tier
     ///
     /// # Examples
     ///
     /// ```no_run
-    /// use rtk::tracking::Tracker;
+    /// use rtk::tracking::{RunContext, Tracker};
     ///
     /// let tracker = Tracker::new()?;
-    /// tracker.record("ls -la", "rtk ls", 1000, 200, 50)?;
+    /// tracker.record("ls -la", "rtk ls", 1000, 200, "heuristic", &RunContext::current())?;
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn record(
@@ -283,7 +568,8 @@ impl Tracker {
         rtk_cmd: &str,
         input_tokens: usize,
         output_tokens: usize,
-        exec_time_ms: u64,
+        token_counter: &str,
+        context: &RunContext,
     ) -> Result<()> {
         let saved = input_tokens.saturating_sub(output_tokens);
         let pct = if input_tokens > 0 {
@@ -293,8 +579,9 @@ impl Tracker {
         };
 
         self.conn.execute(
-            "INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct, exec_time_ms)
-             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            "INSERT INTO commands (timestamp, original_cmd, rtk_cmd, input_tokens, output_tokens, saved_tokens, savings_pct, exec_time_ms, token_counter,
+                                   exit_code, cwd, project_root, filter_tier)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)",
             params![
                 Utc::now().to_rfc3339(),
                 original_cmd,
@@ -303,7 +590,12 @@ impl Tracker {
                 output_tokens as i64,
                 saved as i64,
                 pct,
-                exec_time_ms as i64
+                context.exec_time_ms as i64,
+                token_counter,
+                context.exit_code,
+                context.cwd,
+                context.project_root,
+                context.filter_tier
             ],
         )?;
 
@@ -311,6 +603,82 @@ impl Tracker {
         Ok(())
     }
 
+    /// Failing test names recorded by the previous run of `command` in
+    /// `project`, or `None` if it never ran there.
+    pub fn last_test_failures(&self, project: &str, command: &str) -> Result<Option<Vec<String>>> {
+        let stored: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT failures FROM test_runs WHERE project_root = ?1 AND command = ?2",
+                params![project, command],
+                |row| row.get(0),
+            )
+            .optional()?;
+        match stored {
+            Some(json) => Ok(Some(serde_json::from_str(&json)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Replace the failing test names stored for `command` in `project`.
+    pub fn record_test_failures(
+        &self,
+        project: &str,
+        command: &str,
+        failures: &[String],
+    ) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO test_runs (project_root, command, timestamp, failures)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![
+                project,
+                command,
+                Utc::now().to_rfc3339(),
+                serde_json::to_string(failures)?
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Output last printed by `command` in `cwd`, for repeated-output suppression.
+    pub fn last_output(&self, command: &str, cwd: &str) -> Result<Option<CachedOutput>> {
+        Ok(self
+            .conn
+            .query_row(
+                "SELECT fingerprint, output_hash, timestamp FROM output_cache
+                 WHERE command = ?1 AND cwd = ?2",
+                params![command, cwd],
+                |row| {
+                    Ok(CachedOutput {
+                        fingerprint: row.get(0)?,
+                        output_hash: row.get(1)?,
+                        timestamp: row.get(2)?,
+                    })
+                },
+            )
+            .optional()?)
+    }
+
+    /// Remember the output just printed by `command` in `cwd`.
+    pub fn record_output(&self, command: &str, cwd: &str, output: &CachedOutput) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO output_cache (command, cwd, fingerprint, output_hash, timestamp)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                command,
+                cwd,
+                output.fingerprint,
+                output.output_hash,
+                output.timestamp
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn scope_params(&self) -> [&dyn rusqlite::ToSql; 2] {
+        [&self.scope.project_root, &self.scope.failed_only]
+    }
+
     fn cleanup_old(&self) -> Result<()> {
         let cutoff = Utc::now() - chrono::Duration::days(HISTORY_DAYS);
         self.conn.execute(
@@ -346,11 +714,12 @@ impl Tracker {
         let mut total_saved = 0usize;
         let mut total_time_ms = 0u64;
 
-        let mut stmt = self.conn.prepare(
-            "SELECT input_tokens, output_tokens, saved_tokens, exec_time_ms FROM commands",
-        )?;
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT input_tokens, output_tokens, saved_tokens, exec_time_ms FROM commands WHERE {}",
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             Ok((
                 row.get::<_, i64>(0)? as usize,
                 row.get::<_, i64>(1)? as usize,
@@ -382,6 +751,7 @@ impl Tracker {
 
         let by_command = self.get_by_command()?;
         let by_day = self.get_by_day()?;
+        let by_counter = self.get_by_counter()?;
 
         Ok(GainSummary {
             total_commands,
@@ -393,19 +763,39 @@ impl Tracker {
             avg_time_ms,
             by_command,
             by_day,
+            by_counter,
         })
     }
 
+    fn get_by_counter(&self) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT COALESCE(token_counter, 'heuristic'), COUNT(*)
+             FROM commands
+             WHERE {}
+             GROUP BY 1
+             ORDER BY COUNT(*) DESC",
+            SCOPE_FILTER
+        ))?;
+
+        let rows = stmt.query_map(self.scope_params(), |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+        })?;
+
+        Ok(rows.collect::<Result<Vec<_>, _>>()?)
+    }
+
     fn get_by_command(&self) -> Result<Vec<(String, usize, usize, f64, u64)>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT rtk_cmd, COUNT(*), SUM(saved_tokens), AVG(savings_pct), AVG(exec_time_ms)
              FROM commands
+             WHERE {}
              GROUP BY rtk_cmd
              ORDER BY SUM(saved_tokens) DESC
              LIMIT 10",
-        )?;
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             Ok((
                 row.get::<_, String>(0)?,
                 row.get::<_, i64>(1)? as usize,
@@ -419,15 +809,17 @@ impl Tracker {
     }
 
     fn get_by_day(&self) -> Result<Vec<(String, usize)>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT DATE(timestamp), SUM(saved_tokens)
              FROM commands
+             WHERE {}
              GROUP BY DATE(timestamp)
              ORDER BY DATE(timestamp) DESC
              LIMIT 30",
-        )?;
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
         })?;
 
@@ -455,7 +847,7 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_all_days(&self) -> Result<Vec<DayStats>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT
                 DATE(timestamp) as date,
                 COUNT(*) as commands,
@@ -464,11 +856,13 @@ impl Tracker {
                 SUM(saved_tokens) as saved,
                 SUM(exec_time_ms) as total_time
              FROM commands
+             WHERE {}
              GROUP BY DATE(timestamp)
              ORDER BY DATE(timestamp) DESC",
-        )?;
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             let input = row.get::<_, i64>(2)? as usize;
             let saved = row.get::<_, i64>(4)? as usize;
             let commands = row.get::<_, i64>(1)? as usize;
@@ -501,6 +895,98 @@ impl Tracker {
         Ok(result)
     }
 
+    /// Get statistics grouped by project root, each with a per-command breakdown.
+    ///
+    /// Projects are ordered by tokens saved (most first).
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::Tracker;
+    ///
+    /// let tracker = Tracker::new()?;
+    /// for project in tracker.get_by_project()? {
+    ///     println!("{}: {} tokens saved",
+    ///         project.project.as_deref().unwrap_or("(no project)"),
+    ///         project.saved_tokens);
+    /// }
+    /// # Ok::<(), anyhow::Error>(())
+    /// ```
+    pub fn get_by_project(&self) -> Result<Vec<ProjectStats>> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT
+                project_root,
+                rtk_cmd,
+                COUNT(*) as commands,
+                SUM(input_tokens) as input,
+                SUM(output_tokens) as output,
+                SUM(saved_tokens) as saved,
+                SUM(exec_time_ms) as total_time
+             FROM commands
+             WHERE {}
+             GROUP BY project_root, rtk_cmd
+             ORDER BY project_root, SUM(saved_tokens) DESC",
+            SCOPE_FILTER
+        ))?;
+
+        let rows = stmt.query_map(self.scope_params(), |row| {
+            let commands = row.get::<_, i64>(2)? as usize;
+            let input = row.get::<_, i64>(3)? as usize;
+            let saved = row.get::<_, i64>(5)? as usize;
+            let total_time = row.get::<_, i64>(6)? as u64;
+            Ok((
+                row.get::<_, Option<String>>(0)?,
+                ProjectCommandStats {
+                    command: row.get(1)?,
+                    commands,
+                    input_tokens: input,
+                    output_tokens: row.get::<_, i64>(4)? as usize,
+                    saved_tokens: saved,
+                    savings_pct: savings_pct(input, saved),
+                    total_time_ms: total_time,
+                    avg_time_ms: avg_time(total_time, commands),
+                },
+            ))
+        })?;
+
+        // Rows arrive grouped by project: fold consecutive rows into one entry
+        let mut projects: Vec<ProjectStats> = Vec::new();
+        for row in rows {
+            let (project, cmd) = row?;
+            let current = match projects.last_mut() {
+                Some(last) if last.project == project => last,
+                _ => {
+                    projects.push(ProjectStats {
+                        project,
+                        commands: 0,
+                        input_tokens: 0,
+                        output_tokens: 0,
+                        saved_tokens: 0,
+                        savings_pct: 0.0,
+                        total_time_ms: 0,
+                        avg_time_ms: 0,
+                        by_command: Vec::new(),
+                    });
+                    projects.last_mut().unwrap()
+                }
+            };
+            current.commands += cmd.commands;
+            current.input_tokens += cmd.input_tokens;
+            current.output_tokens += cmd.output_tokens;
+            current.saved_tokens += cmd.saved_tokens;
+            current.total_time_ms += cmd.total_time_ms;
+            current.by_command.push(cmd);
+        }
+
+        for project in &mut projects {
+            project.savings_pct = savings_pct(project.input_tokens, project.saved_tokens);
+            project.avg_time_ms = avg_time(project.total_time_ms, project.commands);
+        }
+        projects.sort_by_key(|p| std::cmp::Reverse(p.saved_tokens));
+
+        Ok(projects)
+    }
+
     /// Get weekly statistics grouped by week.
     ///
     /// Returns one [`WeekStats`] per week with aggregated metrics.
@@ -520,7 +1006,7 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_by_week(&self) -> Result<Vec<WeekStats>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT
                 DATE(timestamp, 'weekday 0', '-6 days') as week_start,
                 DATE(timestamp, 'weekday 0') as week_end,
@@ -530,11 +1016,13 @@ impl Tracker {
                 SUM(saved_tokens) as saved,
                 SUM(exec_time_ms) as total_time
              FROM commands
+             WHERE {}
              GROUP BY week_start
              ORDER BY week_start DESC",
-        )?;
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             let input = row.get::<_, i64>(3)? as usize;
             let saved = row.get::<_, i64>(5)? as usize;
             let commands = row.get::<_, i64>(2)? as usize;
@@ -587,7 +1075,7 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_by_month(&self) -> Result<Vec<MonthStats>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT
                 strftime('%Y-%m', timestamp) as month,
                 COUNT(*) as commands,
@@ -596,11 +1084,13 @@ impl Tracker {
                 SUM(saved_tokens) as saved,
                 SUM(exec_time_ms) as total_time
              FROM commands
+             WHERE {}
              GROUP BY month
              ORDER BY month DESC",
-        )?;
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(self.scope_params(), |row| {
             let input = row.get::<_, i64>(2)? as usize;
             let saved = row.get::<_, i64>(4)? as usize;
             let commands = row.get::<_, i64>(1)? as usize;
@@ -655,23 +1145,32 @@ impl Tracker {
     /// # Ok::<(), anyhow::Error>(())
     /// ```
     pub fn get_recent(&self, limit: usize) -> Result<Vec<CommandRecord>> {
-        let mut stmt = self.conn.prepare(
+        let mut stmt = self.conn.prepare(&format!(
             "SELECT timestamp, rtk_cmd, saved_tokens, savings_pct
              FROM commands
+             WHERE {}
              ORDER BY timestamp DESC
-             LIMIT ?1",
-        )?;
+             LIMIT ?3",
+            SCOPE_FILTER
+        ))?;
 
-        let rows = stmt.query_map(params![limit as i64], |row| {
-            Ok(CommandRecord {
-                timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
-                    .map(|dt| dt.with_timezone(&Utc))
-                    .unwrap_or_else(|_| Utc::now()),
-                rtk_cmd: row.get(1)?,
-                saved_tokens: row.get::<_, i64>(2)? as usize,
-                savings_pct: row.get(3)?,
-            })
-        })?;
+        let rows = stmt.query_map(
+            params![
+                self.scope.project_root,
+                self.scope.failed_only,
+                limit as i64
+            ],
+            |row| {
+                Ok(CommandRecord {
+                    timestamp: DateTime::parse_from_rfc3339(&row.get::<_, String>(0)?)
+                        .map(|dt| dt.with_timezone(&Utc))
+                        .unwrap_or_else(|_| Utc::now()),
+                    rtk_cmd: row.get(1)?,
+                    saved_tokens: row.get::<_, i64>(2)? as usize,
+                    savings_pct: row.get(3)?,
+                })
+            },
+        )?;
 
         Ok(rows.collect::<Result<Vec<_>, _>>()?)
     }
@@ -766,7 +1265,7 @@ impl TimedExecution {
     ///
     /// Records the command execution with:
     /// - Elapsed time since [`start`](Self::start)
-    /// - Token counts estimated from input/output strings
+    /// - Token counts from the configured [`crate::tokens::TokenCounter`]
     /// - Calculated savings metrics
     ///
     /// # Arguments
@@ -787,9 +1286,35 @@ impl TimedExecution {
     /// timer.track("ls -la", "rtk ls", input, output);
     /// ```
     pub fn track(&self, original_cmd: &str, rtk_cmd: &str, input: &str, output: &str) {
-        let elapsed_ms = self.start.elapsed().as_millis() as u64;
-        let input_tokens = estimate_tokens(input);
-        let output_tokens = estimate_tokens(output);
+        self.track_with(original_cmd, rtk_cmd, input, output, RunContext::current());
+    }
+
+    /// Like [`track`](Self::track), with an explicit exit code and filter tier.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use rtk::tracking::{RunContext, TimedExecution};
+    ///
+    /// let timer = TimedExecution::start();
+    /// timer.track_with("vitest run", "rtk vitest run", "raw", "filtered",
+    ///     RunContext::exited(1).with_tier(2));
+    /// ```
+    pub fn track_with(
+        &self,
+        original_cmd: &str,
+        rtk_cmd: &str,
+        input: &str,
+        output: &str,
+        context: RunContext,
+    ) {
+        let context = RunContext {
+            exec_time_ms: self.start.elapsed().as_millis() as u64,
+            ..context
+        };
+        let counter = crate::tokens::configured();
+        let input_tokens = counter.count(input);
+        let output_tokens = counter.count(output);
 
         if let Ok(tracker) = Tracker::new() {
             let _ = tracker.record(
@@ -797,7 +1322,8 @@ impl TimedExecution {
                 rtk_cmd,
                 input_tokens,
                 output_tokens,
-                elapsed_ms,
+                counter.name(),
+                &context,
             );
         }
     }
@@ -823,10 +1349,14 @@ impl TimedExecution {
     /// timer.track_passthrough("git tag", "rtk git tag");
     /// ```
     pub fn track_passthrough(&self, original_cmd: &str, rtk_cmd: &str) {
-        let elapsed_ms = self.start.elapsed().as_millis() as u64;
+        let context = RunContext {
+            exec_time_ms: self.start.elapsed().as_millis() as u64,
+            ..RunContext::current()
+        };
         // input_tokens=0, output_tokens=0 won't dilute savings statistics
         if let Ok(tracker) = Tracker::new() {
-            let _ = tracker.record(original_cmd, rtk_cmd, 0, 0, elapsed_ms);
+            let counter = crate::tokens::configured().name();
+            let _ = tracker.record(original_cmd, rtk_cmd, 0, 0, counter, &context);
         }
     }
 }
@@ -879,11 +1409,19 @@ pub fn args_display(args: &[OsString]) -> String {
 /// ```
 #[deprecated(note = "Use TimedExecution instead")]
 pub fn track(original_cmd: &str, rtk_cmd: &str, input: &str, output: &str) {
-    let input_tokens = estimate_tokens(input);
-    let output_tokens = estimate_tokens(output);
+    let counter = crate::tokens::configured();
+    let input_tokens = counter.count(input);
+    let output_tokens = counter.count(output);
 
     if let Ok(tracker) = Tracker::new() {
-        let _ = tracker.record(original_cmd, rtk_cmd, input_tokens, output_tokens, 0);
+        let _ = tracker.record(
+            original_cmd,
+            rtk_cmd,
+            input_tokens,
+            output_tokens,
+            counter.name(),
+            &RunContext::current(),
+        );
     }
 }
 
@@ -891,6 +1429,13 @@ pub fn track(original_cmd: &str, rtk_cmd: &str, input: &str, output: &str) {
 mod tests {
     use super::*;
 
+    fn timed(exec_time_ms: u64) -> RunContext {
+        RunContext {
+            exec_time_ms,
+            ..RunContext::default()
+        }
+    }
+
     // 1. estimate_tokens — verify ~4 chars/token ratio
     #[test]
     fn test_estimate_tokens() {
@@ -921,7 +1466,7 @@ mod tests {
         let test_cmd = format!("rtk git status test_{}", std::process::id());
 
         tracker
-            .record("git status", &test_cmd, 100, 20, 50)
+            .record("git status", &test_cmd, 100, 20, "heuristic", &timed(50))
             .expect("Failed to record");
 
         let recent = tracker.get_recent(10).expect("Failed to get recent");
@@ -948,12 +1493,12 @@ mod tests {
 
         // Record one real command with 80% savings
         tracker
-            .record("cmd1", &cmd1, 1000, 200, 10)
+            .record("cmd1", &cmd1, 1000, 200, "heuristic", &timed(10))
             .expect("Failed to record cmd1");
 
         // Record passthrough (0, 0)
         tracker
-            .record("cmd2", &cmd2, 0, 0, 5)
+            .record("cmd2", &cmd2, 0, 0, "heuristic", &timed(5))
             .expect("Failed to record passthrough");
 
         // Verify both records exist in recent history
@@ -1012,6 +1557,248 @@ mod tests {
         assert_eq!(pt.saved_tokens, 0);
     }
 
+    // Fixture schemas from before versioning (user_version = 0)
+    const FIXTURE_V0: &str = include_str!("fixtures/tracking_v0.sql");
+    const FIXTURE_EXEC_TIME: &str = include_str!("fixtures/tracking_exec_time.sql");
+
+    fn fixture_db(sql: &str) -> (tempfile::TempDir, PathBuf) {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("history.db");
+        Connection::open(&path).unwrap().execute_batch(sql).unwrap();
+        (dir, path)
+    }
+
+    fn columns(tracker: &Tracker) -> Vec<String> {
+        let mut stmt = tracker
+            .conn
+            .prepare("SELECT name FROM pragma_table_info('commands')")
+            .unwrap();
+        stmt.query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap()
+    }
+
+    // Migrations: fresh database lands on the latest version
+    #[test]
+    fn test_migrate_fresh_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = Tracker::open(&dir.path().join("new.db")).unwrap();
+
+        let info = tracker.db_info().unwrap();
+        assert_eq!(info.schema_version, SCHEMA_VERSION);
+        assert_eq!(info.rows, 0);
+        assert!(columns(&tracker).contains(&"token_counter".to_string()));
+    }
+
+    // Migrations: original schema (no exec_time_ms) upgrades with rows intact
+    #[test]
+    fn test_migrate_v0_fixture() {
+        let (_dir, path) = fixture_db(FIXTURE_V0);
+        let tracker = Tracker::open(&path).unwrap();
+
+        let info = tracker.db_info().unwrap();
+        assert_eq!(info.schema_version, SCHEMA_VERSION);
+        assert_eq!(info.rows, 3);
+
+        let summary = tracker.get_summary().unwrap();
+        assert_eq!(summary.total_saved, 1700);
+        assert_eq!(summary.total_time_ms, 0);
+        assert_eq!(summary.by_counter, vec![("heuristic".to_string(), 3)]);
+    }
+
+    // Migrations: unversioned database that already ran the old exec_time_ms ALTER
+    #[test]
+    fn test_migrate_unversioned_with_exec_time() {
+        let (_dir, path) = fixture_db(FIXTURE_EXEC_TIME);
+        let tracker = Tracker::open(&path).unwrap();
+
+        assert_eq!(tracker.db_info().unwrap().schema_version, SCHEMA_VERSION);
+        assert_eq!(tracker.get_summary().unwrap().total_time_ms, 350);
+
+        // Reopening is a no-op
+        drop(tracker);
+        let tracker = Tracker::open(&path).unwrap();
+        assert_eq!(tracker.db_info().unwrap().rows, 2);
+    }
+
+    // Migrations: refuse databases written by a newer rtk
+    #[test]
+    fn test_migrate_rejects_newer_schema() {
+        let (_dir, path) = fixture_db(&format!("PRAGMA user_version = {};", SCHEMA_VERSION + 1));
+        let err = Tracker::open(&path).err().unwrap();
+        assert!(err.to_string().contains("newer"));
+    }
+
+    #[test]
+    fn test_output_cache_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = Tracker::open(&dir.path().join("cache.db")).unwrap();
+        assert_eq!(tracker.last_output("git status", "/p").unwrap(), None);
+
+        let cached = |hash: &str| CachedOutput {
+            fingerprint: "index:1".to_string(),
+            output_hash: hash.to_string(),
+            timestamp: "2026-01-01T14:02:00+00:00".to_string(),
+        };
+        tracker
+            .record_output("git status", "/p", &cached("a"))
+            .unwrap();
+        tracker
+            .record_output("git status", "/p", &cached("b"))
+            .unwrap();
+        assert_eq!(
+            tracker.last_output("git status", "/p").unwrap(),
+            Some(cached("b"))
+        );
+        assert_eq!(tracker.last_output("git status", "/q").unwrap(), None);
+    }
+
+    #[test]
+    fn test_last_test_failures() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = Tracker::open(&dir.path().join("runs.db")).unwrap();
+        assert_eq!(
+            tracker.last_test_failures("/p", "cargo test").unwrap(),
+            None
+        );
+
+        let failing = vec!["a::b".to_string(), "c".to_string()];
+        tracker
+            .record_test_failures("/p", "cargo test", &failing)
+            .unwrap();
+        tracker
+            .record_test_failures("/p", "cargo test", &[])
+            .unwrap();
+        tracker
+            .record_test_failures("/q", "cargo test", &failing)
+            .unwrap();
+        assert_eq!(
+            tracker.last_test_failures("/p", "cargo test").unwrap(),
+            Some(vec![])
+        );
+        assert_eq!(
+            tracker.last_test_failures("/q", "cargo test").unwrap(),
+            Some(failing)
+        );
+        assert_eq!(tracker.last_test_failures("/q", "pytest").unwrap(), None);
+    }
+
+    // Run context: stored per row and filterable with Scope
+    #[test]
+    fn test_scope_project_and_failed_only() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scope.db");
+        let tracker = Tracker::open(&path).unwrap();
+
+        let ctx = |root: &str, exit_code: i32| RunContext {
+            exit_code: Some(exit_code),
+            cwd: Some(format!("{}/src", root)),
+            project_root: Some(root.to_string()),
+            filter_tier: Some(1),
+            exec_time_ms: 1,
+        };
+        let record = |rtk_cmd: &str, saved: usize, context: &RunContext| {
+            tracker
+                .record("cmd", rtk_cmd, saved * 2, saved, "heuristic", context)
+                .unwrap()
+        };
+        record("rtk cargo test", 100, &ctx("/work/api", 0));
+        record("rtk cargo test", 50, &ctx("/work/api", 101));
+        record("rtk vitest run", 30, &ctx("/work/web", 1));
+        record("rtk ls", 10, &RunContext::default());
+
+        assert_eq!(tracker.get_summary().unwrap().total_commands, 4);
+
+        let api = Tracker::open(&path).unwrap().with_scope(Scope {
+            project_root: Some("/work/api".to_string()),
+            failed_only: false,
+        });
+        let summary = api.get_summary().unwrap();
+        assert_eq!(summary.total_commands, 2);
+        assert_eq!(summary.total_saved, 150);
+
+        let failed = Tracker::open(&path).unwrap().with_scope(Scope {
+            project_root: None,
+            failed_only: true,
+        });
+        assert_eq!(failed.get_summary().unwrap().total_saved, 80);
+        assert_eq!(failed.get_recent(10).unwrap().len(), 2);
+
+        let api_failed = Tracker::open(&path).unwrap().with_scope(Scope {
+            project_root: Some("/work/api".to_string()),
+            failed_only: true,
+        });
+        assert_eq!(api_failed.get_all_days().unwrap()[0].commands, 1);
+    }
+
+    #[test]
+    fn test_get_by_project() {
+        let dir = tempfile::tempdir().unwrap();
+        let tracker = Tracker::open(&dir.path().join("projects.db")).unwrap();
+
+        let ctx = |root: Option<&str>, exit_code: i32| RunContext {
+            exit_code: Some(exit_code),
+            project_root: root.map(String::from),
+            exec_time_ms: 10,
+            ..RunContext::default()
+        };
+        let record = |rtk_cmd: &str, saved: usize, context: &RunContext| {
+            tracker
+                .record("cmd", rtk_cmd, saved * 2, saved, "heuristic", context)
+                .unwrap()
+        };
+        record("rtk cargo test", 100, &ctx(Some("/work/api"), 0));
+        record("rtk cargo test", 50, &ctx(Some("/work/api"), 1));
+        record("rtk git status", 200, &ctx(Some("/work/api"), 0));
+        record("rtk vitest run", 30, &ctx(Some("/work/web"), 0));
+        record("rtk ls", 10, &ctx(None, 0));
+
+        let projects = tracker.get_by_project().unwrap();
+        assert_eq!(projects.len(), 3);
+
+        let api = &projects[0];
+        assert_eq!(api.project.as_deref(), Some("/work/api"));
+        assert_eq!(api.commands, 3);
+        assert_eq!(api.saved_tokens, 350);
+        assert_eq!(api.input_tokens, 700);
+        assert!((api.savings_pct - 50.0).abs() < 0.01);
+        assert_eq!(api.avg_time_ms, 10);
+        assert_eq!(api.by_command.len(), 2);
+        assert_eq!(api.by_command[0].command, "rtk git status");
+        assert_eq!(api.by_command[1].commands, 2);
+        assert_eq!(api.by_command[1].saved_tokens, 150);
+
+        assert_eq!(projects[1].project.as_deref(), Some("/work/web"));
+        assert_eq!(projects[2].project, None);
+
+        // Scope still applies
+        let failed = Tracker::open(&dir.path().join("projects.db"))
+            .unwrap()
+            .with_scope(Scope {
+                project_root: None,
+                failed_only: true,
+            });
+        let projects = failed.get_by_project().unwrap();
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects[0].saved_tokens, 50);
+    }
+
+    #[test]
+    fn test_find_project_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let nested = dir.path().join("crates").join("core");
+        std::fs::create_dir_all(&nested).unwrap();
+        std::fs::create_dir(dir.path().join(".git")).unwrap();
+
+        assert_eq!(find_project_root(&nested), Some(dir.path().to_path_buf()));
+        // Worktrees have a .git file instead of a directory
+        let worktree = nested.join("wt");
+        std::fs::create_dir(&worktree).unwrap();
+        std::fs::write(worktree.join(".git"), "gitdir: /elsewhere").unwrap();
+        assert_eq!(find_project_root(&worktree), Some(worktree.clone()));
+    }
+
     // 7. get_db_path respects environment variable RTK_DB_PATH
     #[test]
     fn test_custom_db_path_env() {
@@ -1,3 +1,6 @@
+use crate::parser::{
+    render_with, truncate_output, LintIssue, LintResult, LintSeverity, OutputParser, ParseResult,
+};
 use crate::tracking;
 use crate::utils::truncate;
 use anyhow::{Context, Result};
@@ -5,6 +8,16 @@ use regex::Regex;
 use std::collections::HashMap;
 use std::process::Command;
 
+/// Longest message or context line shown before truncating
+const MAX_LINE_CHARS: usize = 120;
+
+lazy_static::lazy_static! {
+    // Pattern: src/file.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
+    static ref TSC_ERROR: Regex = Regex::new(
+        r"^(.+?)\((\d+),(\d+)\):\s+(error|warning)\s+(TS\d+):\s+(.+)$"
+    ).unwrap();
+}
+
 pub fn run(args: &[String], verbose: u8) -> Result<()> {
     let timer = tracking::TimedExecution::start();
 
@@ -32,42 +45,69 @@ pub fn run(args: &[String], verbose: u8) -> Result<()> {
         eprintln!("Running: {} {}", tool, args.join(" "));
     }
 
-    let output = cmd
-        .output()
+    let output = crate::stream::capture(&mut cmd)
         .context("Failed to run tsc (try: npm install -g typescript)")?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let raw = format!("{}\n{}", stdout, stderr);
-
-    let filtered = filter_tsc_output(&raw);
-
-    let exit_code = output.status.code().unwrap_or(1);
-    if let Some(hint) = crate::tee::tee_and_hint(&raw, "tsc", exit_code) {
-        println!("{}\n{}", filtered, hint);
-    } else {
-        println!("{}", filtered);
-    }
+    let raw = &output.raw;
+
+    let parse_result = TscParser::parse(raw);
+    let tier = parse_result.tier();
+    let filtered = output.annotate(&render_with("tsc", parse_result, verbose, || {
+        filter_tsc_output(raw)
+    }));
+
+    let exit_code = output.exit_code;
+    println!(
+        "{}",
+        crate::tee::tee_and_link(raw, "tsc", exit_code, &filtered)
+    );
 
-    timer.track(
+    timer.track_with(
         &format!("tsc {}", args.join(" ")),
         &format!("rtk tsc {}", args.join(" ")),
-        &raw,
+        raw,
         &filtered,
+        tracking::RunContext::exited(exit_code).with_tier(tier),
     );
 
     // Preserve tsc exit code for CI/CD compatibility
     std::process::exit(exit_code);
 }
 
-/// Filter TypeScript compiler output - group errors by file, show every error
-fn filter_tsc_output(output: &str) -> String {
-    lazy_static::lazy_static! {
-        // Pattern: src/file.ts(12,5): error TS2322: Type 'string' is not assignable to type 'number'.
-        static ref TSC_ERROR: Regex = Regex::new(
-            r"^(.+?)\((\d+),(\d+)\):\s+(error|warning)\s+(TS\d+):\s+(.+)$"
-        ).unwrap();
+/// Parser for tsc's `file(line,col): error TSxxxx: message` diagnostics
+pub struct TscParser;
+
+impl OutputParser for TscParser {
+    type Output = LintResult;
+
+    fn parse(input: &str) -> ParseResult<LintResult> {
+        let issues: Vec<LintIssue> = input
+            .lines()
+            .filter_map(|line| TSC_ERROR.captures(line))
+            .map(|caps| LintIssue {
+                file_path: caps[1].to_string(),
+                line: caps[2].parse().unwrap_or(0),
+                column: caps[3].parse().unwrap_or(0),
+                severity: if &caps[4] == "error" {
+                    LintSeverity::Error
+                } else {
+                    LintSeverity::Warning
+                },
+                rule_id: caps[5].to_string(),
+                message: caps[6].to_string(),
+            })
+            .collect();
+
+        // A clean run prints nothing (or "Found 0 errors" in watch mode)
+        if issues.is_empty() && !input.trim().is_empty() && !input.contains("Found 0 errors") {
+            return ParseResult::Passthrough(truncate_output(input, 500));
+        }
+
+        ParseResult::Full(LintResult::from_issues(issues))
     }
+}
 
+/// Filter TypeScript compiler output - group errors by file, show every error
+pub(crate) fn filter_tsc_output(output: &str) -> String {
     struct TsError {
         file: String,
         line: usize,
@@ -156,19 +196,37 @@ fn filter_tsc_output(output: &str) -> String {
     let mut files_sorted: Vec<_> = by_file.iter().collect();
     files_sorted.sort_by(|a, b| b.1.len().cmp(&a.1.len()));
 
-    // Show every error per file — no limits
+    // Show every error per file — no limits. When a line had to be truncated,
+    // the file header points at the untruncated lines in the tee file.
     for (file, file_errors) in &files_sorted {
-        result.push_str(&format!("{} ({} errors)\n", file, file_errors.len()));
-
-        for err in *file_errors {
+        let clipped = file_errors.iter().any(|err| {
+            err.message.chars().count() > MAX_LINE_CHARS
+                || err
+                    .context_lines
+                    .iter()
+                    .any(|ctx| ctx.chars().count() > MAX_LINE_CHARS)
+        });
+        let section = if clipped {
+            crate::tee::section_ref(&format!("tsc:{}", file))
+        } else {
+            String::new()
+        };
+        result.push_str(&format!(
+            "{} ({} errors){}\n",
+            file,
+            file_errors.len(),
+            section
+        ));
+
+        for err in file_errors.iter() {
             result.push_str(&format!(
                 "  L{}: {} {}\n",
                 err.line,
                 err.code,
-                truncate(&err.message, 120)
+                truncate(&err.message, MAX_LINE_CHARS)
             ));
             for ctx in &err.context_lines {
-                result.push_str(&format!("    {}\n", truncate(ctx, 120)));
+                result.push_str(&format!("    {}\n", truncate(ctx, MAX_LINE_CHARS)));
             }
         }
         result.push('\n');
@@ -177,6 +235,28 @@ fn filter_tsc_output(output: &str) -> String {
     result.trim().to_string()
 }
 
+/// Raw tsc lines for one file (errors plus their continuation lines), for
+/// `rtk tee show --section tsc:<file>`.
+pub fn extract_section(output: &str, file: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    let mut in_file = false;
+    for line in output.lines() {
+        if let Some(caps) = TSC_ERROR.captures(line) {
+            in_file = &caps[1] == file;
+        } else if !(line.starts_with("  ") || line.starts_with('\t')) || line.trim().is_empty() {
+            in_file = false;
+        }
+        if in_file {
+            lines.push(line);
+        }
+    }
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -250,6 +330,62 @@ src/app.tsx(20,5): error TS2345: Argument of type 'number' is not assignable to
         }
     }
 
+    #[test]
+    fn test_section_ref_only_for_truncated_files() {
+        let mut output = String::new();
+        for i in 1..=22 {
+            output.push_str(&format!(
+                "src/foo.ts({},1): error TS2322: Error {}.\n  context {}\n",
+                i, i, i
+            ));
+        }
+        output.push_str(&format!(
+            "src/foo.ts(30,1): error TS2322: Type '{}' is not assignable.\n",
+            "A".repeat(150)
+        ));
+        output.push_str("src/bar.ts(1,1): error TS2304: Cannot find name 'x'.\n");
+
+        let result = filter_tsc_output(&output);
+        assert!(result.contains(&format!(
+            "src/foo.ts (23 errors){}",
+            crate::tee::section_ref("tsc:src/foo.ts")
+        )));
+        assert!(result.contains("L11:"));
+        assert!(result.contains("L22:"));
+        assert!(result.contains("src/bar.ts (1 errors)\n"));
+        assert!(!result.contains("section tsc:src/bar.ts"));
+
+        let section = extract_section(&output, "src/foo.ts").unwrap();
+        assert_eq!(section.lines().count(), 45);
+        assert!(section.contains("Error 22."));
+        assert!(section.contains("  context 22"));
+        assert!(!section.contains("bar.ts"));
+        assert!(extract_section(&output, "src/missing.ts").is_none());
+    }
+
+    #[test]
+    fn test_tsc_parser() {
+        let output = "\
+src/api.ts(10,5): error TS2322: Type 'string' is not assignable to type 'number'.
+  Context line.
+src/app.tsx(3,1): error TS2304: Cannot find name 'x'.
+";
+        let result = TscParser::parse(output);
+        assert_eq!(result.tier(), 1);
+        let lint = result.unwrap();
+        assert_eq!(lint.errors, 2);
+        assert_eq!(lint.files_with_issues, 2);
+        assert_eq!(lint.issues[0].rule_id, "TS2322");
+        assert_eq!(lint.issues[0].line, 10);
+        assert_eq!(lint.issues[0].column, 5);
+
+        assert_eq!(TscParser::parse("\n").unwrap().total_issues, 0);
+        assert_eq!(
+            TscParser::parse("error TS5058: The specified path does not exist").tier(),
+            3
+        );
+    }
+
     #[test]
     fn test_filter_no_errors() {
         let output = "Found 0 errors. Watching for file changes.";
@@ -0,0 +1,332 @@
+//! Declarative output filters defined in config.toml.
+//!
+//! Each `[[filters.custom]]` entry matches a command line by regex and describes
+//! how to reduce its output, so tools without a built-in rtk filter (in-house
+//! build scripts, bazel wrappers, ...) can still be compacted via `rtk run`.
+//!
+//! ```toml
+//! [[filters.custom]]
+//! name = "bazel"
+//! command = "^bazel (build|test)"
+//! strip = ["^INFO: ", "^Loading:"]
+//! dedup = true
+//! tail = 40
+//! summary = "^(INFO: Build completed|FAILED:|ERROR:)"
+//! ```
+
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A user-defined filter as written in config.toml.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UserFilterConfig {
+    /// Short name, used for tee file names and tracking
+    pub name: String,
+    /// Regex matched against the full command line
+    pub command: String,
+    /// Drop lines matching any of these regexes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub strip: Vec<String>,
+    /// If set, keep only lines matching one of these regexes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keep: Vec<String>,
+    /// Collapse consecutive identical lines into one with a count
+    #[serde(default)]
+    pub dedup: bool,
+    /// Keep at most this many lines from the start
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub head: Option<usize>,
+    /// Keep at most this many lines from the end
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tail: Option<usize>,
+    /// Lines matching this regex are always shown, even if stripped or truncated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+/// A filter with its regexes compiled, ready to apply.
+#[derive(Debug)]
+pub struct UserFilter {
+    pub name: String,
+    command: Regex,
+    strip: Vec<Regex>,
+    keep: Vec<Regex>,
+    dedup: bool,
+    head: Option<usize>,
+    tail: Option<usize>,
+    summary: Option<Regex>,
+}
+
+fn compile(filter: &str, field: &str, pattern: &str) -> Result<Regex> {
+    Regex::new(pattern).with_context(|| {
+        format!(
+            "Invalid regex in filter '{}' ({}): {}",
+            filter, field, pattern
+        )
+    })
+}
+
+impl UserFilter {
+    pub fn compile(config: &UserFilterConfig) -> Result<Self> {
+        let name = &config.name;
+        Ok(Self {
+            name: name.clone(),
+            command: compile(name, "command", &config.command)?,
+            strip: config
+                .strip
+                .iter()
+                .map(|p| compile(name, "strip", p))
+                .collect::<Result<_>>()?,
+            keep: config
+                .keep
+                .iter()
+                .map(|p| compile(name, "keep", p))
+                .collect::<Result<_>>()?,
+            dedup: config.dedup,
+            head: config.head,
+            tail: config.tail,
+            summary: config
+                .summary
+                .as_deref()
+                .map(|p| compile(name, "summary", p))
+                .transpose()?,
+        })
+    }
+
+    pub fn matches(&self, command: &str) -> bool {
+        self.command.is_match(command)
+    }
+
+    /// Apply strip → keep → dedup → head/tail, then re-add any summary lines
+    /// that did not survive, keeping everything in output order.
+    pub fn apply(&self, output: &str) -> String {
+        let mut lines: Vec<Kept> = output
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !self.strip.iter().any(|re| re.is_match(line)))
+            .filter(|(_, line)| {
+                self.keep.is_empty() || self.keep.iter().any(|re| re.is_match(line))
+            })
+            .map(|(i, line)| Kept::line(i, line))
+            .collect();
+
+        if self.dedup {
+            lines = dedup_consecutive(&lines);
+        }
+
+        lines = truncate_lines(lines, self.head, self.tail);
+
+        if let Some(summary) = &self.summary {
+            for (i, line) in output.lines().enumerate() {
+                if summary.is_match(line) && !lines.iter().any(|kept| kept.sources.contains(&i)) {
+                    lines.push(Kept::line(i, line));
+                }
+            }
+            // Stable, so a cut marker stays ahead of the summary line it starts at
+            lines.sort_by_key(|kept| kept.at);
+        }
+
+        lines
+            .into_iter()
+            .map(|kept| kept.text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// An output line and the input lines it stands for: several after dedup,
+/// none for a cut marker.
+#[derive(Clone)]
+struct Kept {
+    /// Input line index the output line is ordered by
+    at: usize,
+    sources: Vec<usize>,
+    text: String,
+}
+
+impl Kept {
+    fn line(index: usize, text: &str) -> Self {
+        Self {
+            at: index,
+            sources: vec![index],
+            text: text.to_string(),
+        }
+    }
+}
+
+/// Compile every configured filter, failing on the first invalid one.
+pub fn load_filters(configs: &[UserFilterConfig]) -> Result<Vec<UserFilter>> {
+    configs.iter().map(UserFilter::compile).collect()
+}
+
+/// First filter (in config order) whose command pattern matches.
+pub fn find_filter<'a>(filters: &'a [UserFilter], command: &str) -> Option<&'a UserFilter> {
+    filters.iter().find(|f| f.matches(command))
+}
+
+fn dedup_consecutive(lines: &[Kept]) -> Vec<Kept> {
+    let mut result: Vec<Kept> = Vec::new();
+    let mut i = 0;
+    while i < lines.len() {
+        let mut count = 1;
+        while i + count < lines.len() && lines[i + count].text == lines[i].text {
+            count += 1;
+        }
+        if count > 1 {
+            let run = &lines[i..i + count];
+            result.push(Kept {
+                at: lines[i].at,
+                sources: run.iter().flat_map(|kept| kept.sources.clone()).collect(),
+                text: format!("{} [×{}]", lines[i].text, count),
+            });
+        } else {
+            result.push(lines[i].clone());
+        }
+        i += count;
+    }
+    result
+}
+
+fn truncate_lines(lines: Vec<Kept>, head: Option<usize>, tail: Option<usize>) -> Vec<Kept> {
+    let total = lines.len();
+    let (head, tail) = match (head, tail) {
+        (None, None) => return lines,
+        (Some(h), None) => (h, 0),
+        (None, Some(t)) => (0, t),
+        (Some(h), Some(t)) => (h, t),
+    };
+    if head + tail >= total {
+        return lines;
+    }
+
+    let omitted = total - head - tail;
+    let mut result: Vec<Kept> = lines[..head].to_vec();
+    result.push(Kept {
+        at: lines[head].at,
+        sources: Vec::new(),
+        text: format!("... +{} more lines", omitted),
+    });
+    result.extend_from_slice(&lines[total - tail..]);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn filter(toml_src: &str) -> UserFilter {
+        let config: UserFilterConfig = toml::from_str(toml_src).unwrap();
+        UserFilter::compile(&config).unwrap()
+    }
+
+    #[test]
+    fn test_strip_and_keep() {
+        let f = filter(
+            r#"
+            name = "build"
+            command = "^./build.sh"
+            strip = ["^DEBUG"]
+            keep = ["error", "warning", "DEBUG"]
+            "#,
+        );
+        let out = "DEBUG error detail\nerror: boom\ninfo: ok\nwarning: meh";
+        assert_eq!(f.apply(out), "error: boom\nwarning: meh");
+    }
+
+    #[test]
+    fn test_dedup() {
+        let f = filter("name = \"x\"\ncommand = \"x\"\ndedup = true");
+        assert_eq!(f.apply("a\na\na\nb\na"), "a [×3]\nb\na");
+
+        // A stripped summary line between duplicates is still restored
+        let f = filter(
+            "name = \"x\"\ncommand = \"x\"\ndedup = true\nstrip = [\"^Done\"]\nsummary = \"^Done\"",
+        );
+        assert_eq!(f.apply("a\nDone\na"), "a [×2]\nDone");
+    }
+
+    #[test]
+    fn test_head_tail() {
+        let f = filter("name = \"x\"\ncommand = \"x\"\nhead = 1\ntail = 2");
+        assert_eq!(f.apply("1\n2\n3\n4\n5"), "1\n... +2 more lines\n4\n5");
+        // Nothing to cut
+        assert_eq!(f.apply("1\n2\n3"), "1\n2\n3");
+    }
+
+    #[test]
+    fn test_summary_survives_filtering() {
+        let f = filter(
+            r#"
+            name = "bazel"
+            command = "^bazel"
+            strip = ["^INFO: "]
+            tail = 1
+            summary = "^INFO: Build completed"
+            "#,
+        );
+        let out = "INFO: Analyzed 3 targets\nline a\nline b\nINFO: Build completed successfully";
+        assert_eq!(
+            f.apply(out),
+            "... +1 more lines\nline b\nINFO: Build completed successfully"
+        );
+    }
+
+    #[test]
+    fn test_summary_lines_keep_output_order() {
+        let f = filter(
+            r#"
+            name = "make"
+            command = "^make"
+            head = 1
+            tail = 1
+            summary = "^make: \\*\\*\\*"
+            "#,
+        );
+        let out = "cc a.c\ncc b.c\nmake: *** [b.o] Error 1\ncc c.c\ncc d.c\nmake: leaving";
+        assert_eq!(
+            f.apply(out),
+            "cc a.c\n... +4 more lines\nmake: *** [b.o] Error 1\nmake: leaving"
+        );
+
+        // A summary line the keep patterns dropped goes back where it was
+        let f = filter(
+            r#"
+            name = "x"
+            command = "x"
+            keep = ["error"]
+            summary = "^Finished"
+            "#,
+        );
+        assert_eq!(
+            f.apply("error: a\nFinished in 2s\nok\nerror: b"),
+            "error: a\nFinished in 2s\nerror: b"
+        );
+    }
+
+    #[test]
+    fn test_find_filter_first_match_wins() {
+        let filters = load_filters(&[
+            toml::from_str("name = \"test\"\ncommand = \"^bazel test\"").unwrap(),
+            toml::from_str("name = \"any\"\ncommand = \"^bazel\"").unwrap(),
+        ])
+        .unwrap();
+        assert_eq!(
+            find_filter(&filters, "bazel test //...").unwrap().name,
+            "test"
+        );
+        assert_eq!(
+            find_filter(&filters, "bazel build //...").unwrap().name,
+            "any"
+        );
+        assert!(find_filter(&filters, "make").is_none());
+    }
+
+    #[test]
+    fn test_invalid_regex_names_filter() {
+        let config: UserFilterConfig =
+            toml::from_str("name = \"broken\"\ncommand = \"(unclosed\"").unwrap();
+        let err = UserFilter::compile(&config).unwrap_err();
+        assert!(err.to_string().contains("broken"));
+    }
+}
@@ -53,6 +53,8 @@ pub fn strip_ansi(text: &str) -> String {
 
 /// Exécute une commande et retourne stdout/stderr nettoyés.
 ///
+/// Les limites `--timeout` / `--max-output` s'appliquent (voir [`crate::stream::Limits`]).
+///
 /// # Arguments
 /// * `cmd` - Commande à exécuter (ex: "eslint")
 /// * `args` - Arguments de la commande
@@ -68,16 +70,10 @@ pub fn strip_ansi(text: &str) -> String {
 /// ```
 #[allow(dead_code)]
 pub fn execute_command(cmd: &str, args: &[&str]) -> Result<(String, String, i32)> {
-    let output = Command::new(cmd)
-        .args(args)
-        .output()
+    let output = crate::stream::capture(Command::new(cmd).args(args))
         .context(format!("Failed to execute {}", cmd))?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let exit_code = output.status.code().unwrap_or(-1);
-
-    Ok((stdout, stderr, exit_code))
+    Ok((output.stdout, output.stderr, output.exit_code))
 }
 
 /// Formate un nombre de tokens avec suffixes K/M pour lisibilité.
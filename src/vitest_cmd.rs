@@ -3,9 +3,10 @@ use regex::Regex;
 use serde::Deserialize;
 
 use crate::parser::{
-    emit_degradation_warning, emit_passthrough_warning, extract_json_object, truncate_output,
-    FormatMode, OutputParser, ParseResult, TestFailure, TestResult, TokenFormatter,
+    extract_json_object, render, truncate_output, OutputParser, ParseResult, TestFailure,
+    TestResult,
 };
+use crate::test_diff;
 use crate::tracking;
 use crate::utils::{package_manager_exec, strip_ansi};
 
@@ -232,42 +233,34 @@ fn run_vitest(args: &[String], verbose: u8) -> Result<()> {
         cmd.arg(arg);
     }
 
-    let output = cmd.output().context("Failed to run vitest")?;
-    let stdout = String::from_utf8_lossy(&output.stdout);
-    let stderr = String::from_utf8_lossy(&output.stderr);
-    let combined = format!("{}{}", stdout, stderr);
+    let output = crate::stream::capture(&mut cmd).context("Failed to run vitest")?;
+    let combined = format!("{}{}", output.stdout, output.stderr);
 
     // Parse output using VitestParser
-    let parse_result = VitestParser::parse(&stdout);
-    let mode = FormatMode::from_verbosity(verbose);
-
-    let filtered = match parse_result {
-        ParseResult::Full(data) => {
-            if verbose > 0 {
-                eprintln!("vitest run (Tier 1: Full JSON parse)");
-            }
-            data.format(mode)
-        }
-        ParseResult::Degraded(data, warnings) => {
-            if verbose > 0 {
-                emit_degradation_warning("vitest", &warnings.join(", "));
-            }
-            data.format(mode)
-        }
-        ParseResult::Passthrough(raw) => {
-            emit_passthrough_warning("vitest", "All parsing tiers failed");
-            raw
-        }
-    };
+    let parse_result = VitestParser::parse(&output.stdout);
+    let tier = parse_result.tier();
+    let current = parse_result.data().cloned();
+    let diff = test_diff::record(&format!("vitest run {}", args.join(" ")), current.as_ref());
+    let mut filtered = render("vitest run", parse_result, verbose);
+    if verbose == 0 && !crate::parser::json_output() {
+        filtered = test_diff::compact("vitest run", current.as_ref(), diff, filtered);
+    }
 
-    let exit_code = output.status.code().unwrap_or(1);
+    let filtered = output.annotate(&filtered);
+    let exit_code = output.exit_code;
     if let Some(hint) = crate::tee::tee_and_hint(&combined, "vitest_run", exit_code) {
         println!("{}\n{}", filtered, hint);
     } else {
         println!("{}", filtered);
     }
 
-    timer.track("vitest run", "rtk vitest run", &combined, &filtered);
+    timer.track_with(
+        "vitest run",
+        "rtk vitest run",
+        &combined,
+        &filtered,
+        tracking::RunContext::exited(exit_code).with_tier(tier),
+    );
 
     // Propagate original exit code
     std::process::exit(exit_code)